@@ -0,0 +1,35 @@
+//! 回放合成的访问序列，比较各淘汰策略的命中率。
+//!
+//! 运行：`cargo run --release --features bench --example cache_hit_ratio`
+
+use r_tool::cache::CachePolicy;
+use r_tool::cache::bench::{compare, mixed_trace, scan_trace, zipf_trace};
+
+fn main() {
+    let policies = [CachePolicy::Lru, CachePolicy::Lfu, CachePolicy::Fifo, CachePolicy::Arc, CachePolicy::TinyLfu];
+    let capacity = 1_000;
+
+    let zipf = zipf_trace(100_000, 1_000_000, 0.9, 42);
+    let mut looping = Vec::new();
+    for _ in 0..20 {
+        looping.extend(scan_trace(0, capacity * 3 / 2));
+    }
+    let traces = [
+        ("zipf(0.9)", zipf.clone()),
+        ("zipf + scan", mixed_trace(&zipf, 5_000, 2_000)),
+        ("loop", looping),
+    ];
+
+    print!("{:<14}", "trace");
+    for policy in &policies {
+        print!("{:>10}", format!("{:?}", policy));
+    }
+    println!();
+    for (name, trace) in &traces {
+        print!("{:<14}", name);
+        for (_, ratio) in compare(&policies, capacity, trace) {
+            print!("{:>9.2}%", ratio * 100.0);
+        }
+        println!();
+    }
+}
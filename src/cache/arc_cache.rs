@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::cache::Cache;
+use crate::cache::cache_core::{impl_cache, CacheCore, Policy};
+use crate::cache::linked_slab::{LinkedSlab, List};
+use crate::cache::snapshot::{self, Codec, SnapshotError, SnapshotKind};
+use crate::cache::stats::RemovalCause;
+
+/// 缓存项所在的链表，对应论文中的 T1、T2、B1、B2。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Segment {
+    // T1：只被访问过一次的项
+    Recent = 0,
+    // T2：被访问过至少两次的项
+    Frequent = 1,
+    // B1：最近从 T1 淘汰的幽灵项，只保留键
+    RecentGhost = 2,
+    // B2：最近从 T2 淘汰的幽灵项，只保留键
+    FrequentGhost = 3,
+}
+
+/// 幽灵项只保留键和权重。
+struct Ghost<K> {
+    key: K,
+    weight: usize,
+    segment: Segment,
+}
+
+/// 自适应替换缓存（Adaptive Replacement Cache）。
+///
+/// 同时维护“最近访问”和“频繁访问”两个 LRU 链表，以及记录最近被淘汰的键的两个幽灵链表。
+/// 幽灵项再次被写入时说明对应的链表分得太小，据此自动调整两个链表的目标大小，
+/// 因此一次性的顺序扫描不会冲掉被反复访问的热点数据。
+pub struct ARCCache<K, V> {
+    // 常驻项，缓存项上记录的是所在的链表
+    core: CacheCore<K, V, Segment>,
+    // 幽灵项的下标
+    ghosts: HashMap<K, usize>,
+    ghost_entries: LinkedSlab<Ghost<K>>,
+    // 按 Segment 取下标，每个链表头部为最久未使用；T1、T2 链接常驻项，B1、B2 链接幽灵项
+    lists: [List; 4],
+    segment_weights: [usize; 4],
+    // T1 的目标权重，幽灵项命中时自适应调整
+    target: usize,
+}
+
+impl<K, V> ARCCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone,
+{
+    /// 创建一个新的ARC缓存实例。
+    ///
+    /// # 参数
+    ///
+    /// * `capacity`: usize - 缓存的容量，幽灵项不占用容量
+    ///
+    /// 返回值：ARCCache<K, V> - 新创建的ARC缓存实例
+    pub fn new(capacity: usize) -> Self {
+        ARCCache {
+            core: CacheCore::new(capacity),
+            ghosts: HashMap::new(),
+            ghost_entries: LinkedSlab::with_capacity(capacity),
+            lists: [List::new(); 4],
+            segment_weights: [0; 4],
+            target: 0,
+        }
+    }
+
+    /// 创建一个按权重限制容量的ARC缓存实例，缓存项的权重由 `weigher` 计算，
+    /// 淘汰会持续到所有项的权重之和不超过 `max_weight`。
+    ///
+    /// 权重超过 `max_weight` 的项不会放入缓存，而是直接以 `RemovalCause::Capacity` 通知移除回调。
+    ///
+    /// # 参数
+    ///
+    /// * `max_weight`: usize - 最大总权重
+    /// * `weigher`: Fn(&K, &V) -> usize - 计算缓存项权重的函数
+    ///
+    /// 返回值：ARCCache<K, V> - 新创建的ARC缓存实例
+    pub fn with_weigher<F>(max_weight: usize, weigher: F) -> Self
+        where
+            F: Fn(&K, &V) -> usize + Send + Sync + 'static,
+    {
+        ARCCache { core: CacheCore::weighted(max_weight, Box::new(weigher)), ..ARCCache::new(0) }
+    }
+
+    /// 获取缓存中指定键的值，被访问的项移动到频繁访问链表的末尾。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要获取的键的引用
+    ///
+    /// 返回值：Option<&V> - 如果存在则返回对应值的引用，否则返回None
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.lookup(key)
+    }
+
+    /// 将键值对插入缓存中，缓存已满时按两个链表的目标大小选择淘汰最近访问或频繁访问链表中最久未使用的项。
+    ///
+    /// 如果键已存在，则替换值，并与 `get` 一样计为一次访问；如果键是幽灵项，则调整目标大小后
+    /// 直接放入频繁访问链表。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    ///
+    /// 返回值：Option<V> - 如果键已存在则返回被替换的旧值，否则返回None
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value, None)
+    }
+
+    /// 将键值对插入缓存中，并为该项单独指定存活时间。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    /// * `ttl`: Duration - 该项的存活时间，覆盖默认存活时间
+    ///
+    /// 返回值：Option<V> - 如果键已存在且未过期则返回被替换的旧值，否则返回None
+    pub fn put_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        self.insert(key, value, Some(ttl))
+    }
+
+    /// 淘汰常驻项，直到再放入 `incoming` 的权重也不超出最大总权重，`keep` 指向的项不会被淘汰。
+    fn make_room(&mut self, incoming: usize, keep: Option<usize>, frequent_ghost_hit: bool) {
+        while self.core.weights.overflows(incoming) {
+            let recent = self.segment_weights[Segment::Recent as usize];
+            // T1 超出目标大小时从 T1 淘汰，否则从 T2 淘汰
+            let prefer_recent = recent > 0 && (recent > self.target || (frequent_ghost_hit && recent == self.target));
+            let order = if prefer_recent {
+                [Segment::Recent, Segment::Frequent]
+            } else {
+                [Segment::Frequent, Segment::Recent]
+            };
+            let victim = order.iter().find_map(|&segment| {
+                self.core.entries.indices(&self.lists[segment as usize]).find(|&index| Some(index) != keep)
+            });
+            let Some(index) = victim else {
+                break;
+            };
+            self.evict_index(index);
+        }
+    }
+
+    /// 限制幽灵项的数量：T1 与 B1 之和、B1 与 B2 之和都不超过容量。
+    fn trim_ghosts(&mut self) {
+        let max_weight = self.core.weights.max_weight();
+        while self.segment_weights[Segment::Recent as usize] + self.segment_weights[Segment::RecentGhost as usize] > max_weight {
+            if !self.drop_oldest_ghost(Segment::RecentGhost) {
+                break;
+            }
+        }
+        while self.segment_weights[Segment::RecentGhost as usize] + self.segment_weights[Segment::FrequentGhost as usize] > max_weight {
+            if !self.drop_oldest_ghost(Segment::FrequentGhost) && !self.drop_oldest_ghost(Segment::RecentGhost) {
+                break;
+            }
+        }
+    }
+
+    fn drop_oldest_ghost(&mut self, segment: Segment) -> bool {
+        let oldest = self.ghost_entries.indices(&self.lists[segment as usize]).next();
+        match oldest {
+            Some(index) => {
+                self.remove_ghost(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 移除幽灵项，幽灵项没有值，不会通知移除回调。
+    fn remove_ghost(&mut self, index: usize) -> Ghost<K> {
+        let segment = self.ghost_entries.get(index).segment;
+        self.ghost_entries.unlink(&mut self.lists[segment as usize], index);
+        let ghost = self.ghost_entries.remove(index);
+        self.ghosts.remove(&ghost.key);
+        self.segment_weights[segment as usize] -= ghost.weight;
+        ghost
+    }
+
+    /// 将常驻项移动到指定链表的末尾。
+    fn relink(&mut self, index: usize, to: Segment) {
+        let entry = self.core.entries.get(index);
+        let (from, weight) = (entry.meta, entry.weight);
+        self.core.entries.unlink(&mut self.lists[from as usize], index);
+        self.segment_weights[from as usize] -= weight;
+        self.core.entries.push_back(&mut self.lists[to as usize], index);
+        self.segment_weights[to as usize] += weight;
+        self.core.entries.get_mut(index).meta = to;
+    }
+}
+
+impl<K, V> ARCCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone + Codec,
+        V: Codec,
+{
+    /// 将缓存中未过期的常驻项保存到快照文件，保留各项所在的链表、链表内的顺序、
+    /// T1 的目标大小和剩余的过期时间。幽灵项不会保存。
+    ///
+    /// 先写入同目录下的临时文件再重命名，保存中途失败不会破坏已有的快照。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 快照文件路径
+    ///
+    /// 返回值：Result<(), SnapshotError> - 写入失败时返回错误
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        let entries = self.snapshot_entries(|&segment| segment as u8);
+        snapshot::save(path.as_ref(), SnapshotKind::Arc, self.target as u64, &entries)
+    }
+
+    /// 从快照文件加载缓存项，替换缓存中现有的内容。
+    ///
+    /// 快照全部校验通过后才会修改缓存，加载失败时缓存保持不变。各项恢复到保存时所在的链表，
+    /// 过期时间按保存时的剩余时长重新计算，超出容量的项按淘汰策略淘汰，加载过程不计入统计信息。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 快照文件路径
+    ///
+    /// 返回值：Result<usize, SnapshotError> - 加载后缓存中的项数
+    pub fn load_from<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, SnapshotError> {
+        let (target, entries) = snapshot::load::<K, V, u8>(path.as_ref(), SnapshotKind::Arc)?;
+        Cache::clear(self);
+        for (key, value, remaining, segment) in entries {
+            let segment = if segment == Segment::Frequent as u8 { Segment::Frequent } else { Segment::Recent };
+            self.restore(key, value, remaining, segment);
+        }
+        self.target = usize::try_from(target).unwrap_or(usize::MAX).min(self.core.weights.max_weight());
+        self.make_room(0, None, false);
+        Ok(self.core.cache.len())
+    }
+}
+
+impl<K, V> Policy<K, V> for ARCCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone,
+{
+    type Meta = Segment;
+
+    fn core(&self) -> &CacheCore<K, V, Segment> {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut CacheCore<K, V, Segment> {
+        &mut self.core
+    }
+
+    fn link(&mut self, index: usize) {
+        let entry = self.core.entries.get(index);
+        let (segment, weight) = (entry.meta, entry.weight);
+        self.core.entries.push_back(&mut self.lists[segment as usize], index);
+        self.segment_weights[segment as usize] += weight;
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let entry = self.core.entries.get(index);
+        let (segment, weight) = (entry.meta, entry.weight);
+        self.core.entries.unlink(&mut self.lists[segment as usize], index);
+        self.segment_weights[segment as usize] -= weight;
+    }
+
+    /// 因容量不足淘汰常驻项，未过期的项转为对应的幽灵项。
+    fn evict_index(&mut self, index: usize) {
+        let cause = self.core.eviction_cause(index);
+        let entry = self.remove_index(index, cause);
+        if cause == RemovalCause::Expired {
+            return;
+        }
+        let segment = match entry.meta {
+            Segment::Recent => Segment::RecentGhost,
+            _ => Segment::FrequentGhost,
+        };
+        let ghost = self.ghost_entries.insert(Ghost { key: entry.key.clone(), weight: entry.weight, segment });
+        self.ghost_entries.push_back(&mut self.lists[segment as usize], ghost);
+        self.segment_weights[segment as usize] += entry.weight;
+        self.ghosts.insert(entry.key, ghost);
+    }
+
+    /// 被访问的项移动到频繁访问链表的末尾。
+    fn on_hit(&mut self, index: usize) {
+        self.relink(index, Segment::Frequent);
+    }
+
+    fn on_update(&mut self, index: usize, old_weight: usize) {
+        let entry = self.core.entries.get(index);
+        let (segment, weight) = (entry.meta, entry.weight);
+        self.segment_weights[segment as usize] = self.segment_weights[segment as usize] - old_weight + weight;
+        self.relink(index, Segment::Frequent);
+        // 新值更重时可能需要淘汰其他项
+        self.make_room(0, Some(index), false);
+        self.trim_ghosts();
+    }
+
+    /// 键是幽灵项时按命中的幽灵链表调整 T1 的目标大小，新项直接放入频繁访问链表。
+    fn make_room_for(&mut self, key: &K, weight: usize) -> Segment {
+        let recent_ghosts = self.segment_weights[Segment::RecentGhost as usize];
+        let frequent_ghosts = self.segment_weights[Segment::FrequentGhost as usize];
+        let ghost = self.ghosts.get(key).copied().map(|index| self.remove_ghost(index).segment);
+        match ghost {
+            Some(Segment::RecentGhost) => {
+                // B1 命中：T1 分得太小，增大 T1 的目标大小
+                let delta = weight.max(1).saturating_mul((frequent_ghosts / recent_ghosts.max(1)).max(1));
+                self.target = self.target.saturating_add(delta).min(self.core.weights.max_weight());
+                self.make_room(weight, None, false);
+                Segment::Frequent
+            }
+            Some(_) => {
+                // B2 命中：T2 分得太小，减小 T1 的目标大小
+                let delta = weight.max(1).saturating_mul((recent_ghosts / frequent_ghosts.max(1)).max(1));
+                self.target = self.target.saturating_sub(delta);
+                self.make_room(weight, None, true);
+                Segment::Frequent
+            }
+            None => {
+                self.make_room(weight, None, false);
+                Segment::Recent
+            }
+        }
+    }
+
+    fn after_insert(&mut self, _index: usize) {
+        self.trim_ghosts();
+    }
+
+    /// 先最近访问链表，再频繁访问链表，链表内从最久未使用到最近使用。
+    fn order(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(
+            [Segment::Recent, Segment::Frequent]
+                .into_iter()
+                .flat_map(move |segment| self.core.entries.indices(&self.lists[segment as usize])),
+        )
+    }
+
+    fn reset(&mut self) {
+        self.ghosts.clear();
+        self.ghost_entries.clear();
+        self.lists = [List::new(); 4];
+        self.segment_weights = [0; 4];
+        self.target = 0;
+    }
+
+    fn forget(&mut self, key: &K) {
+        if let Some(&index) = self.ghosts.get(key) {
+            self.remove_ghost(index);
+        }
+    }
+}
+
+impl_cache!(ARCCache);
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::cache::expiry::ManualClock;
+
+    #[test]
+    fn test_arc_cache() {
+        let mut arc_cache = ARCCache::new(3);
+
+        arc_cache.put("one", 1);
+        arc_cache.put("two", 2);
+        arc_cache.put("three", 3);
+        // 被访问过两次的 "one" 进入频繁访问链表
+        assert_eq!(arc_cache.get(&"one"), Some(&1));
+
+        arc_cache.put("four", 4);
+        assert_eq!(arc_cache.len(), 3);
+        assert_eq!(arc_cache.get(&"two"), None);
+        assert_eq!(arc_cache.peek(&"one"), Some(&1));
+
+        // "two" 成为幽灵项，再次写入时增大最近访问链表的目标大小，并直接进入频繁访问链表
+        assert_eq!(arc_cache.target, 0);
+        assert_eq!(arc_cache.put("two", 20), None);
+        assert_eq!(arc_cache.target, 1);
+        assert_eq!(arc_cache.len(), 3);
+        let keys: Vec<_> = arc_cache.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec!["four", "one", "two"]);
+
+        assert_eq!(arc_cache.put("one", 10), Some(1));
+        assert_eq!(arc_cache.remove(&"one"), Some(10));
+        assert_eq!(arc_cache.remove(&"three"), None);
+        assert_eq!(arc_cache.len(), 2);
+    }
+
+    #[test]
+    fn test_arc_cache_scan_resistance() {
+        let mut arc_cache = ARCCache::new(4);
+
+        for key in [1000, 1001] {
+            arc_cache.put(key, key);
+            arc_cache.get(&key);
+        }
+        // 只访问一次的顺序扫描只会在最近访问链表中轮换
+        for key in 0..100 {
+            if arc_cache.get(&key).is_none() {
+                arc_cache.put(key, key);
+            }
+        }
+
+        assert_eq!(arc_cache.get(&1000), Some(&1000));
+        assert_eq!(arc_cache.get(&1001), Some(&1001));
+        assert_eq!(arc_cache.len(), 4);
+        // 幽灵项不超过容量
+        assert!(arc_cache.len() + arc_cache.ghosts.len() <= 8);
+    }
+
+    #[test]
+    fn test_arc_cache_snapshot() {
+        let path = std::env::temp_dir().join(format!("r-tool-arc-{}.snapshot", std::process::id()));
+        let mut arc_cache = ARCCache::new(4);
+        for key in 0..4u32 {
+            arc_cache.put(key, key.to_string());
+        }
+        arc_cache.get(&2);
+        arc_cache.get(&3);
+        arc_cache.put(4, "4".to_string());
+        arc_cache.put(5, "5".to_string());
+        // 写入刚被淘汰的键命中幽灵项，T1 的目标大小随之增大
+        arc_cache.put(0, "0".to_string());
+        assert!(arc_cache.target > 0);
+        arc_cache.save_to(&path).unwrap();
+
+        let mut restored = ARCCache::new(4);
+        assert_eq!(restored.load_from(&path).unwrap(), 4);
+        assert_eq!(restored.target, arc_cache.target);
+        assert_eq!(restored.iter().collect::<Vec<_>>(), arc_cache.iter().collect::<Vec<_>>());
+        let segment = |cache: &ARCCache<u32, String>, key| cache.core.entries.get(cache.core.cache[&key]).meta;
+        assert_eq!(segment(&restored, 0), Segment::Frequent);
+        assert_eq!(segment(&restored, 5), Segment::Recent);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_arc_cache_expiry_and_weight() {
+        let clock = ManualClock::new();
+        let mut arc_cache = ARCCache::with_weigher(10, |_: &&str, v: &String| v.len())
+            .with_ttl(Duration::from_secs(10))
+            .with_clock(Arc::new(clock.clone()));
+
+        arc_cache.put("one", "aaaa".to_string());
+        arc_cache.put_with_ttl("two", "bbbb".to_string(), Duration::from_secs(1));
+        assert_eq!(arc_cache.put("big", "c".repeat(11)), None);
+        assert_eq!(arc_cache.total_weight(), 8);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(arc_cache.get(&"two"), None);
+        assert_eq!(arc_cache.total_weight(), 4);
+
+        arc_cache.put("three", "ccccc".to_string());
+        arc_cache.put("four", "ddd".to_string());
+        assert_eq!(arc_cache.peek(&"one"), None);
+        assert_eq!(arc_cache.total_weight(), 8);
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(arc_cache.purge_expired(), 2);
+        assert!(arc_cache.is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::cache::{Cache, CachePolicy};
+
+/// 生成服从 Zipf 分布的访问序列，键越小越热门。
+///
+/// # 参数
+///
+/// * `key_count`: u64 - 不同键的个数
+/// * `length`: usize - 序列长度
+/// * `exponent`: f64 - 分布的指数，越大访问越集中，常用0.8到1.2
+/// * `seed`: u64 - 随机数种子，相同种子生成相同的序列
+///
+/// 返回值：Vec<u64> - 访问的键序列
+pub fn zipf_trace(key_count: u64, length: usize, exponent: f64, seed: u64) -> Vec<u64> {
+    let mut cumulative = Vec::with_capacity(key_count as usize);
+    let mut total = 0.0;
+    for rank in 1..=key_count {
+        total += 1.0 / (rank as f64).powf(exponent);
+        cumulative.push(total);
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..length)
+        .map(|_| {
+            let point = rng.gen::<f64>() * total;
+            cumulative.partition_point(|&weight| weight < point).min(cumulative.len() - 1) as u64
+        })
+        .collect()
+}
+
+/// 生成顺序扫描序列，每个键只访问一次。
+///
+/// # 参数
+///
+/// * `start`: u64 - 第一个键
+/// * `length`: usize - 序列长度
+///
+/// 返回值：Vec<u64> - 访问的键序列
+pub fn scan_trace(start: u64, length: usize) -> Vec<u64> {
+    (start..start + length as u64).collect()
+}
+
+/// 在热点访问序列中周期性地插入顺序扫描，模拟批处理任务与在线请求混合的负载。
+///
+/// # 参数
+///
+/// * `hot`: &[u64] - 热点访问序列，例如 `zipf_trace` 的结果
+/// * `every`: usize - 每隔多少次热点访问插入一次扫描
+/// * `scan_length`: usize - 每次扫描的长度，扫描的键与热点的键不重叠
+///
+/// 返回值：Vec<u64> - 混合后的键序列
+pub fn mixed_trace(hot: &[u64], every: usize, scan_length: usize) -> Vec<u64> {
+    let mut next_scan = u64::MAX / 2;
+    let mut trace = Vec::with_capacity(hot.len() + hot.len() / every.max(1) * scan_length);
+    for chunk in hot.chunks(every.max(1)) {
+        trace.extend_from_slice(chunk);
+        trace.extend(scan_trace(next_scan, scan_length));
+        next_scan += scan_length as u64;
+    }
+    trace
+}
+
+/// 按序列回放访问：命中则计数，未命中则写入缓存。
+///
+/// # 参数
+///
+/// * `cache`: &mut C - 被测试的缓存
+/// * `trace`: &[u64] - 访问的键序列
+///
+/// 返回值：f64 - 命中率，序列为空时返回0
+pub fn replay<C: Cache<u64, u64> + ?Sized>(cache: &mut C, trace: &[u64]) -> f64 {
+    let mut hits = 0usize;
+    for &key in trace {
+        if cache.get(&key).is_some() {
+            hits += 1;
+        } else {
+            cache.put(key, key);
+        }
+    }
+    if trace.is_empty() {
+        0.0
+    } else {
+        hits as f64 / trace.len() as f64
+    }
+}
+
+/// 用同一个序列分别回放各淘汰策略，返回每种策略的命中率。
+///
+/// # 参数
+///
+/// * `policies`: &[CachePolicy] - 要比较的淘汰策略
+/// * `capacity`: usize - 缓存容量
+/// * `trace`: &[u64] - 访问的键序列
+///
+/// 返回值：Vec<(CachePolicy, f64)> - 各策略及其命中率
+pub fn compare(policies: &[CachePolicy], capacity: usize, trace: &[u64]) -> Vec<(CachePolicy, f64)> {
+    policies
+        .iter()
+        .map(|&policy| {
+            let mut cache = policy.build::<u64, u64>(capacity);
+            (policy, replay(cache.as_mut(), trace))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traces() {
+        let trace = zipf_trace(1000, 10_000, 1.0, 7);
+        assert_eq!(trace, zipf_trace(1000, 10_000, 1.0, 7));
+        assert!(trace.iter().all(|&key| key < 1000));
+        // 排名第一的键远比排名靠后的键热门
+        let count = |key| trace.iter().filter(|&&k| k == key).count();
+        assert!(count(0) > 10 * count(500));
+
+        let mixed = mixed_trace(&[1, 2, 3, 4], 2, 3);
+        assert_eq!(mixed.len(), 10);
+        assert_eq!(&mixed[..2], &[1, 2]);
+        assert_eq!(mixed[2] + 1, mixed[3]);
+    }
+
+    #[test]
+    fn test_compare_hit_ratios() {
+        let hot = zipf_trace(5_000, 50_000, 0.9, 42);
+        let trace = mixed_trace(&hot, 1_000, 500);
+        let results = compare(&[CachePolicy::Lru, CachePolicy::Arc, CachePolicy::TinyLfu], 500, &trace);
+        let ratio = |policy| results.iter().find(|(p, _)| *p == policy).unwrap().1;
+
+        // 扫描会冲掉 LRU 中的热点，ARC 和 W-TinyLFU 不受影响
+        assert!(ratio(CachePolicy::Arc) > ratio(CachePolicy::Lru));
+        assert!(ratio(CachePolicy::TinyLfu) > ratio(CachePolicy::Lru));
+    }
+}
@@ -0,0 +1,548 @@
+use std::fmt;
+
+use crate::cache::snapshot::Codec;
+use crate::secure::secure::SecureUtil;
+
+/// 序列化后的 `BloomFilter` 开头的魔数。
+const BLOOM_MAGIC: &[u8; 4] = b"RTBF";
+
+/// 序列化后的 `CountingBloomFilter` 开头的魔数。
+const COUNTING_MAGIC: &[u8; 4] = b"RTCB";
+
+/// 计数器的最大值，达到后不再增减，避免删除导致误判为不存在。
+const MAX_COUNT: u8 = u8::MAX;
+
+/// 合并过滤器或从字节恢复过滤器时的错误。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BloomFilterError {
+    /// 两个过滤器的位数或哈希函数个数不同，无法合并
+    Incompatible,
+    /// 字节数据不是对应类型的过滤器或已损坏
+    InvalidFormat,
+}
+
+impl fmt::Display for BloomFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BloomFilterError::Incompatible => write!(f, "布隆过滤器的位数或哈希函数个数不同"),
+            BloomFilterError::InvalidFormat => write!(f, "不是有效的布隆过滤器数据"),
+        }
+    }
+}
+
+impl std::error::Error for BloomFilterError {}
+
+/// 按预计元素个数和目标误判率计算位数与哈希函数个数。
+fn optimal_size(expected_items: usize, false_positive_rate: f64) -> (usize, u32) {
+    let items = expected_items.max(1) as f64;
+    let rate = false_positive_rate.clamp(f64::MIN_POSITIVE, 0.5);
+    let ln2 = std::f64::consts::LN_2;
+    let bit_count = (-items * rate.ln() / (ln2 * ln2)).ceil().max(1.0) as usize;
+    let hash_count = ((bit_count as f64 / items) * ln2).round().clamp(1.0, 32.0) as u32;
+    (bit_count, hash_count)
+}
+
+/// 计算元素对应的各个位置。
+///
+/// 使用 MD5 摘要的前后两半作为两个独立的哈希值，按 `h1 + i * h2` 组合出 `hash_count` 个位置，
+/// 结果与进程和平台无关，序列化后的过滤器可以在其他进程中继续使用。
+fn positions(item: &[u8], hash_count: u32, slot_count: usize) -> impl Iterator<Item = usize> {
+    let digest = SecureUtil::md5_bytes(item);
+    let h1 = u64::from_le_bytes(digest[..8].try_into().unwrap());
+    // 第二个哈希值为奇数，避免所有位置落在同一处
+    let h2 = u64::from_le_bytes(digest[8..].try_into().unwrap()) | 1;
+    let slot_count = slot_count as u64;
+    (0..hash_count as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % slot_count) as usize)
+}
+
+/// 写入过滤器的公共头部：魔数、哈希函数个数和位数。
+fn encode_header(magic: &[u8; 4], hash_count: u32, slot_count: usize, out: &mut Vec<u8>) {
+    out.extend_from_slice(magic);
+    hash_count.encode(out);
+    slot_count.encode(out);
+}
+
+/// 读取并校验过滤器的公共头部，返回哈希函数个数、位数和剩余的数据。
+fn decode_header<'a>(magic: &[u8; 4], bytes: &'a [u8]) -> Result<(u32, usize, &'a [u8]), BloomFilterError> {
+    let mut input = bytes.strip_prefix(magic.as_slice()).ok_or(BloomFilterError::InvalidFormat)?;
+    let hash_count = u32::decode(&mut input).ok_or(BloomFilterError::InvalidFormat)?;
+    let slot_count = usize::decode(&mut input).ok_or(BloomFilterError::InvalidFormat)?;
+    if hash_count == 0 || slot_count == 0 {
+        return Err(BloomFilterError::InvalidFormat);
+    }
+    Ok((hash_count, slot_count, input))
+}
+
+/// 布隆过滤器，以很小的空间判断元素是否可能存在。
+///
+/// 判断为不存在的元素一定没有插入过，判断为存在的元素有一定概率是误判，
+/// 适合放在缓存或存储前面，过滤掉一定会未命中的昂贵查询。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    bit_count: usize,
+    hash_count: u32,
+}
+
+impl BloomFilter {
+    /// 按预计元素个数和目标误判率创建布隆过滤器，自动计算位数和哈希函数个数。
+    ///
+    /// # 参数
+    ///
+    /// * `expected_items`: usize - 预计插入的元素个数
+    /// * `false_positive_rate`: f64 - 插入 `expected_items` 个元素后的目标误判率，例如0.01
+    ///
+    /// 返回值：BloomFilter - 新创建的布隆过滤器
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let (bit_count, hash_count) = optimal_size(expected_items, false_positive_rate);
+        BloomFilter::with_size(bit_count, hash_count)
+    }
+
+    /// 按指定的位数和哈希函数个数创建布隆过滤器。
+    ///
+    /// # 参数
+    ///
+    /// * `bit_count`: usize - 位数，至少为1
+    /// * `hash_count`: u32 - 哈希函数个数，至少为1
+    ///
+    /// 返回值：BloomFilter - 新创建的布隆过滤器
+    pub fn with_size(bit_count: usize, hash_count: u32) -> Self {
+        let bit_count = bit_count.max(1);
+        BloomFilter {
+            bits: vec![0; bit_count.div_ceil(64)],
+            bit_count,
+            hash_count: hash_count.max(1),
+        }
+    }
+
+    /// 插入元素。
+    ///
+    /// # 参数
+    ///
+    /// * `item`: &T - 要插入的元素，例如字符串或字节数组
+    ///
+    /// 返回值：bool - 元素此前判断为不存在时返回true
+    pub fn insert<T: AsRef<[u8]> + ?Sized>(&mut self, item: &T) -> bool {
+        let mut inserted = false;
+        for position in positions(item.as_ref(), self.hash_count, self.bit_count) {
+            let (word, mask) = (position / 64, 1u64 << (position % 64));
+            inserted |= self.bits[word] & mask == 0;
+            self.bits[word] |= mask;
+        }
+        inserted
+    }
+
+    /// 判断元素是否可能存在。
+    ///
+    /// # 参数
+    ///
+    /// * `item`: &T - 要判断的元素
+    ///
+    /// 返回值：bool - 返回false时元素一定不存在，返回true时元素可能存在
+    pub fn contains<T: AsRef<[u8]> + ?Sized>(&self, item: &T) -> bool {
+        positions(item.as_ref(), self.hash_count, self.bit_count)
+            .all(|position| self.bits[position / 64] & (1 << (position % 64)) != 0)
+    }
+
+    /// 返回位数。
+    pub fn bit_count(&self) -> usize {
+        self.bit_count
+    }
+
+    /// 返回哈希函数个数。
+    pub fn hash_count(&self) -> u32 {
+        self.hash_count
+    }
+
+    /// 按当前被置位的比例估算误判率。
+    pub fn false_positive_rate(&self) -> f64 {
+        let ones: u64 = self.bits.iter().map(|word| word.count_ones() as u64).sum();
+        (ones as f64 / self.bit_count as f64).powi(self.hash_count as i32)
+    }
+
+    /// 判断是否没有插入过任何元素。
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&word| word == 0)
+    }
+
+    /// 清空所有元素。
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+
+    /// 合并另一个过滤器，之后任一过滤器中可能存在的元素在本过滤器中都判断为可能存在。
+    ///
+    /// # 参数
+    ///
+    /// * `other`: &BloomFilter - 位数和哈希函数个数相同的过滤器
+    ///
+    /// 返回值：Result<(), BloomFilterError> - 两个过滤器不兼容时返回错误，本过滤器保持不变
+    pub fn union(&mut self, other: &BloomFilter) -> Result<(), BloomFilterError> {
+        self.check_compatible(other)?;
+        for (word, other) in self.bits.iter_mut().zip(&other.bits) {
+            *word |= other;
+        }
+        Ok(())
+    }
+
+    /// 与另一个过滤器求交集，之后只有两个过滤器中都可能存在的元素才判断为可能存在。
+    ///
+    /// 交集的误判率高于直接插入两者共同元素得到的过滤器。
+    ///
+    /// # 参数
+    ///
+    /// * `other`: &BloomFilter - 位数和哈希函数个数相同的过滤器
+    ///
+    /// 返回值：Result<(), BloomFilterError> - 两个过滤器不兼容时返回错误，本过滤器保持不变
+    pub fn intersect(&mut self, other: &BloomFilter) -> Result<(), BloomFilterError> {
+        self.check_compatible(other)?;
+        for (word, other) in self.bits.iter_mut().zip(&other.bits) {
+            *word &= other;
+        }
+        Ok(())
+    }
+
+    /// 序列化为字节，可以保存到文件或在进程间传递。
+    ///
+    /// 返回值：Vec<u8> - 序列化后的字节
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(16 + self.bits.len() * 8);
+        encode_header(BLOOM_MAGIC, self.hash_count, self.bit_count, &mut out);
+        for word in &self.bits {
+            word.encode(&mut out);
+        }
+        out
+    }
+
+    /// 从 `to_bytes` 的结果恢复布隆过滤器。
+    ///
+    /// # 参数
+    ///
+    /// * `bytes`: &[u8] - 序列化后的字节
+    ///
+    /// 返回值：Result<BloomFilter, BloomFilterError> - 数据不完整或格式不正确时返回错误
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BloomFilterError> {
+        let (hash_count, bit_count, mut input) = decode_header(BLOOM_MAGIC, bytes)?;
+        if input.len() != bit_count.div_ceil(64) * 8 {
+            return Err(BloomFilterError::InvalidFormat);
+        }
+        let mut bits = Vec::with_capacity(input.len() / 8);
+        while let Some(word) = u64::decode(&mut input) {
+            bits.push(word);
+        }
+        // 最后一个字中超出位数的部分必须为0，否则数据已损坏
+        let used = bit_count % 64;
+        if used != 0 && bits.last().is_some_and(|&word| word >> used != 0) {
+            return Err(BloomFilterError::InvalidFormat);
+        }
+        Ok(BloomFilter { bits, bit_count, hash_count })
+    }
+
+    fn check_compatible(&self, other: &BloomFilter) -> Result<(), BloomFilterError> {
+        if self.bit_count == other.bit_count && self.hash_count == other.hash_count {
+            Ok(())
+        } else {
+            Err(BloomFilterError::Incompatible)
+        }
+    }
+}
+
+/// 计数布隆过滤器，每一位换成一个计数器，因此支持删除元素。
+///
+/// 计数器为8位，达到上限后不再增减，以免删除其他元素时把它减为0而产生漏判。
+/// 占用空间是相同参数的 `BloomFilter` 的8倍。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountingBloomFilter {
+    counters: Vec<u8>,
+    hash_count: u32,
+}
+
+impl CountingBloomFilter {
+    /// 按预计元素个数和目标误判率创建计数布隆过滤器，自动计算计数器个数和哈希函数个数。
+    ///
+    /// # 参数
+    ///
+    /// * `expected_items`: usize - 预计同时存在的元素个数
+    /// * `false_positive_rate`: f64 - 存在 `expected_items` 个元素时的目标误判率，例如0.01
+    ///
+    /// 返回值：CountingBloomFilter - 新创建的计数布隆过滤器
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let (counter_count, hash_count) = optimal_size(expected_items, false_positive_rate);
+        CountingBloomFilter::with_size(counter_count, hash_count)
+    }
+
+    /// 按指定的计数器个数和哈希函数个数创建计数布隆过滤器。
+    ///
+    /// # 参数
+    ///
+    /// * `counter_count`: usize - 计数器个数，至少为1
+    /// * `hash_count`: u32 - 哈希函数个数，至少为1
+    ///
+    /// 返回值：CountingBloomFilter - 新创建的计数布隆过滤器
+    pub fn with_size(counter_count: usize, hash_count: u32) -> Self {
+        CountingBloomFilter {
+            counters: vec![0; counter_count.max(1)],
+            hash_count: hash_count.max(1),
+        }
+    }
+
+    /// 插入元素，同一个元素可以插入多次。
+    ///
+    /// # 参数
+    ///
+    /// * `item`: &T - 要插入的元素
+    pub fn insert<T: AsRef<[u8]> + ?Sized>(&mut self, item: &T) {
+        for position in positions(item.as_ref(), self.hash_count, self.counters.len()) {
+            let counter = &mut self.counters[position];
+            *counter = counter.saturating_add(1);
+        }
+    }
+
+    /// 删除一次插入的元素。
+    ///
+    /// 只能删除确实插入过的元素，删除从未插入的元素可能导致其他元素被误判为不存在。
+    ///
+    /// # 参数
+    ///
+    /// * `item`: &T - 要删除的元素
+    ///
+    /// 返回值：bool - 元素判断为不存在时不做任何修改并返回false
+    pub fn remove<T: AsRef<[u8]> + ?Sized>(&mut self, item: &T) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+        for position in positions(item.as_ref(), self.hash_count, self.counters.len()) {
+            let counter = &mut self.counters[position];
+            if *counter < MAX_COUNT {
+                *counter -= 1;
+            }
+        }
+        true
+    }
+
+    /// 判断元素是否可能存在。
+    ///
+    /// # 参数
+    ///
+    /// * `item`: &T - 要判断的元素
+    ///
+    /// 返回值：bool - 返回false时元素一定不存在，返回true时元素可能存在
+    pub fn contains<T: AsRef<[u8]> + ?Sized>(&self, item: &T) -> bool {
+        self.count(item) > 0
+    }
+
+    /// 估算元素被插入的次数，只会高估不会低估。
+    ///
+    /// # 参数
+    ///
+    /// * `item`: &T - 要估算的元素
+    ///
+    /// 返回值：u8 - 估算的次数，达到255后不再增加
+    pub fn count<T: AsRef<[u8]> + ?Sized>(&self, item: &T) -> u8 {
+        positions(item.as_ref(), self.hash_count, self.counters.len())
+            .map(|position| self.counters[position])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// 返回计数器个数。
+    pub fn counter_count(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// 返回哈希函数个数。
+    pub fn hash_count(&self) -> u32 {
+        self.hash_count
+    }
+
+    /// 判断是否没有任何元素。
+    pub fn is_empty(&self) -> bool {
+        self.counters.iter().all(|&counter| counter == 0)
+    }
+
+    /// 清空所有元素。
+    pub fn clear(&mut self) {
+        self.counters.fill(0);
+    }
+
+    /// 合并另一个过滤器，对应的计数器相加。
+    ///
+    /// # 参数
+    ///
+    /// * `other`: &CountingBloomFilter - 计数器个数和哈希函数个数相同的过滤器
+    ///
+    /// 返回值：Result<(), BloomFilterError> - 两个过滤器不兼容时返回错误，本过滤器保持不变
+    pub fn union(&mut self, other: &CountingBloomFilter) -> Result<(), BloomFilterError> {
+        self.check_compatible(other)?;
+        for (counter, other) in self.counters.iter_mut().zip(&other.counters) {
+            *counter = counter.saturating_add(*other);
+        }
+        Ok(())
+    }
+
+    /// 与另一个过滤器求交集，对应的计数器取较小值。
+    ///
+    /// # 参数
+    ///
+    /// * `other`: &CountingBloomFilter - 计数器个数和哈希函数个数相同的过滤器
+    ///
+    /// 返回值：Result<(), BloomFilterError> - 两个过滤器不兼容时返回错误，本过滤器保持不变
+    pub fn intersect(&mut self, other: &CountingBloomFilter) -> Result<(), BloomFilterError> {
+        self.check_compatible(other)?;
+        for (counter, other) in self.counters.iter_mut().zip(&other.counters) {
+            *counter = (*counter).min(*other);
+        }
+        Ok(())
+    }
+
+    /// 转换为参数相同的 `BloomFilter`，计数不为0的位置被置位，可用于节省空间地发布过滤器。
+    ///
+    /// 返回值：BloomFilter - 判断结果与本过滤器相同的布隆过滤器
+    pub fn to_bloom_filter(&self) -> BloomFilter {
+        let mut filter = BloomFilter::with_size(self.counters.len(), self.hash_count);
+        for (position, _) in self.counters.iter().enumerate().filter(|(_, &counter)| counter > 0) {
+            filter.bits[position / 64] |= 1 << (position % 64);
+        }
+        filter
+    }
+
+    /// 序列化为字节，可以保存到文件或在进程间传递。
+    ///
+    /// 返回值：Vec<u8> - 序列化后的字节
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(16 + self.counters.len());
+        encode_header(COUNTING_MAGIC, self.hash_count, self.counters.len(), &mut out);
+        out.extend_from_slice(&self.counters);
+        out
+    }
+
+    /// 从 `to_bytes` 的结果恢复计数布隆过滤器。
+    ///
+    /// # 参数
+    ///
+    /// * `bytes`: &[u8] - 序列化后的字节
+    ///
+    /// 返回值：Result<CountingBloomFilter, BloomFilterError> - 数据不完整或格式不正确时返回错误
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BloomFilterError> {
+        let (hash_count, counter_count, input) = decode_header(COUNTING_MAGIC, bytes)?;
+        if input.len() != counter_count {
+            return Err(BloomFilterError::InvalidFormat);
+        }
+        Ok(CountingBloomFilter { counters: input.to_vec(), hash_count })
+    }
+
+    fn check_compatible(&self, other: &CountingBloomFilter) -> Result<(), BloomFilterError> {
+        if self.counters.len() == other.counters.len() && self.hash_count == other.hash_count {
+            Ok(())
+        } else {
+            Err(BloomFilterError::Incompatible)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom_filter() {
+        let mut filter = BloomFilter::new(1000, 0.01);
+        assert_eq!(filter.hash_count(), 7);
+        assert!(filter.is_empty());
+        assert!(filter.insert("key-0"));
+        assert!(!filter.insert("key-0"));
+        for i in 1..1000 {
+            filter.insert(&format!("key-{}", i));
+        }
+        // 插入过的元素不会漏判
+        assert!((0..1000).all(|i| filter.contains(&format!("key-{}", i))));
+
+        // 实际误判率接近目标值
+        let false_positives = (0..10_000).filter(|i| filter.contains(&format!("other-{}", i))).count();
+        assert!(false_positives < 200, "false positives: {}", false_positives);
+        assert!((filter.false_positive_rate() - 0.01).abs() < 0.005);
+
+        filter.clear();
+        assert!(!filter.contains("key-0"));
+    }
+
+    #[test]
+    fn test_bloom_filter_union_and_intersection() {
+        let mut left = BloomFilter::new(100, 0.01);
+        let mut right = BloomFilter::new(100, 0.01);
+        left.insert("a");
+        left.insert("shared");
+        right.insert("b");
+        right.insert("shared");
+
+        let mut intersection = left.clone();
+        intersection.intersect(&right).unwrap();
+        assert!(intersection.contains("shared"));
+        assert!(!intersection.contains("a"));
+
+        left.union(&right).unwrap();
+        assert!(["a", "b", "shared"].iter().all(|item| left.contains(item)));
+
+        let other = BloomFilter::new(1000, 0.01);
+        assert_eq!(left.union(&other), Err(BloomFilterError::Incompatible));
+    }
+
+    #[test]
+    fn test_bloom_filter_bytes() {
+        let mut filter = BloomFilter::new(100, 0.01);
+        filter.insert("hello");
+        let bytes = filter.to_bytes();
+        let restored = BloomFilter::from_bytes(&bytes).unwrap();
+        assert_eq!(restored, filter);
+        assert!(restored.contains("hello"));
+
+        assert_eq!(BloomFilter::from_bytes(&bytes[..bytes.len() - 1]), Err(BloomFilterError::InvalidFormat));
+        // 末尾的填充位不为0
+        assert_ne!(filter.bit_count % 64, 0);
+        let mut padded = bytes.clone();
+        let last = padded.len() - 1;
+        padded[last] |= 0x80;
+        assert_eq!(BloomFilter::from_bytes(&padded), Err(BloomFilterError::InvalidFormat));
+        assert_eq!(BloomFilter::from_bytes(b"RTCB"), Err(BloomFilterError::InvalidFormat));
+        let counting = CountingBloomFilter::new(100, 0.01);
+        assert_eq!(BloomFilter::from_bytes(&counting.to_bytes()), Err(BloomFilterError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_counting_bloom_filter() {
+        let mut filter = CountingBloomFilter::new(100, 0.01);
+        filter.insert("a");
+        filter.insert("a");
+        filter.insert("b");
+        assert_eq!(filter.count("a"), 2);
+        assert!(filter.contains("b"));
+
+        assert!(filter.remove("b"));
+        assert!(!filter.contains("b"));
+        assert!(!filter.remove("b"));
+        assert!(filter.remove("a"));
+        assert!(filter.contains("a"));
+        assert!(filter.remove("a"));
+        assert!(filter.is_empty());
+
+        // 计数器饱和后不再减少，不会产生漏判
+        for _ in 0..300 {
+            filter.insert("hot");
+        }
+        for _ in 0..300 {
+            filter.remove("hot");
+        }
+        assert!(filter.contains("hot"));
+
+        let mut other = CountingBloomFilter::new(100, 0.01);
+        other.insert("c");
+        filter.union(&other).unwrap();
+        assert!(filter.contains("c"));
+        filter.intersect(&other).unwrap();
+        assert!(filter.contains("c") && !filter.contains("hot"));
+        assert!(filter.to_bloom_filter().contains("c"));
+
+        let restored = CountingBloomFilter::from_bytes(&filter.to_bytes()).unwrap();
+        assert_eq!(restored, filter);
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Duration;
+
+use crate::cache::expiry::{Deadline, Expiry, Remaining};
+use crate::cache::linked_slab::LinkedSlab;
+use crate::cache::snapshot::SnapshotEntry;
+use crate::cache::stats::{Recorder, RemovalCause};
+use crate::cache::weigher::{Weigher, Weights};
+
+/// 缓存项，`meta` 保存淘汰策略自己的信息，例如访问频率或所在的链表。
+pub(crate) struct Entry<K, V, M> {
+    pub(crate) key: K,
+    pub(crate) value: V,
+    pub(crate) deadline: Deadline,
+    pub(crate) weight: usize,
+    pub(crate) meta: M,
+}
+
+/// 各淘汰策略共用的存储：键到下标的索引、缓存项、总权重、过期时间和统计信息。
+///
+/// 缓存项的链接关系由淘汰策略维护，这里只负责缓存项本身的增删和记录。
+pub(crate) struct CacheCore<K, V, M> {
+    pub(crate) weights: Weights<K, V>,
+    pub(crate) cache: HashMap<K, usize>,
+    pub(crate) entries: LinkedSlab<Entry<K, V, M>>,
+    pub(crate) expiry: Expiry,
+    pub(crate) recorder: Recorder<K, V>,
+}
+
+impl<K, V, M> CacheCore<K, V, M>
+    where
+        K: Eq + Hash + Clone,
+{
+    pub(crate) fn new(capacity: usize) -> Self {
+        CacheCore {
+            weights: Weights::new(capacity),
+            cache: HashMap::with_capacity(capacity),
+            entries: LinkedSlab::with_capacity(capacity),
+            expiry: Expiry::new(),
+            recorder: Recorder::new(),
+        }
+    }
+
+    pub(crate) fn weighted(max_weight: usize, weigher: Weigher<K, V>) -> Self {
+        CacheCore { weights: Weights::weighted(max_weight, weigher), ..CacheCore::new(0) }
+    }
+
+    pub(crate) fn is_expired(&self, index: usize) -> bool {
+        self.expiry.is_expired(&self.entries.get(index).deadline)
+    }
+
+    /// 返回未过期项的下标。
+    pub(crate) fn live_index(&self, key: &K) -> Option<usize> {
+        let index = *self.cache.get(key)?;
+        (!self.is_expired(index)).then_some(index)
+    }
+
+    pub(crate) fn peek(&self, key: &K) -> Option<&V> {
+        self.live_index(key).map(|index| &self.entries.get(index).value)
+    }
+
+    /// 因容量不足淘汰时的移除原因，已过期的项按过期记录。
+    pub(crate) fn eviction_cause(&self, index: usize) -> RemovalCause {
+        if self.is_expired(index) {
+            RemovalCause::Expired
+        } else {
+            RemovalCause::Capacity
+        }
+    }
+
+    /// 放入一个新项并返回其下标，调用方负责把它链接到淘汰顺序中。
+    pub(crate) fn attach(&mut self, entry: Entry<K, V, M>) -> usize {
+        let key = entry.key.clone();
+        let weight = entry.weight;
+        let index = self.entries.insert(entry);
+        self.cache.insert(key, index);
+        self.weights.add(weight);
+        index
+    }
+
+    /// 释放一个已经从淘汰顺序中摘除的项，并通知移除回调。
+    pub(crate) fn detach(&mut self, index: usize, cause: RemovalCause) -> Entry<K, V, M> {
+        let entry = self.entries.remove(index);
+        self.cache.remove(&entry.key);
+        self.weights.sub(entry.weight);
+        self.recorder.record_removal(&entry.key, &entry.value, cause);
+        entry
+    }
+
+    /// 替换已有项的值、过期时间和权重，计为一次写入，旧值以 `RemovalCause::Replaced` 通知移除回调。
+    ///
+    /// 返回值：(V, usize) - 旧值和旧权重
+    pub(crate) fn replace(&mut self, index: usize, value: V, deadline: Deadline, weight: usize) -> (V, usize) {
+        let entry = self.entries.get_mut(index);
+        entry.deadline = deadline;
+        let old_weight = std::mem::replace(&mut entry.weight, weight);
+        let old_value = std::mem::replace(&mut entry.value, value);
+        self.weights.sub(old_weight);
+        self.weights.add(weight);
+        self.recorder.record_insertion();
+        self.recorder.record_removal(&entry.key, &old_value, RemovalCause::Replaced);
+        (old_value, old_weight)
+    }
+}
+
+/// 淘汰策略：在 `CacheCore` 之上维护缓存项的淘汰顺序。
+///
+/// 查找、写入、移除、过期清理、遍历和快照恢复的流程由默认方法实现，
+/// 各策略只需要维护自己的链表并决定淘汰哪些项。
+pub(crate) trait Policy<K, V>
+    where
+        K: Eq + Hash + Clone,
+{
+    /// 每个缓存项上由策略使用的信息。
+    type Meta;
+
+    fn core(&self) -> &CacheCore<K, V, Self::Meta>;
+
+    fn core_mut(&mut self) -> &mut CacheCore<K, V, Self::Meta>;
+
+    /// 将刚放入的项按其 `meta` 链接到淘汰顺序中。
+    fn link(&mut self, index: usize);
+
+    /// 将项从淘汰顺序中摘除。
+    fn unlink(&mut self, index: usize);
+
+    /// 处理一次 `get` 命中。
+    fn on_hit(&mut self, index: usize);
+
+    /// 已有项的值被替换后调用：按写入计为一次访问，并按新的权重淘汰其他项，`index` 指向的项不会被淘汰。
+    fn on_update(&mut self, index: usize, old_weight: usize);
+
+    /// 为即将写入的新键腾出空间，返回新项的策略信息。
+    fn make_room_for(&mut self, key: &K, weight: usize) -> Self::Meta;
+
+    /// 新项链接到淘汰顺序之后调用。
+    fn after_insert(&mut self, _index: usize) {}
+
+    /// 按淘汰顺序返回所有项的下标，最先被淘汰的在前。
+    fn order(&self) -> Box<dyn Iterator<Item = usize> + '_>;
+
+    /// 缓存被清空后重置策略的状态。
+    fn reset(&mut self);
+
+    /// 键被显式移除时，丢弃策略为它保存的其他信息，默认什么也不做。
+    fn forget(&mut self, _key: &K) {}
+
+    fn remove_index(&mut self, index: usize, cause: RemovalCause) -> Entry<K, V, Self::Meta> {
+        self.unlink(index);
+        self.core_mut().detach(index, cause)
+    }
+
+    /// 因容量不足淘汰项。
+    fn evict_index(&mut self, index: usize) {
+        let cause = self.core().eviction_cause(index);
+        self.remove_index(index, cause);
+    }
+
+    /// 查找键并记录命中或未命中，已过期的项在此时被清理，命中的项顺延空闲时间。
+    fn lookup<'a>(&'a mut self, key: &K) -> Option<&'a V>
+        where
+            K: 'a,
+            V: 'a,
+            Self::Meta: 'a,
+    {
+        let Some(&index) = self.core().cache.get(key) else {
+            self.core_mut().recorder.record_miss();
+            return None;
+        };
+        if self.core().is_expired(index) {
+            self.remove_index(index, RemovalCause::Expired);
+            self.core_mut().recorder.record_miss();
+            return None;
+        }
+        self.core_mut().recorder.record_hit();
+        self.on_hit(index);
+        let core = self.core_mut();
+        let entry = core.entries.get_mut(index);
+        core.expiry.touch(&mut entry.deadline);
+        Some(&entry.value)
+    }
+
+    /// 写入键值对，`ttl` 为空时使用默认存活时间。
+    fn insert(&mut self, key: K, value: V, ttl: Option<Duration>) -> Option<V> {
+        let core = self.core();
+        let deadline = core.expiry.deadline(ttl);
+        let weight = core.weights.weigh(&key, &value);
+        if let Some(&index) = core.cache.get(&key) {
+            if core.is_expired(index) {
+                // 已过期的项视为不存在，按新项重新插入
+                self.remove_index(index, RemovalCause::Expired);
+            } else if !core.weights.admits(weight) {
+                // 新值放不进缓存，旧值也不再保留
+                let old_value = self.remove_index(index, RemovalCause::Replaced).value;
+                self.core_mut().recorder.record_removal(&key, &value, RemovalCause::Capacity);
+                return Some(old_value);
+            } else {
+                let (old_value, old_weight) = self.core_mut().replace(index, value, deadline, weight);
+                self.on_update(index, old_weight);
+                return Some(old_value);
+            }
+        }
+
+        if !self.core().weights.admits(weight) {
+            // 比最大总权重还重的项直接拒绝，不为它清空整个缓存
+            self.core_mut().recorder.record_removal(&key, &value, RemovalCause::Capacity);
+            return None;
+        }
+        let meta = self.make_room_for(&key, weight);
+        let core = self.core_mut();
+        let index = core.attach(Entry { key, value, deadline, weight, meta });
+        core.recorder.record_insertion();
+        self.link(index);
+        self.after_insert(index);
+        None
+    }
+
+    /// 移除指定键，已过期的项按过期通知移除回调并返回None。
+    fn remove_key(&mut self, key: &K) -> Option<V> {
+        self.forget(key);
+        let index = *self.core().cache.get(key)?;
+        let expired = self.core().is_expired(index);
+        let cause = if expired { RemovalCause::Expired } else { RemovalCause::Explicit };
+        let entry = self.remove_index(index, cause);
+        (!expired).then_some(entry.value)
+    }
+
+    /// 清理所有已过期的项，返回被清理的项数。
+    fn purge(&mut self) -> usize {
+        let core = self.core();
+        let expired: Vec<usize> = core.cache.values().copied().filter(|&index| core.is_expired(index)).collect();
+        for &index in &expired {
+            self.remove_index(index, RemovalCause::Expired);
+        }
+        expired.len()
+    }
+
+    /// 按淘汰顺序逐项通知移除回调后清空缓存。
+    fn clear_all(&mut self) {
+        let order: Vec<usize> = self.order().collect();
+        let core = self.core_mut();
+        for index in order {
+            let entry = core.entries.get(index);
+            let cause = if core.expiry.is_expired(&entry.deadline) { RemovalCause::Expired } else { RemovalCause::Explicit };
+            core.recorder.record_removal(&entry.key, &entry.value, cause);
+        }
+        core.cache.clear();
+        core.entries.clear();
+        core.weights.clear();
+        self.reset();
+    }
+
+    /// 按淘汰顺序遍历未过期的项。
+    fn live_entries(&self) -> Box<dyn Iterator<Item = &Entry<K, V, Self::Meta>> + '_> {
+        let core = self.core();
+        Box::new(
+            self.order()
+                .map(move |index| core.entries.get(index))
+                .filter(move |entry| !core.expiry.is_expired(&entry.deadline)),
+        )
+    }
+
+    /// 按淘汰顺序收集要写入快照的项，`meta` 给出各项在快照中保存的策略信息。
+    fn snapshot_entries<S>(&self, meta: impl Fn(&Self::Meta) -> S) -> Vec<SnapshotEntry<&K, &V, S>> {
+        let expiry = &self.core().expiry;
+        self.live_entries()
+            .map(|entry| (&entry.key, &entry.value, expiry.remaining(&entry.deadline), meta(&entry.meta)))
+            .collect()
+    }
+
+    /// 放入快照中的一项，不计入统计信息，重复、已过期或过重的项被丢弃。
+    ///
+    /// 返回值：Option<usize> - 放入的项的下标
+    fn restore(&mut self, key: K, value: V, remaining: Remaining, meta: Self::Meta) -> Option<usize> {
+        let core = self.core_mut();
+        let deadline = core.expiry.restore(remaining);
+        let weight = core.weights.weigh(&key, &value);
+        if core.cache.contains_key(&key) || core.expiry.is_expired(&deadline) || !core.weights.admits(weight) {
+            return None;
+        }
+        let index = core.attach(Entry { key, value, deadline, weight, meta });
+        self.link(index);
+        Some(index)
+    }
+}
+
+/// 为基于 `CacheCore` 的缓存生成公共的构造选项、统计方法和 `Cache` 实现。
+macro_rules! impl_cache {
+    ($name:ident) => {
+        impl<K, V> $name<K, V>
+            where
+                K: Eq + std::hash::Hash + Clone,
+        {
+            /// 设置缓存项的默认存活时间（TTL），写入后超过该时间的项视为过期。
+            ///
+            /// # 参数
+            ///
+            /// * `ttl`: Duration - 默认存活时间
+            pub fn with_ttl(mut self, ttl: std::time::Duration) -> Self {
+                self.core.expiry.set_time_to_live(ttl);
+                self
+            }
+
+            /// 设置缓存项的空闲时间（TTI），超过该时间未被访问的项视为过期，每次访问都会顺延。
+            ///
+            /// # 参数
+            ///
+            /// * `tti`: Duration - 空闲时间
+            pub fn with_time_to_idle(mut self, tti: std::time::Duration) -> Self {
+                self.core.expiry.set_time_to_idle(tti);
+                self
+            }
+
+            /// 替换计算过期时间所用的时钟，测试中可以传入 `ManualClock`。
+            ///
+            /// # 参数
+            ///
+            /// * `clock`: Arc<dyn Clock> - 时钟
+            pub fn with_clock(mut self, clock: std::sync::Arc<dyn $crate::cache::expiry::Clock>) -> Self {
+                self.core.expiry.set_clock(clock);
+                self
+            }
+
+            /// 开启统计信息记录，之后可以通过 `stats` 获取命中率等统计信息。
+            pub fn with_stats(mut self) -> Self {
+                self.core.recorder.enable_stats();
+                self
+            }
+
+            /// 注册缓存项被移除时的回调，回调会收到被移除的键、值和移除原因，
+            /// 可用于把被淘汰的项写回存储。
+            ///
+            /// # 参数
+            ///
+            /// * `listener`: Fn(&K, &V, RemovalCause) - 移除回调
+            pub fn with_removal_listener<F>(mut self, listener: F) -> Self
+                where
+                    F: Fn(&K, &V, $crate::cache::stats::RemovalCause) + Send + Sync + 'static,
+            {
+                self.core.recorder.set_listener(Box::new(listener));
+                self
+            }
+
+            /// 返回统计信息快照，未开启统计时各项均为0。
+            pub fn stats(&self) -> $crate::cache::stats::CacheStats {
+                self.core.recorder.snapshot()
+            }
+
+            /// 返回缓存中所有项的权重之和，未设置权重函数时等于项数。
+            pub fn total_weight(&self) -> usize {
+                self.core.weights.total_weight()
+            }
+
+            /// 清理所有已过期的项。
+            ///
+            /// 返回值：usize - 被清理的项数
+            pub fn purge_expired(&mut self) -> usize {
+                $crate::cache::cache_core::Policy::purge(self)
+            }
+        }
+
+        impl<K, V> $crate::cache::Cache<K, V> for $name<K, V>
+            where
+                K: Eq + std::hash::Hash + Clone,
+        {
+            fn get(&mut self, key: &K) -> Option<&V> {
+                $name::get(self, key)
+            }
+
+            fn peek(&self, key: &K) -> Option<&V> {
+                self.core.peek(key)
+            }
+
+            fn put(&mut self, key: K, value: V) -> Option<V> {
+                $name::put(self, key, value)
+            }
+
+            fn put_with_ttl(&mut self, key: K, value: V, ttl: std::time::Duration) -> Option<V> {
+                $name::put_with_ttl(self, key, value, ttl)
+            }
+
+            fn remove(&mut self, key: &K) -> Option<V> {
+                $crate::cache::cache_core::Policy::remove_key(self, key)
+            }
+
+            fn purge_expired(&mut self) -> usize {
+                $name::purge_expired(self)
+            }
+
+            fn stats(&self) -> $crate::cache::stats::CacheStats {
+                $name::stats(self)
+            }
+
+            fn contains_key(&self, key: &K) -> bool {
+                self.core.live_index(key).is_some()
+            }
+
+            fn len(&self) -> usize {
+                self.core.cache.len()
+            }
+
+            fn clear(&mut self) {
+                $crate::cache::cache_core::Policy::clear_all(self)
+            }
+
+            fn capacity(&self) -> usize {
+                self.core.weights.max_weight()
+            }
+
+            /// 按淘汰顺序遍历，最先被淘汰的项在前，跳过已过期的项。
+            fn iter(&self) -> Box<dyn Iterator<Item = (&K, &V)> + '_> {
+                Box::new($crate::cache::cache_core::Policy::live_entries(self).map(|entry| (&entry.key, &entry.value)))
+            }
+        }
+    };
+}
+
+pub(crate) use impl_cache;
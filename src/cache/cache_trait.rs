@@ -0,0 +1,248 @@
+use std::hash::Hash;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::cache::arc_cache::ARCCache;
+use crate::cache::fifo_cache::FIFOCache;
+use crate::cache::lfu_cache::LFUCache;
+use crate::cache::lru_cache::LRUCache;
+use crate::cache::stats::CacheStats;
+use crate::cache::tiny_lfu_cache::TinyLFUCache;
+
+/// 各淘汰策略缓存的公共接口，便于业务代码对具体策略保持泛型。
+pub trait Cache<K, V> {
+    /// 获取指定键的值，是否更新淘汰顺序由具体策略决定，已过期的项会在此时被清理。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要获取的键的引用
+    ///
+    /// 返回值：Option<&V> - 如果存在则返回对应值的引用，否则返回None
+    fn get(&mut self, key: &K) -> Option<&V>;
+
+    /// 查看指定键的值，不影响任何淘汰顺序。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要查看的键的引用
+    ///
+    /// 返回值：Option<&V> - 如果存在则返回对应值的引用，否则返回None
+    fn peek(&self, key: &K) -> Option<&V>;
+
+    /// 将键值对插入缓存中，缓存已满时按具体策略淘汰。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    ///
+    /// 返回值：Option<V> - 如果键已存在则返回被替换的旧值，否则返回None
+    fn put(&mut self, key: K, value: V) -> Option<V>;
+
+    /// 将键值对插入缓存中，并为该项单独指定存活时间，覆盖缓存的默认存活时间。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    /// * `ttl`: Duration - 该项的存活时间
+    ///
+    /// 返回值：Option<V> - 如果键已存在且未过期则返回被替换的旧值，否则返回None
+    fn put_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V>;
+
+    /// 从缓存中移除指定键。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要移除的键的引用
+    ///
+    /// 返回值：Option<V> - 如果存在则返回被移除的值，否则返回None
+    fn remove(&mut self, key: &K) -> Option<V>;
+
+    /// 清理所有已过期的项。
+    ///
+    /// 返回值：usize - 被清理的项数
+    fn purge_expired(&mut self) -> usize;
+
+    /// 返回统计信息快照，未开启统计时各项均为0。
+    fn stats(&self) -> CacheStats;
+
+    /// 判断缓存中是否存在指定键，不影响任何淘汰顺序。
+    fn contains_key(&self, key: &K) -> bool;
+
+    /// 返回缓存中当前的项数，包括已过期但尚未被清理的项。
+    fn len(&self) -> usize;
+
+    /// 判断缓存是否为空。
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 清空缓存中的所有项。
+    fn clear(&mut self);
+
+    /// 返回缓存的容量，按权重限制容量时为最大总权重。
+    fn capacity(&self) -> usize;
+
+    /// 遍历缓存中的所有键值对，不影响任何淘汰顺序，遍历顺序由具体策略决定。
+    fn iter(&self) -> Box<dyn Iterator<Item = (&K, &V)> + '_>;
+}
+
+impl<K, V, C> Cache<K, V> for Box<C>
+    where
+        C: Cache<K, V> + ?Sized,
+{
+    fn get(&mut self, key: &K) -> Option<&V> {
+        (**self).get(key)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        (**self).peek(key)
+    }
+
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        (**self).put(key, value)
+    }
+
+    fn put_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        (**self).put_with_ttl(key, value, ttl)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        (**self).remove(key)
+    }
+
+    fn purge_expired(&mut self) -> usize {
+        (**self).purge_expired()
+    }
+
+    fn stats(&self) -> CacheStats {
+        (**self).stats()
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        (**self).contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    fn is_empty(&self) -> bool {
+        (**self).is_empty()
+    }
+
+    fn clear(&mut self) {
+        (**self).clear()
+    }
+
+    fn capacity(&self) -> usize {
+        (**self).capacity()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (&K, &V)> + '_> {
+        (**self).iter()
+    }
+}
+
+/// 缓存淘汰策略，可从配置字符串（"lru"、"lfu"、"fifo"、"arc"、"tinylfu"）解析。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    Lru,
+    Lfu,
+    Fifo,
+    /// 自适应替换缓存
+    Arc,
+    /// W-TinyLFU
+    TinyLfu,
+}
+
+impl CachePolicy {
+    /// 按当前策略创建一个缓存实例。
+    ///
+    /// # 参数
+    ///
+    /// * `capacity`: usize - 缓存的容量
+    ///
+    /// 返回值：Box<dyn Cache<K, V> + Send> - 新创建的缓存实例
+    pub fn build<K, V>(self, capacity: usize) -> Box<dyn Cache<K, V> + Send>
+        where
+            K: Eq + Hash + Clone + Send + 'static,
+            V: Send + 'static,
+    {
+        match self {
+            CachePolicy::Lru => Box::new(LRUCache::new(capacity)),
+            CachePolicy::Lfu => Box::new(LFUCache::new(capacity)),
+            CachePolicy::Fifo => Box::new(FIFOCache::new(capacity)),
+            CachePolicy::Arc => Box::new(ARCCache::new(capacity)),
+            CachePolicy::TinyLfu => Box::new(TinyLFUCache::new(capacity)),
+        }
+    }
+}
+
+impl FromStr for CachePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "lru" => Ok(CachePolicy::Lru),
+            "lfu" => Ok(CachePolicy::Lfu),
+            "fifo" => Ok(CachePolicy::Fifo),
+            "arc" => Ok(CachePolicy::Arc),
+            "tinylfu" | "w-tinylfu" => Ok(CachePolicy::TinyLfu),
+            _ => Err(format!("未知的缓存策略: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill<C: Cache<&'static str, i32> + ?Sized>(cache: &mut C) {
+        cache.put("one", 1);
+        cache.put("two", 2);
+        cache.put("three", 3);
+    }
+
+    #[test]
+    fn test_cache_trait_common_operations() {
+        for policy in [CachePolicy::Lru, CachePolicy::Lfu, CachePolicy::Fifo, CachePolicy::Arc, CachePolicy::TinyLfu] {
+            let mut cache = policy.build::<&'static str, i32>(3);
+            assert!(cache.is_empty());
+            assert_eq!(cache.capacity(), 3);
+
+            fill(cache.as_mut());
+            assert_eq!(cache.len(), 3);
+            assert!(cache.contains_key(&"two"));
+            assert_eq!(cache.peek(&"two"), Some(&2));
+            assert_eq!(cache.get(&"two"), Some(&2));
+
+            assert_eq!(cache.put("two", 20), Some(2));
+            assert_eq!(cache.len(), 3);
+
+            assert_eq!(cache.remove(&"two"), Some(20));
+            assert_eq!(cache.remove(&"two"), None);
+            assert!(!cache.contains_key(&"two"));
+            assert_eq!(cache.len(), 2);
+
+            let mut keys: Vec<_> = cache.iter().map(|(k, v)| (*k, *v)).collect();
+            keys.sort();
+            assert_eq!(keys, vec![("one", 1), ("three", 3)]);
+
+            cache.clear();
+            assert!(cache.is_empty());
+            assert_eq!(cache.get(&"one"), None);
+        }
+    }
+
+    #[test]
+    fn test_cache_policy_from_str() {
+        assert_eq!("LRU".parse::<CachePolicy>(), Ok(CachePolicy::Lru));
+        assert_eq!(" lfu ".parse::<CachePolicy>(), Ok(CachePolicy::Lfu));
+        assert_eq!("fifo".parse::<CachePolicy>(), Ok(CachePolicy::Fifo));
+        assert_eq!("ARC".parse::<CachePolicy>(), Ok(CachePolicy::Arc));
+        assert_eq!("W-TinyLFU".parse::<CachePolicy>(), Ok(CachePolicy::TinyLfu));
+        assert!("mru".parse::<CachePolicy>().is_err());
+    }
+}
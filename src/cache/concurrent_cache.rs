@@ -0,0 +1,247 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::cache::{Cache, CachePolicy};
+use crate::cache::stats::CacheStats;
+
+/// 线程安全的分段缓存。
+///
+/// 键空间按哈希分散到多个独立加锁的分段中，每个分段是一个普通的 `Cache` 实现，
+/// 不同分段上的读写互不阻塞。所有方法只需要 `&self`，读取时返回值的克隆，
+/// 较大的值可以用 `Arc<T>` 存放以降低克隆开销。
+pub struct ConcurrentCache<K, V, C = Box<dyn Cache<K, V> + Send>> {
+    shards: Vec<Mutex<C>>,
+    hasher: RandomState,
+    _marker: PhantomData<fn(K, V)>,
+}
+
+impl<K, V> ConcurrentCache<K, V>
+    where
+        K: Eq + Hash + Clone + Send + 'static,
+        V: Clone + Send + 'static,
+{
+    /// 按指定淘汰策略创建分段缓存，总容量平均分配到各分段，不能整除时前面的分段各多分1，各分段容量之和等于总容量。
+    ///
+    /// # 参数
+    ///
+    /// * `policy`: CachePolicy - 每个分段使用的淘汰策略
+    /// * `capacity`: usize - 缓存的总容量
+    /// * `shard_count`: usize - 分段数量，至少为1，容量小于分段数量时减少为容量，避免出现容量为0的分段
+    ///
+    /// 返回值：ConcurrentCache<K, V> - 新创建的分段缓存实例
+    pub fn new(policy: CachePolicy, capacity: usize, shard_count: usize) -> Self {
+        let shard_count = shard_count.clamp(1, capacity.max(1));
+        let (base, remainder) = (capacity / shard_count, capacity % shard_count);
+        ConcurrentCache::with_shards(shard_count, |i| policy.build(base + usize::from(i < remainder)))
+    }
+}
+
+impl<K, V, C> ConcurrentCache<K, V, C>
+    where
+        K: Eq + Hash + Clone,
+        V: Clone,
+        C: Cache<K, V>,
+{
+    /// 使用自定义的分段构造函数创建分段缓存，适用于需要为分段设置过期时间等参数的场景。
+    ///
+    /// # 参数
+    ///
+    /// * `shard_count`: usize - 分段数量，至少为1
+    /// * `factory`: Fn(usize) -> C - 根据分段下标创建分段缓存
+    ///
+    /// 返回值：ConcurrentCache<K, V, C> - 新创建的分段缓存实例
+    pub fn with_shards<F>(shard_count: usize, factory: F) -> Self
+        where
+            F: Fn(usize) -> C,
+    {
+        ConcurrentCache {
+            shards: (0..shard_count.max(1)).map(|i| Mutex::new(factory(i))).collect(),
+            hasher: RandomState::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// 获取指定键的值的克隆。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要获取的键的引用
+    ///
+    /// 返回值：Option<V> - 如果存在则返回值的克隆，否则返回None
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.shard(key).get(key).cloned()
+    }
+
+    /// 查看指定键的值的克隆，不影响淘汰顺序。
+    pub fn peek(&self, key: &K) -> Option<V> {
+        self.shard(key).peek(key).cloned()
+    }
+
+    /// 将键值对插入缓存中。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    ///
+    /// 返回值：Option<V> - 如果键已存在则返回被替换的旧值，否则返回None
+    pub fn put(&self, key: K, value: V) -> Option<V> {
+        self.shard(&key).put(key, value)
+    }
+
+    /// 将键值对插入缓存中，并为该项单独指定存活时间。
+    pub fn put_with_ttl(&self, key: K, value: V, ttl: Duration) -> Option<V> {
+        self.shard(&key).put_with_ttl(key, value, ttl)
+    }
+
+    /// 从缓存中移除指定键，返回被移除的值。
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.shard(key).remove(key)
+    }
+
+    /// 判断缓存中是否存在指定键。
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.shard(key).contains_key(key)
+    }
+
+    /// 依次清理各分段中已过期的项，返回被清理的项数。
+    pub fn purge_expired(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).purge_expired()).sum()
+    }
+
+    /// 返回各分段统计信息之和。
+    pub fn stats(&self) -> CacheStats {
+        self.shards.iter().map(|shard| lock(shard).stats()).fold(CacheStats::default(), |a, b| a + b)
+    }
+
+    /// 返回各分段项数之和，并发写入时只是一个近似值。
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).len()).sum()
+    }
+
+    /// 判断缓存是否为空。
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| lock(shard).is_empty())
+    }
+
+    /// 清空所有分段。
+    pub fn clear(&self) {
+        self.shards.iter().for_each(|shard| lock(shard).clear());
+    }
+
+    /// 返回各分段容量之和。
+    pub fn capacity(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).capacity()).sum()
+    }
+
+    /// 返回分段数量。
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    fn shard(&self, key: &K) -> MutexGuard<'_, C> {
+        let index = self.hasher.hash_one(key) as usize % self.shards.len();
+        lock(&self.shards[index])
+    }
+}
+
+/// 获取分段锁。某个线程在持有锁时发生 panic 不会影响其他线程继续使用缓存。
+pub(crate) fn lock<C>(shard: &Mutex<C>) -> MutexGuard<'_, C> {
+    shard.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+    use crate::cache::lru_cache::LRUCache;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_concurrent_cache() {
+        assert_send_sync::<ConcurrentCache<String, Arc<Vec<u8>>>>();
+        assert_send_sync::<ConcurrentCache<u64, u64, LRUCache<u64, u64>>>();
+
+        let cache = ConcurrentCache::new(CachePolicy::Lru, 8, 4);
+        assert_eq!(cache.shard_count(), 4);
+        assert_eq!(cache.capacity(), 8);
+        for (capacity, shards, expected) in [(10, 4, 4), (3, 4, 3), (0, 4, 1)] {
+            let cache = ConcurrentCache::<u32, u32>::new(CachePolicy::Lru, capacity, shards);
+            assert_eq!(cache.capacity(), capacity);
+            assert_eq!(cache.shard_count(), expected);
+        }
+
+        assert_eq!(cache.put("one", 1), None);
+        assert_eq!(cache.put("one", 10), Some(1));
+        assert_eq!(cache.get(&"one"), Some(10));
+        assert!(cache.contains_key(&"one"));
+        assert_eq!(cache.remove(&"one"), Some(10));
+        assert!(cache.is_empty());
+
+        let cache = ConcurrentCache::with_shards(4, |_| LRUCache::new(2).with_stats());
+        cache.put(1, 1);
+        assert_eq!(cache.get(&1), Some(1));
+        assert_eq!(cache.get(&2), None);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.insertions), (1, 1, 1));
+    }
+
+    #[test]
+    fn test_concurrent_cache_stress() {
+        let cache = ConcurrentCache::with_shards(8, |_| LRUCache::new(4096));
+        let threads = 8u64;
+        let per_thread = 2_000u64;
+
+        thread::scope(|scope| {
+            for t in 0..threads {
+                let cache = &cache;
+                scope.spawn(move || {
+                    for i in 0..per_thread {
+                        let key = t * per_thread + i;
+                        cache.put(key, key * 2);
+                        // 各线程的键互不重叠，容量足够时必然能读到自己写入的值
+                        assert_eq!(cache.get(&key), Some(key * 2));
+                        if i % 3 == 0 {
+                            assert_eq!(cache.remove(&key), Some(key * 2));
+                        }
+                    }
+                });
+            }
+        });
+
+        let expected = threads * (per_thread - per_thread.div_ceil(3));
+        assert_eq!(cache.len() as u64, expected);
+    }
+
+    #[test]
+    fn test_concurrent_cache_bounded_under_contention() {
+        let cache = ConcurrentCache::new(CachePolicy::Lfu, 64, 4);
+
+        thread::scope(|scope| {
+            for t in 0..8u32 {
+                let cache = &cache;
+                scope.spawn(move || {
+                    for i in 0..5_000u32 {
+                        let key = (i * 31 + t) % 500;
+                        if cache.get(&key).is_none() {
+                            cache.put(key, Arc::new(key.to_string()));
+                        }
+                    }
+                });
+            }
+        });
+
+        assert!(cache.len() <= cache.capacity());
+        for key in 0..500u32 {
+            if let Some(value) = cache.peek(&key) {
+                assert_eq!(*value, key.to_string());
+            }
+        }
+    }
+}
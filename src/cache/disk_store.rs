@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+
+use crate::cache::linked_slab::{LinkedSlab, List};
+use crate::cache::snapshot::{self, Codec};
+
+/// 缓存项文件的扩展名。
+const ENTRY_EXTENSION: &str = "entry";
+
+/// 文件名中 SHA-256 十六进制字符串的长度。
+const HASH_LEN: usize = 64;
+
+/// 文件开头记录键长度的字节数。
+const HEADER_LEN: u64 = 8;
+
+struct DiskEntry<K> {
+    key: K,
+    file_name: String,
+    size: u64,
+}
+
+/// 按总字节数限制大小的磁盘缓存目录。
+///
+/// 每个缓存项保存为一个文件，文件名是编码后的键的 SHA-256，内容依次为键的长度、键和值，
+/// 重启后可以只读取文件开头重建索引。内存中维护文件的 LRU 顺序，总大小超出上限时删除最久未使用的文件。
+pub(crate) struct DiskStore<K> {
+    dir: PathBuf,
+    max_bytes: u64,
+    total_bytes: u64,
+    index: HashMap<K, usize>,
+    entries: LinkedSlab<DiskEntry<K>>,
+    // 头部为最久未使用，尾部为最近写入
+    order: List,
+}
+
+impl<K> DiskStore<K>
+    where
+        K: Eq + std::hash::Hash + Clone + Codec,
+{
+    /// 打开目录，不存在时创建。
+    ///
+    /// 已有的缓存项按修改时间重建 LRU 顺序；写入中断留下的临时文件、无法解析或文件名与键不符的
+    /// 缓存项文件会被删除。只处理文件名为 SHA-256 加 `.entry` 的文件和 `write_atomic` 写入它们时使用的临时文件，
+    /// 目录中的其他文件保持不动。
+    pub(crate) fn open(dir: &Path, max_bytes: u64) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let mut found = Vec::new();
+        for dir_entry in fs::read_dir(dir)? {
+            let path = dir_entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if snapshot::tmp_target(name).is_some_and(is_entry_name) {
+                remove_stale(&path)?;
+            } else if is_entry_name(name) {
+                match read_header::<K>(&path) {
+                    Some(header) => found.push(header),
+                    None => remove_stale(&path)?,
+                }
+            }
+        }
+        found.sort_by_key(|&(modified, _)| modified);
+
+        let mut store = DiskStore {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            total_bytes: 0,
+            index: HashMap::with_capacity(found.len()),
+            entries: LinkedSlab::with_capacity(found.len()),
+            order: List::new(),
+        };
+        for (_, entry) in found {
+            store.link(entry);
+        }
+        store.evict_until_fits(0);
+        Ok(store)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub(crate) fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    pub(crate) fn contains_key(&self, key: &K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// 写入一项，覆盖同一个键的旧文件，必要时删除最久未使用的文件。
+    /// 比上限还大的项不会写入，写入失败时该项被丢弃。
+    pub(crate) fn insert(&mut self, key: K, value: &[u8]) {
+        self.remove(&key);
+        let mut key_bytes = Vec::new();
+        key.encode(&mut key_bytes);
+        let file_name = file_name(&key_bytes);
+        let mut data = Vec::with_capacity(HEADER_LEN as usize + key_bytes.len() + value.len());
+        (key_bytes.len() as u64).encode(&mut data);
+        data.extend_from_slice(&key_bytes);
+        data.extend_from_slice(value);
+
+        let size = data.len() as u64;
+        if size > self.max_bytes {
+            return;
+        }
+        self.evict_until_fits(size);
+        let path = self.dir.join(&file_name);
+        match snapshot::write_atomic(&path, &data) {
+            Ok(()) => self.link(DiskEntry { key, file_name, size }),
+            // 重命名之后同步目录失败时文件已经存在，删除它以免留下未被索引的文件
+            Err(_) => {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+
+    /// 取出一项的值并删除对应的文件，文件丢失或读取失败时返回None。
+    pub(crate) fn take(&mut self, key: &K) -> Option<Vec<u8>> {
+        let entry = self.unlink(key)?;
+        let path = self.dir.join(&entry.file_name);
+        let data = fs::read(&path);
+        let _ = fs::remove_file(&path);
+        let data = data.ok()?;
+        let mut input = data.as_slice();
+        let key_len = usize::try_from(u64::decode(&mut input)?).ok()?;
+        (key_len <= input.len()).then(|| input[key_len..].to_vec())
+    }
+
+    /// 删除一项，返回该项是否存在。
+    pub(crate) fn remove(&mut self, key: &K) -> bool {
+        match self.unlink(key) {
+            Some(entry) => {
+                let _ = fs::remove_file(self.dir.join(&entry.file_name));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 删除所有缓存项文件。
+    pub(crate) fn clear(&mut self) {
+        for entry in self.entries.iter(&self.order) {
+            let _ = fs::remove_file(self.dir.join(&entry.file_name));
+        }
+        self.index.clear();
+        self.entries.clear();
+        self.order = List::new();
+        self.total_bytes = 0;
+    }
+
+    /// 从最久未使用的文件开始删除，直到再写入 `incoming` 字节也不超出上限。
+    fn evict_until_fits(&mut self, incoming: u64) {
+        while self.total_bytes + incoming > self.max_bytes {
+            let Some(index) = self.entries.indices(&self.order).next() else {
+                break;
+            };
+            let key = self.entries.get(index).key.clone();
+            self.remove(&key);
+        }
+    }
+
+    fn link(&mut self, entry: DiskEntry<K>) {
+        self.total_bytes += entry.size;
+        let key = entry.key.clone();
+        let index = self.entries.insert(entry);
+        self.entries.push_back(&mut self.order, index);
+        self.index.insert(key, index);
+    }
+
+    fn unlink(&mut self, key: &K) -> Option<DiskEntry<K>> {
+        let index = self.index.remove(key)?;
+        self.entries.unlink(&mut self.order, index);
+        let entry = self.entries.remove(index);
+        self.total_bytes -= entry.size;
+        Some(entry)
+    }
+}
+
+/// 由编码后的键得到文件名。
+fn file_name(key: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(key);
+    format!("{}.{}", hasher.result_str(), ENTRY_EXTENSION)
+}
+
+/// 文件名是否为 `file_name` 生成的格式。
+fn is_entry_name(name: &str) -> bool {
+    name.strip_suffix(ENTRY_EXTENSION)
+        .and_then(|stem| stem.strip_suffix('.'))
+        .is_some_and(|hash| hash.len() == HASH_LEN && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')))
+}
+
+/// 删除无效的文件，文件已经被其他进程删除或重命名时忽略。
+fn remove_stale(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// 只读取文件开头的键，返回文件的修改时间和索引项，文件不是有效的缓存项时返回None。
+fn read_header<K: Codec>(path: &Path) -> Option<(SystemTime, DiskEntry<K>)> {
+    let mut file = File::open(path).ok()?;
+    let metadata = file.metadata().ok()?;
+    let mut len = [0; HEADER_LEN as usize];
+    file.read_exact(&mut len).ok()?;
+    let key_len = u64::from_le_bytes(len);
+    if key_len > metadata.len() - HEADER_LEN {
+        return None;
+    }
+    let mut key_bytes = vec![0; key_len as usize];
+    file.read_exact(&mut key_bytes).ok()?;
+
+    let file_name = file_name(&key_bytes);
+    if path.file_name()?.to_str()? != file_name {
+        return None;
+    }
+    let mut input = key_bytes.as_slice();
+    let key = K::decode(&mut input).filter(|_| input.is_empty())?;
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    Some((modified, DiskEntry { key, file_name, size: metadata.len() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_store() {
+        let dir = std::env::temp_dir().join(format!("r-tool-disk-store-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        // 每项占用 8 + 4 + 10 字节
+        let mut store = DiskStore::open(&dir, 50).unwrap();
+        store.insert(1u32, &[1; 10]);
+        store.insert(2u32, &[2; 10]);
+        assert_eq!(store.total_bytes(), 44);
+        store.insert(3u32, &[3; 10]);
+        // 超出上限时删除最久未使用的文件
+        assert!(!store.contains_key(&1));
+        assert_eq!(store.len(), 2);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        // 比上限还大的项不会写入
+        store.insert(4u32, &[4; 100]);
+        assert!(!store.contains_key(&4));
+
+        assert_eq!(store.take(&2), Some(vec![2; 10]));
+        assert_eq!(store.take(&2), None);
+        assert_eq!(store.len(), 1);
+
+        // 重新打开时重建索引并清理无效文件，只删除缓存项自己的文件和临时文件
+        drop(store);
+        let garbage = format!("{}.{}", "ab".repeat(32), ENTRY_EXTENSION);
+        let partial = format!("{}.{}-{:016x}.tmp", file_name(b"partial"), 42, 7);
+        fs::write(dir.join(&garbage), b"garbage").unwrap();
+        fs::write(dir.join(&partial), b"partial").unwrap();
+        fs::write(dir.join("other.entry"), b"keep").unwrap();
+        fs::write(dir.join("other.entry.tmp"), b"keep").unwrap();
+        fs::write(dir.join("notes.txt"), b"keep").unwrap();
+        let mut store = DiskStore::<u32>::open(&dir, 50).unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.total_bytes(), 22);
+        assert!(!dir.join(&garbage).exists());
+        assert!(!dir.join(&partial).exists());
+        assert!(dir.join("other.entry").exists());
+        assert!(dir.join("other.entry.tmp").exists());
+        assert!(dir.join("notes.txt").exists());
+        assert_eq!(store.take(&3), Some(vec![3; 10]));
+        assert!(remove_stale(&dir.join(&garbage)).is_ok());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
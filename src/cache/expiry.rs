@@ -0,0 +1,132 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub use crate::date::clock::{Clock, ManualClock, SystemClock};
+
+/// 距离存活时间和空闲时间到期的剩余时长，用于保存和加载快照。
+pub(crate) type Remaining = (Option<Duration>, Option<Duration>);
+
+/// 单个缓存项的过期时刻。
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Deadline {
+    // 存活时间（TTL）到期的时刻
+    expires_at: Option<Instant>,
+    // 空闲时间（TTI）到期的时刻，每次访问后顺延
+    idle_until: Option<Instant>,
+}
+
+/// 缓存的过期配置，被各淘汰策略共享。
+#[derive(Clone)]
+pub(crate) struct Expiry {
+    clock: Arc<dyn Clock>,
+    time_to_live: Option<Duration>,
+    time_to_idle: Option<Duration>,
+}
+
+impl Expiry {
+    pub(crate) fn new() -> Self {
+        Expiry {
+            clock: Arc::new(SystemClock),
+            time_to_live: None,
+            time_to_idle: None,
+        }
+    }
+
+    pub(crate) fn set_time_to_live(&mut self, ttl: Duration) {
+        self.time_to_live = Some(ttl);
+    }
+
+    pub(crate) fn set_time_to_idle(&mut self, tti: Duration) {
+        self.time_to_idle = Some(tti);
+    }
+
+    pub(crate) fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// 为新写入的项计算过期时刻，`ttl` 为空时使用缓存的默认存活时间。
+    pub(crate) fn deadline(&self, ttl: Option<Duration>) -> Deadline {
+        let ttl = ttl.or(self.time_to_live);
+        let tti = self.time_to_idle;
+        if ttl.is_none() && tti.is_none() {
+            return Deadline { expires_at: None, idle_until: None };
+        }
+        let now = self.clock.now();
+        Deadline {
+            expires_at: ttl.map(|ttl| now + ttl),
+            idle_until: tti.map(|tti| now + tti),
+        }
+    }
+
+    /// 记录一次访问，顺延空闲过期时刻。
+    pub(crate) fn touch(&self, deadline: &mut Deadline) {
+        if let Some(tti) = self.time_to_idle {
+            deadline.idle_until = Some(self.clock.now() + tti);
+        }
+    }
+
+    /// 返回距离存活时间和空闲时间到期的剩余时长，用于保存快照。
+    pub(crate) fn remaining(&self, deadline: &Deadline) -> Remaining {
+        let now = self.clock.now();
+        (
+            deadline.expires_at.map(|at| at.saturating_duration_since(now)),
+            deadline.idle_until.map(|at| at.saturating_duration_since(now)),
+        )
+    }
+
+    /// 按剩余时长重建过期时刻，用于加载快照。
+    pub(crate) fn restore(&self, remaining: Remaining) -> Deadline {
+        let now = self.clock.now();
+        Deadline {
+            expires_at: remaining.0.map(|ttl| now + ttl),
+            idle_until: remaining.1.map(|tti| now + tti),
+        }
+    }
+
+    pub(crate) fn is_expired(&self, deadline: &Deadline) -> bool {
+        if deadline.expires_at.is_none() && deadline.idle_until.is_none() {
+            return false;
+        }
+        let now = self.clock.now();
+        deadline.expires_at.is_some_and(|at| now >= at) || deadline.idle_until.is_some_and(|at| now >= at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry_deadline() {
+        let clock = ManualClock::new();
+        let mut expiry = Expiry::new();
+        expiry.set_clock(Arc::new(clock.clone()));
+        expiry.set_time_to_live(Duration::from_secs(10));
+        expiry.set_time_to_idle(Duration::from_secs(4));
+
+        let mut deadline = expiry.deadline(None);
+        clock.advance(Duration::from_secs(3));
+        assert!(!expiry.is_expired(&deadline));
+
+        // 访问后空闲时间顺延，但存活时间不变
+        expiry.touch(&mut deadline);
+        clock.advance(Duration::from_secs(3));
+        assert!(!expiry.is_expired(&deadline));
+        expiry.touch(&mut deadline);
+        clock.advance(Duration::from_secs(4));
+        assert!(expiry.is_expired(&deadline));
+
+        // 剩余时长可以在另一个时刻还原
+        let deadline = expiry.deadline(None);
+        clock.advance(Duration::from_secs(6));
+        let remaining = expiry.remaining(&deadline);
+        assert_eq!(remaining, (Some(Duration::from_secs(4)), Some(Duration::ZERO)));
+        assert!(expiry.is_expired(&expiry.restore(remaining)));
+        assert!(!expiry.is_expired(&expiry.restore((remaining.0, None))));
+
+        // 单项存活时间覆盖默认值
+        let deadline = expiry.deadline(Some(Duration::from_secs(1)));
+        clock.advance(Duration::from_secs(1));
+        assert!(expiry.is_expired(&deadline));
+    }
+}
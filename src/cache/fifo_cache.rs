@@ -1,31 +1,51 @@
-use std::collections::{HashMap, LinkedList};
+use std::path::Path;
+use std::time::Duration;
 
-pub struct LRUCache<K, V> {
-    capacity: usize,
-    cache: HashMap<K, (V, usize)>,
-    order: LinkedList<K>,
+use crate::cache::Cache;
+use crate::cache::cache_core::{impl_cache, CacheCore, Policy};
+use crate::cache::linked_slab::List;
+use crate::cache::snapshot::{self, Codec, SnapshotError, SnapshotKind};
+
+pub struct FIFOCache<K, V> {
+    core: CacheCore<K, V, ()>,
+    // 插入顺序，头部为最早插入的项
+    order: List,
 }
 
-impl<K, V> LRUCache<K, V>
+impl<K, V> FIFOCache<K, V>
     where
         K: Eq + std::hash::Hash + Clone,
 {
-    /// 创建一个新的LRU缓存实例。
+    /// 创建一个新的FIFO缓存实例。
     ///
     /// # 参数
     ///
     /// * `capacity`: usize - 缓存的容量
     ///
-    /// 返回值：LRUCache<K, V> - 新创建的LRU缓存实例
+    /// 返回值：FIFOCache<K, V> - 新创建的FIFO缓存实例
     pub fn new(capacity: usize) -> Self {
-        LRUCache {
-            capacity,
-            cache: HashMap::with_capacity(capacity),
-            order: LinkedList::new(),
-        }
+        FIFOCache { core: CacheCore::new(capacity), order: List::new() }
     }
 
-    /// 获取缓存中指定键的值，并将该键标记为最近使用。
+    /// 创建一个按权重限制容量的FIFO缓存实例，缓存项的权重由 `weigher` 计算，
+    /// 淘汰会持续到所有项的权重之和不超过 `max_weight`。
+    ///
+    /// 权重超过 `max_weight` 的项不会放入缓存，而是直接以 `RemovalCause::Capacity` 通知移除回调。
+    ///
+    /// # 参数
+    ///
+    /// * `max_weight`: usize - 最大总权重
+    /// * `weigher`: Fn(&K, &V) -> usize - 计算缓存项权重的函数
+    ///
+    /// 返回值：FIFOCache<K, V> - 新创建的FIFO缓存实例
+    pub fn with_weigher<F>(max_weight: usize, weigher: F) -> Self
+        where
+            F: Fn(&K, &V) -> usize + Send + Sync + 'static,
+    {
+        FIFOCache { core: CacheCore::weighted(max_weight, Box::new(weigher)), order: List::new() }
+    }
+
+    /// 获取缓存中指定键的值，访问不会改变键的淘汰顺序。
     ///
     /// # 参数
     ///
@@ -33,64 +53,223 @@ impl<K, V> LRUCache<K, V>
     ///
     /// 返回值：Option<&V> - 如果存在则返回对应值的引用，否则返回None
     pub fn get(&mut self, key: &K) -> Option<&V> {
-        if let Some((value, timestamp)) = self.cache.get_mut(key) {
-            // 更新时间戳
-            *timestamp = self.order.len();
-            // 将键移动到顺序列表的末尾（最近使用）
-            let popped_key = self.order.pop_front().unwrap();
-            self.order.push_back(popped_key);
-            Some(value)
-        } else {
-            None
-        }
+        self.lookup(key)
     }
 
-    /// 将键值对插入缓存中，如果缓存已满则淘汰最久未使用的项。
+    /// 将键值对插入缓存中，如果缓存已满则淘汰最早插入的项。
+    ///
+    /// 如果键已存在，则原地更新值，不改变该键的插入顺序。
     ///
     /// # 参数
     ///
     /// * `key`: K - 要插入的键
     /// * `value`: V - 要插入的值
-    pub fn put(&mut self, key: K, value: V) {
-        if self.cache.len() >= self.capacity {
-            // 淘汰最久未使用的项
-            if let Some(oldest_key) = self.order.pop_front() {
-                self.cache.remove(&oldest_key);
-            }
+    ///
+    /// 返回值：Option<V> - 如果键已存在则返回被替换的旧值，否则返回None
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value, None)
+    }
+
+    /// 将键值对插入缓存中，并为该项单独指定存活时间。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    /// * `ttl`: Duration - 该项的存活时间，覆盖默认存活时间
+    ///
+    /// 返回值：Option<V> - 如果键已存在且未过期则返回被替换的旧值，否则返回None
+    pub fn put_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        self.insert(key, value, Some(ttl))
+    }
+
+    /// 从最早插入的项开始淘汰，直到再放入 `incoming` 的权重也不超出最大总权重，
+    /// `keep` 指向的项不会被淘汰。
+    fn evict_until_fits(&mut self, incoming: usize, keep: Option<usize>) {
+        while self.core.weights.overflows(incoming) {
+            let Some(index) = self.core.entries.indices(&self.order).find(|&index| Some(index) != keep) else {
+                break;
+            };
+            self.evict_index(index);
+        }
+    }
+}
+
+impl<K, V> FIFOCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone + Codec,
+        V: Codec,
+{
+    /// 将缓存中未过期的项保存到快照文件，保留写入顺序和剩余的过期时间。
+    ///
+    /// 先写入同目录下的临时文件再重命名，保存中途失败不会破坏已有的快照。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 快照文件路径
+    ///
+    /// 返回值：Result<(), SnapshotError> - 写入失败时返回错误
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        snapshot::save(path.as_ref(), SnapshotKind::Fifo, 0, &self.snapshot_entries(|&meta| meta))
+    }
+
+    /// 从快照文件加载缓存项，替换缓存中现有的内容。
+    ///
+    /// 快照全部校验通过后才会修改缓存，加载失败时缓存保持不变。过期时间按保存时的剩余时长重新计算，
+    /// 超出容量的项按淘汰策略淘汰，加载过程不计入统计信息。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 快照文件路径
+    ///
+    /// 返回值：Result<usize, SnapshotError> - 加载后缓存中的项数
+    pub fn load_from<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, SnapshotError> {
+        let (_, entries) = snapshot::load::<K, V, ()>(path.as_ref(), SnapshotKind::Fifo)?;
+        Cache::clear(self);
+        for (key, value, remaining, ()) in entries {
+            self.restore(key, value, remaining, ());
         }
+        self.evict_until_fits(0, None);
+        Ok(self.core.cache.len())
+    }
+}
+
+impl<K, V> Policy<K, V> for FIFOCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone,
+{
+    type Meta = ();
+
+    fn core(&self) -> &CacheCore<K, V, ()> {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut CacheCore<K, V, ()> {
+        &mut self.core
+    }
+
+    fn link(&mut self, index: usize) {
+        self.core.entries.push_back(&mut self.order, index);
+    }
 
-        // 插入新项
-        let timestamp = self.order.len();
-        self.cache.insert(key.clone(), (value, timestamp));
-        // 将键移动到顺序列表的末尾（最近使用）
-        self.order.push_back(key);
+    fn unlink(&mut self, index: usize) {
+        self.core.entries.unlink(&mut self.order, index);
+    }
+
+    /// 访问不会改变插入顺序。
+    fn on_hit(&mut self, _index: usize) {}
+
+    fn on_update(&mut self, index: usize, _old_weight: usize) {
+        // 已存在的键只更新值，保持原有的插入顺序；新值更重时可能需要淘汰其他项，被更新的项即使排在最前也保留
+        self.evict_until_fits(0, Some(index));
+    }
+
+    fn make_room_for(&mut self, _key: &K, weight: usize) {
+        self.evict_until_fits(weight, None);
+    }
+
+    /// 按插入顺序，最早插入的项在前。
+    fn order(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(self.core.entries.indices(&self.order))
+    }
+
+    fn reset(&mut self) {
+        self.order = List::new();
     }
 }
 
+impl_cache!(FIFOCache);
+
+
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use super::*;
+    use crate::cache::expiry::ManualClock;
 
     #[test]
-    fn test_lru_cache() {
-        let mut lru_cache = LRUCache::new(3);
+    fn test_fifo_cache() {
+        let mut fifo_cache = FIFOCache::new(3);
 
         // 插入项
-        lru_cache.put("one", 1);
-        lru_cache.put("two", 2);
-        lru_cache.put("three", 3);
+        fifo_cache.put("one", 1);
+        fifo_cache.put("two", 2);
+        fifo_cache.put("three", 3);
 
-        // 访问一项以使其成为最近使用的项
-        assert_eq!(lru_cache.get(&"one"), Some(&1));
+        // 访问一项，FIFO 不会因为访问而改变淘汰顺序
+        assert_eq!(fifo_cache.get(&"one"), Some(&1));
 
-        // 插入新项，淘汰最久未使用的项（"two"）
-        lru_cache.put("four", 4);
+        // 插入新项，淘汰最早插入的项（"one"），而不是 LRU 下的 "two"
+        fifo_cache.put("four", 4);
 
         // 检查缓存的状态
-        assert_eq!(lru_cache.cache.len(), 3);
-        assert_eq!(lru_cache.get(&"one"), Some(&1));
-        assert_eq!(lru_cache.get(&"two"), None); // "two" 应该被淘汰
-        assert_eq!(lru_cache.get(&"three"), Some(&3));
-        assert_eq!(lru_cache.get(&"four"), Some(&4));
+        assert_eq!(fifo_cache.core.cache.len(), 3);
+        assert_eq!(fifo_cache.get(&"one"), None); // "one" 应该被淘汰
+        assert_eq!(fifo_cache.get(&"two"), Some(&2));
+        assert_eq!(fifo_cache.get(&"three"), Some(&3));
+        assert_eq!(fifo_cache.get(&"four"), Some(&4));
+    }
+
+    #[test]
+    fn test_fifo_cache_update_keeps_position() {
+        let mut fifo_cache = FIFOCache::new(2);
+
+        fifo_cache.put("one", 1);
+        fifo_cache.put("two", 2);
+
+        // 更新已存在的键，只修改值，不刷新插入顺序
+        assert_eq!(fifo_cache.put("one", 10), Some(1));
+        assert_eq!(fifo_cache.len(), 2);
+        assert_eq!(fifo_cache.get(&"one"), Some(&10));
+
+        // "one" 仍然是最早插入的项，应该被淘汰
+        fifo_cache.put("three", 3);
+        assert_eq!(fifo_cache.get(&"one"), None);
+        assert_eq!(fifo_cache.get(&"two"), Some(&2));
+        assert_eq!(fifo_cache.get(&"three"), Some(&3));
+    }
+
+    #[test]
+    fn test_fifo_cache_expiry() {
+        let clock = ManualClock::new();
+        let mut fifo_cache = FIFOCache::new(3)
+            .with_time_to_idle(Duration::from_secs(5))
+            .with_clock(Arc::new(clock.clone()));
+
+        fifo_cache.put("one", 1);
+        fifo_cache.put_with_ttl("two", 2, Duration::from_secs(2));
+        fifo_cache.put("three", 3);
+
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(fifo_cache.get(&"one"), Some(&1));
+        assert_eq!(fifo_cache.peek(&"two"), None);
+
+        // "two" 存活时间到期，"three" 空闲时间到期，"one" 因访问被顺延
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(fifo_cache.purge_expired(), 2);
+        let keys: Vec<_> = fifo_cache.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec!["one"]);
+    }
+
+    #[test]
+    fn test_fifo_cache_weighted() {
+        let mut fifo_cache = FIFOCache::with_weigher(6, |_: &&str, v: &Vec<u8>| v.len());
+
+        fifo_cache.put("one", vec![1; 2]);
+        fifo_cache.put("two", vec![2; 2]);
+        fifo_cache.put("three", vec![3; 2]);
+
+        // 最早插入的 "one" 变重后仍保留原位置，淘汰排在它后面的项
+        assert_eq!(fifo_cache.put("one", vec![1; 4]), Some(vec![1; 2]));
+        assert_eq!(fifo_cache.total_weight(), 6);
+        let keys: Vec<_> = fifo_cache.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec!["one", "three"]);
+
+        assert_eq!(fifo_cache.put("four", vec![4; 7]), None);
+        assert!(!fifo_cache.contains_key(&"four"));
+        fifo_cache.put("four", vec![4; 2]);
+        let keys: Vec<_> = fifo_cache.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec!["three", "four"]);
     }
 }
@@ -0,0 +1,127 @@
+/// 每行计数器使用的哈希种子，使各行的下标相互独立。
+const SEEDS: [u64; 4] = [0xc3a5_c85c_97cb_3127, 0xb492_b66f_be98_f273, 0x9ae1_6a3b_2f90_404f, 0xcbf2_9ce4_8422_2325];
+
+/// 计数器的最大值，超过后不再增加。
+const MAX_COUNT: u8 = 15;
+
+/// 估算访问频率的 Count-Min Sketch。
+///
+/// 每个键在每行中对应一个计数器，估算值取各行计数器的最小值，只会高估不会低估。
+/// 记录次数达到采样大小后所有计数器减半，使频率随时间衰减，旧的热点可以被新的热点取代。
+pub(crate) struct FrequencySketch {
+    table: Vec<[u8; 4]>,
+    mask: usize,
+    additions: usize,
+    sample_size: usize,
+}
+
+impl FrequencySketch {
+    /// 按预计的项数创建，实际宽度取不小于项数的2的幂。
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let width = capacity.clamp(16, 1 << 24).next_power_of_two();
+        FrequencySketch {
+            table: vec![[0; 4]; width],
+            mask: width - 1,
+            additions: 0,
+            sample_size: width * 10,
+        }
+    }
+
+    /// 缓存中的项数超过宽度时扩容。
+    ///
+    /// 下标取哈希的低位，扩容后键的新下标与旧下标的低位相同，因此新表的每个计数器沿用旧表中对应计数器的值，
+    /// 之前记录的频率保持不变。
+    pub(crate) fn ensure_capacity(&mut self, capacity: usize) {
+        if capacity > self.table.len() && self.table.len() < 1 << 24 {
+            let grown = FrequencySketch::with_capacity(capacity);
+            let table = (0..grown.table.len()).map(|index| self.table[index & self.mask]).collect();
+            *self = FrequencySketch { table, additions: self.additions, ..grown };
+        }
+    }
+
+    /// 返回键的估算访问频率。
+    pub(crate) fn frequency(&self, hash: u64) -> u8 {
+        (0..4).map(|row| self.table[self.index(hash, row)][row]).min().unwrap_or(0)
+    }
+
+    /// 记录一次访问。
+    pub(crate) fn increment(&mut self, hash: u64) {
+        let mut added = false;
+        for row in 0..4 {
+            let index = self.index(hash, row);
+            let counter = &mut self.table[index][row];
+            if *counter < MAX_COUNT {
+                *counter += 1;
+                added = true;
+            }
+        }
+        if added {
+            self.additions += 1;
+            if self.additions >= self.sample_size {
+                self.reset();
+            }
+        }
+    }
+
+    /// 所有计数器减半。
+    fn reset(&mut self) {
+        for counters in &mut self.table {
+            for counter in counters.iter_mut() {
+                *counter >>= 1;
+            }
+        }
+        self.additions /= 2;
+    }
+
+    fn index(&self, hash: u64, row: usize) -> usize {
+        let hash = (hash ^ SEEDS[row]).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        (hash ^ (hash >> 32)) as usize & self.mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frequency_sketch() {
+        let mut sketch = FrequencySketch::with_capacity(64);
+        for _ in 0..5 {
+            sketch.increment(42);
+        }
+        sketch.increment(7);
+        assert!(sketch.frequency(42) >= 5);
+        assert!(sketch.frequency(7) >= 1);
+        assert!(sketch.frequency(42) > sketch.frequency(7));
+
+        // 计数器饱和后不再增加
+        for _ in 0..100 {
+            sketch.increment(42);
+        }
+        assert_eq!(sketch.frequency(42), MAX_COUNT);
+
+        // 达到采样大小后计数器减半
+        for hash in 0..sketch.sample_size as u64 {
+            sketch.increment(hash * 1_000_003 + 100);
+        }
+        assert!(sketch.frequency(42) < MAX_COUNT);
+    }
+
+    #[test]
+    fn test_frequency_sketch_grow_keeps_counts() {
+        let mut sketch = FrequencySketch::with_capacity(16);
+        let hashes: Vec<u64> = (0..8).map(|i| i * 0x9e37_79b9 + 1).collect();
+        for (count, &hash) in hashes.iter().enumerate() {
+            for _ in 0..=count {
+                sketch.increment(hash);
+            }
+        }
+        let before: Vec<u8> = hashes.iter().map(|&hash| sketch.frequency(hash)).collect();
+
+        sketch.ensure_capacity(1000);
+        assert_eq!(sketch.table.len(), 1024);
+        assert_eq!(sketch.sample_size, 10240);
+        let after: Vec<u8> = hashes.iter().map(|&hash| sketch.frequency(hash)).collect();
+        assert_eq!(after, before);
+    }
+}
@@ -1,9 +1,23 @@
-use std::collections::{HashMap, LinkedList};
+use std::path::Path;
+use std::time::Duration;
 
-pub struct LFUCache<K:Eq, V> {
-    capacity: usize,
-    cache: HashMap<K, (V, usize, usize)>,
-    order: LinkedList<K>,
+use crate::cache::Cache;
+use crate::cache::cache_core::{impl_cache, CacheCore, Policy};
+use crate::cache::linked_slab::{LinkedSlab, List, NIL};
+use crate::cache::snapshot::{self, Codec, SnapshotError, SnapshotKind};
+
+/// 同一访问频率的项，链表头部为该频率下最久未使用的项。
+struct Bucket {
+    frequency: usize,
+    items: List,
+}
+
+pub struct LFUCache<K: Eq, V> {
+    // 缓存项上记录的是所在频率桶的下标
+    core: CacheCore<K, V, usize>,
+    buckets: LinkedSlab<Bucket>,
+    // 非空的频率桶按访问频率从低到高排列，头部为最低频率，访问频率加一时移到相邻的桶
+    frequencies: List,
 }
 
 impl<K :Eq, V> LFUCache<K, V>
@@ -19,12 +33,30 @@ impl<K :Eq, V> LFUCache<K, V>
     /// 返回值：LFUCache<K, V> - 新创建的LFU缓存实例
     pub fn new(capacity: usize) -> Self {
         LFUCache {
-            capacity,
-            cache: HashMap::with_capacity(capacity),
-            order: LinkedList::new(),
+            core: CacheCore::new(capacity),
+            buckets: LinkedSlab::with_capacity(0),
+            frequencies: List::new(),
         }
     }
 
+    /// 创建一个按权重限制容量的LFU缓存实例，缓存项的权重由 `weigher` 计算，
+    /// 淘汰会持续到所有项的权重之和不超过 `max_weight`。
+    ///
+    /// 权重超过 `max_weight` 的项不会放入缓存，而是直接以 `RemovalCause::Capacity` 通知移除回调。
+    ///
+    /// # 参数
+    ///
+    /// * `max_weight`: usize - 最大总权重
+    /// * `weigher`: Fn(&K, &V) -> usize - 计算缓存项权重的函数
+    ///
+    /// 返回值：LFUCache<K, V> - 新创建的LFU缓存实例
+    pub fn with_weigher<F>(max_weight: usize, weigher: F) -> Self
+        where
+            F: Fn(&K, &V) -> usize + Send + Sync + 'static,
+    {
+        LFUCache { core: CacheCore::weighted(max_weight, Box::new(weigher)), ..LFUCache::new(0) }
+    }
+
     /// 获取缓存中指定键的值，并将该键的访问频率增加，并将该键标记为最近使用。
     ///
     /// # 参数
@@ -33,52 +65,211 @@ impl<K :Eq, V> LFUCache<K, V>
     ///
     /// 返回值：Option<&V> - 如果存在则返回对应值的引用，否则返回None
     pub fn get(&mut self, key: &K) -> Option<&V> {
-        if let Some((value, frequency, timestamp)) = self.cache.get_mut(key) {
-            // 更新访问频率
-            *frequency += 1;
-            // 更新时间戳
-            *timestamp = self.order.len();
-            // 将键移动到顺序列表的末尾（最近使用）
-            let popped_key = self.order.pop_front().unwrap();
-            self.order.push_back(popped_key);
-            Some(value)
-        } else {
-            None
-        }
+        self.lookup(key)
     }
 
-    /// 将键值对插入缓存中，如果缓存已满则淘汰访问频率最低的项。
+    /// 将键值对插入缓存中，如果缓存已满则淘汰访问频率最低的项，
+    /// 访问频率相同时淘汰其中最久未使用的项。
+    ///
+    /// 如果键已存在，则替换值，并与 `get` 一样计为一次访问。
     ///
     /// # 参数
     ///
     /// * `key`: K - 要插入的键
     /// * `value`: V - 要插入的值
-    pub fn put(&mut self, key: K, value: V) {
-        if self.cache.len() >= self.capacity {
-            // 找到访问频率最低的项
-            let min_frequency_key = self
-                .order
-                .iter()
-                .min_by_key(|&k| self.cache.get(k).map_or(usize::MAX, |(_, f, _)| *f))
-                .cloned();
-
-            // 淘汰访问频率最低的项
-            if let Some(oldest_key) = min_frequency_key {
-                self.cache.remove(&oldest_key);
+    ///
+    /// 返回值：Option<V> - 如果键已存在则返回被替换的旧值，否则返回None
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value, None)
+    }
+
+    /// 将键值对插入缓存中，并为该项单独指定存活时间。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    /// * `ttl`: Duration - 该项的存活时间，覆盖默认存活时间
+    ///
+    /// 返回值：Option<V> - 如果键已存在且未过期则返回被替换的旧值，否则返回None
+    pub fn put_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        self.insert(key, value, Some(ttl))
+    }
+
+    /// 淘汰访问频率最低的项，直到再放入 `incoming` 的权重也不超出最大总权重，
+    /// `keep` 指向的项不会被淘汰。
+    fn evict_until_fits(&mut self, incoming: usize, keep: Option<usize>) {
+        while self.core.weights.overflows(incoming) {
+            let Some(index) = self.victim(keep) else {
+                break;
+            };
+            self.evict_index(index);
+        }
+    }
+
+    /// 选出下一个被淘汰的项：访问频率最低的桶中最久未使用的项，最多跳过被保留的一项。
+    fn victim(&self, keep: Option<usize>) -> Option<usize> {
+        self.buckets
+            .indices(&self.frequencies)
+            .flat_map(|bucket| self.core.entries.indices(&self.buckets.get(bucket).items))
+            .find(|&index| Some(index) != keep)
+    }
+
+    /// 返回项的访问频率。
+    fn frequency(&self, index: usize) -> usize {
+        self.buckets.get(self.core.entries.get(index).meta).frequency
+    }
+
+    /// 返回 `prev` 之后访问频率为 `frequency` 的桶，没有时新建一个，`prev` 为 `NIL` 时从最低频率开始找。
+    ///
+    /// 桶按频率排列，调用方保证 `frequency` 大于 `prev` 的频率且不大于其后继的频率。
+    fn bucket_after(&mut self, prev: usize, frequency: usize) -> usize {
+        let next = if prev == NIL { self.buckets.indices(&self.frequencies).next() } else { self.buckets.next(prev) };
+        if let Some(next) = next.filter(|&next| self.buckets.get(next).frequency == frequency) {
+            return next;
+        }
+        let bucket = self.buckets.insert(Bucket { frequency, items: List::new() });
+        self.buckets.insert_after(&mut self.frequencies, prev, bucket);
+        bucket
+    }
+
+    /// 删除没有项的桶。
+    fn remove_bucket_if_empty(&mut self, bucket: usize) {
+        if self.buckets.get(bucket).items.is_empty() {
+            self.buckets.unlink(&mut self.frequencies, bucket);
+            self.buckets.remove(bucket);
+        }
+    }
+
+    /// 将节点的访问频率加一，并移动到相邻的高一级频率桶的末尾（最近使用）。
+    fn touch(&mut self, index: usize) {
+        let bucket = self.core.entries.get(index).meta;
+        let target = self.bucket_after(bucket, self.frequency(index) + 1);
+        self.unlink(index);
+        self.core.entries.get_mut(index).meta = target;
+        self.link(index);
+    }
+}
+
+impl<K, V> LFUCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone + Codec,
+        V: Codec,
+{
+    /// 将缓存中未过期的项保存到快照文件，保留各项的访问频率、同频率下的访问顺序和剩余的过期时间。
+    ///
+    /// 先写入同目录下的临时文件再重命名，保存中途失败不会破坏已有的快照。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 快照文件路径
+    ///
+    /// 返回值：Result<(), SnapshotError> - 写入失败时返回错误
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        snapshot::save(path.as_ref(), SnapshotKind::Lfu, 0, &self.snapshot_entries(|&bucket| self.buckets.get(bucket).frequency))
+    }
+
+    /// 从快照文件加载缓存项，替换缓存中现有的内容。
+    ///
+    /// 快照全部校验通过后才会修改缓存，加载失败时缓存保持不变。各项恢复保存时的访问频率，
+    /// 过期时间按保存时的剩余时长重新计算，超出容量的项按淘汰策略淘汰，加载过程不计入统计信息。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 快照文件路径
+    ///
+    /// 返回值：Result<usize, SnapshotError> - 加载后缓存中的项数
+    pub fn load_from<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, SnapshotError> {
+        let (_, mut entries) = snapshot::load::<K, V, usize>(path.as_ref(), SnapshotKind::Lfu)?;
+        Cache::clear(self);
+        // 按频率从低到高放入，频率桶总是追加在末尾；排序是稳定的，同一频率内保持保存时的访问顺序
+        entries.sort_by_key(|entry| entry.3.max(1));
+        for (key, value, remaining, frequency) in entries {
+            let tail = self.frequencies.back().unwrap_or(NIL);
+            let bucket = self.bucket_after(tail, frequency.max(1));
+            if self.restore(key, value, remaining, bucket).is_none() {
+                self.remove_bucket_if_empty(bucket);
             }
         }
+        self.evict_until_fits(0, None);
+        Ok(self.core.cache.len())
+    }
+}
+
+impl<K, V> Policy<K, V> for LFUCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone,
+{
+    type Meta = usize;
+
+    fn core(&self) -> &CacheCore<K, V, usize> {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut CacheCore<K, V, usize> {
+        &mut self.core
+    }
+
+    /// 放到所在频率桶的末尾。
+    fn link(&mut self, index: usize) {
+        let bucket = self.core.entries.get(index).meta;
+        self.core.entries.push_back(&mut self.buckets.get_mut(bucket).items, index);
+    }
+
+    /// 从所在频率桶中摘除，桶为空时删除，下一个桶成为最低频率。
+    fn unlink(&mut self, index: usize) {
+        let bucket = self.core.entries.get(index).meta;
+        self.core.entries.unlink(&mut self.buckets.get_mut(bucket).items, index);
+        self.remove_bucket_if_empty(bucket);
+    }
+
+    fn on_hit(&mut self, index: usize) {
+        self.touch(index);
+    }
+
+    fn on_update(&mut self, index: usize, _old_weight: usize) {
+        self.touch(index);
+        // 新值更重时可能需要淘汰其他项，被更新的项即使访问频率最低也保留
+        self.evict_until_fits(0, Some(index));
+    }
+
+    /// 新项的访问频率为1，放在最低频率的位置。
+    fn make_room_for(&mut self, _key: &K, weight: usize) -> usize {
+        self.evict_until_fits(weight, None);
+        self.bucket_after(NIL, 1)
+    }
+
+    /// 访问频率从低到高，同一频率内从最久未使用到最近使用。
+    fn order(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(
+            self.buckets
+                .indices(&self.frequencies)
+                .flat_map(move |bucket| self.core.entries.indices(&self.buckets.get(bucket).items)),
+        )
+    }
 
-        // 插入新项
-        let timestamp = self.order.len();
-        self.cache.insert(key.clone(), (value, 1, timestamp));
-        // 将键移动到顺序列表的末尾（最近使用）
-        self.order.push_back(key);
+    fn reset(&mut self) {
+        self.buckets.clear();
+        self.frequencies = List::new();
     }
 }
 
+impl_cache!(LFUCache);
+
+
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
+    use std::sync::Arc;
+
     use super::*;
+    use crate::cache::expiry::ManualClock;
+    use crate::cache::stats::RemovalCause;
+
+    fn min_frequency<K: Eq, V>(cache: &LFUCache<K, V>) -> usize {
+        cache.buckets.iter(&cache.frequencies).next().map_or(0, |bucket| bucket.frequency)
+    }
 
     #[test]
     fn test_lfu_cache() {
@@ -100,10 +291,229 @@ mod tests {
         lfu_cache.put("four", 4);
 
         // 检查缓存的状态
-        assert_eq!(lfu_cache.cache.len(), 3);
+        assert_eq!(lfu_cache.core.cache.len(), 3);
         assert_eq!(lfu_cache.get(&"one"), Some(&1));
         assert_eq!(lfu_cache.get(&"two"), Some(&2));
         assert_eq!(lfu_cache.get(&"three"), None); // "three" 应该被淘汰
         assert_eq!(lfu_cache.get(&"four"), Some(&4));
     }
+
+    #[test]
+    fn test_lfu_cache_tie_breaking_and_update() {
+        let mut lfu_cache = LFUCache::new(3);
+
+        lfu_cache.put("one", 1);
+        lfu_cache.put("two", 2);
+        lfu_cache.put("three", 3);
+
+        // 三项访问频率相同，淘汰其中最久未使用的 "one"
+        lfu_cache.put("four", 4);
+        assert!(!lfu_cache.contains_key(&"one"));
+
+        // 更新 "two" 计为一次访问，频率升为2
+        assert_eq!(lfu_cache.put("two", 20), Some(2));
+        assert_eq!(lfu_cache.len(), 3);
+
+        // "three" 与 "four" 频率都为1，淘汰更久未使用的 "three"
+        lfu_cache.put("five", 5);
+        assert!(!lfu_cache.contains_key(&"three"));
+        let keys: Vec<_> = lfu_cache.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec!["four", "five", "two"]);
+        assert_eq!(lfu_cache.get(&"two"), Some(&20));
+    }
+
+    #[test]
+    fn test_lfu_cache_expiry() {
+        let clock = ManualClock::new();
+        let mut lfu_cache = LFUCache::new(2)
+            .with_ttl(Duration::from_secs(10))
+            .with_time_to_idle(Duration::from_secs(5))
+            .with_clock(Arc::new(clock.clone()));
+
+        lfu_cache.put("one", 1);
+        lfu_cache.put_with_ttl("two", 2, Duration::from_secs(3));
+        assert_eq!(lfu_cache.get(&"one"), Some(&1));
+
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(lfu_cache.get(&"two"), None);
+        assert_eq!(lfu_cache.get(&"one"), Some(&1));
+
+        // 过期的键重新写入时视为新项
+        assert_eq!(lfu_cache.put_with_ttl("two", 20, Duration::from_secs(3)), None);
+
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(lfu_cache.purge_expired(), 1);
+        assert_eq!(lfu_cache.get(&"one"), Some(&1));
+
+        // 存活时间不会因访问而顺延
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(lfu_cache.get(&"one"), None);
+        assert!(lfu_cache.is_empty());
+    }
+
+    #[test]
+    fn test_lfu_cache_removal_listener() {
+        let evicted = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&evicted);
+        let mut lfu_cache = LFUCache::new(2)
+            .with_stats()
+            .with_removal_listener(move |k: &&str, _: &i32, cause| sink.lock().unwrap().push((*k, cause)));
+
+        lfu_cache.put("one", 1);
+        lfu_cache.put("two", 2);
+        lfu_cache.get(&"one");
+        lfu_cache.put("three", 3);
+        lfu_cache.clear();
+
+        let mut evicted = evicted.lock().unwrap().clone();
+        assert_eq!(evicted.remove(0), ("two", RemovalCause::Capacity));
+        evicted.sort_by_key(|(k, _)| *k);
+        assert_eq!(evicted, vec![("one", RemovalCause::Explicit), ("three", RemovalCause::Explicit)]);
+        assert_eq!(lfu_cache.stats().evictions, 1);
+    }
+
+    #[test]
+    fn test_lfu_cache_snapshot() {
+        let path = std::env::temp_dir().join(format!("r-tool-lfu-{}.snapshot", std::process::id()));
+        let mut lfu_cache = LFUCache::new(3);
+        lfu_cache.put(1u32, 10u64);
+        lfu_cache.put(2, 20);
+        lfu_cache.put(3, 30);
+        lfu_cache.get(&1);
+        lfu_cache.get(&1);
+        lfu_cache.get(&2);
+        lfu_cache.save_to(&path).unwrap();
+
+        // 访问频率随快照恢复，新项挤掉的是频率最低的 3
+        let mut restored: LFUCache<u32, u64> = LFUCache::new(3);
+        assert_eq!(restored.load_from(&path).unwrap(), 3);
+        assert_eq!(restored.frequency(restored.core.cache[&1]), 3);
+        assert_eq!(min_frequency(&restored), 1);
+        restored.put(4, 40);
+        assert!(!restored.contains_key(&3));
+        assert_eq!(restored.peek(&1), Some(&10));
+        assert_eq!(restored.peek(&2), Some(&20));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_lfu_cache_weighted() {
+        let mut lfu_cache = LFUCache::with_weigher(6, |_: &&str, v: &u32| *v as usize);
+
+        lfu_cache.put("one", 2);
+        lfu_cache.put("two", 2);
+        lfu_cache.put("three", 2);
+        lfu_cache.get(&"two");
+        lfu_cache.get(&"three");
+        assert_eq!(lfu_cache.total_weight(), 6);
+
+        // 访问频率最低的 "one" 被更新后频率为2，仍然保留，淘汰同频率中最久未使用的 "two"
+        assert_eq!(lfu_cache.put("one", 4), Some(2));
+        assert_eq!(lfu_cache.peek(&"two"), None);
+        assert_eq!(lfu_cache.total_weight(), 6);
+
+        assert_eq!(lfu_cache.put("four", 7), None);
+        assert_eq!(lfu_cache.len(), 2);
+        lfu_cache.put("four", 3);
+        assert_eq!(lfu_cache.peek(&"three"), None);
+        assert_eq!(lfu_cache.peek(&"one"), None);
+        assert_eq!(lfu_cache.total_weight(), 3);
+    }
+
+    #[test]
+    fn test_lfu_cache_min_frequency_after_update_eviction() {
+        let mut lfu_cache = LFUCache::with_weigher(6, |_: &&str, v: &u32| *v as usize);
+
+        lfu_cache.put("one", 2);
+        lfu_cache.put("two", 2);
+        lfu_cache.put("three", 2);
+        lfu_cache.get(&"one");
+        lfu_cache.get(&"three");
+        lfu_cache.get(&"three");
+
+        // 更新 "one" 淘汰了频率为1的唯一一项 "two"，最低频率随之变为3
+        assert_eq!(lfu_cache.put("one", 4), Some(2));
+        assert_eq!(lfu_cache.peek(&"two"), None);
+        assert_eq!(min_frequency(&lfu_cache), 3);
+        lfu_cache.put("four", 1);
+        assert_eq!(min_frequency(&lfu_cache), 1);
+        // 删除最低频率的唯一一项后，下一个频率桶成为最低频率
+        assert_eq!(lfu_cache.remove(&"four"), Some(1));
+        assert_eq!(min_frequency(&lfu_cache), 3);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Get(u8),
+        Put(u8, u32),
+        Remove(u8),
+    }
+
+    fn op_strategy() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            (0u8..8).prop_map(Op::Get),
+            (0u8..8, any::<u32>()).prop_map(|(k, v)| Op::Put(k, v)),
+            (0u8..8).prop_map(Op::Remove),
+        ]
+    }
+
+    /// 朴素的参考实现：淘汰时线性扫描 (访问频率, 最近访问时刻) 最小的项。
+    struct Model {
+        capacity: usize,
+        tick: u64,
+        entries: Vec<(u8, u32, usize, u64)>,
+    }
+
+    impl Model {
+        fn touch(&mut self, key: u8) -> Option<&mut (u8, u32, usize, u64)> {
+            self.tick += 1;
+            let tick = self.tick;
+            let entry = self.entries.iter_mut().find(|e| e.0 == key)?;
+            entry.2 += 1;
+            entry.3 = tick;
+            Some(entry)
+        }
+
+        fn put(&mut self, key: u8, value: u32) -> Option<u32> {
+            if let Some(entry) = self.touch(key) {
+                return Some(std::mem::replace(&mut entry.1, value));
+            }
+            if self.capacity == 0 {
+                return None;
+            }
+            if self.entries.len() >= self.capacity {
+                let victim = (0..self.entries.len())
+                    .min_by_key(|&i| (self.entries[i].2, self.entries[i].3))
+                    .unwrap();
+                self.entries.remove(victim);
+            }
+            self.entries.push((key, value, 1, self.tick));
+            None
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_lfu_cache_matches_model(capacity in 0usize..6, ops in prop::collection::vec(op_strategy(), 0..200)) {
+            let mut lfu_cache = LFUCache::new(capacity);
+            let mut model = Model { capacity, tick: 0, entries: Vec::new() };
+
+            for op in ops {
+                match op {
+                    Op::Get(k) => prop_assert_eq!(lfu_cache.get(&k).copied(), model.touch(k).map(|e| e.1)),
+                    Op::Put(k, v) => prop_assert_eq!(lfu_cache.put(k, v), model.put(k, v)),
+                    Op::Remove(k) => {
+                        let expected = model.entries.iter().position(|e| e.0 == k)
+                            .map(|position| model.entries.remove(position).1);
+                        prop_assert_eq!(lfu_cache.remove(&k), expected);
+                    }
+                }
+                let mut expected = model.entries.clone();
+                expected.sort_by_key(|e| (e.2, e.3));
+                let expected: Vec<_> = expected.into_iter().map(|e| (e.0, e.1)).collect();
+                let actual: Vec<_> = lfu_cache.iter().map(|(k, v)| (*k, *v)).collect();
+                prop_assert_eq!(actual, expected);
+            }
+        }
+    }
 }
@@ -0,0 +1,166 @@
+/// 空指针标记，表示没有前驱或后继节点。
+pub(crate) const NIL: usize = usize::MAX;
+
+struct Node<T> {
+    value: Option<T>,
+    prev: usize,
+    next: usize,
+}
+
+/// 双向链表的头尾信息，节点本身存放在 `LinkedSlab` 中。
+///
+/// 多个链表可以共享同一个 `LinkedSlab`，例如 LFU 中每个访问频率对应一个链表。
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct List {
+    head: usize,
+    tail: usize,
+    len: usize,
+}
+
+impl List {
+    pub(crate) fn new() -> Self {
+        List { head: NIL, tail: NIL, len: 0 }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 返回链表尾部节点的下标。
+    pub(crate) fn back(&self) -> Option<usize> {
+        (self.tail != NIL).then_some(self.tail)
+    }
+}
+
+/// 以数组下标代替指针的侵入式双向链表存储。
+///
+/// 节点通过下标访问，释放的槽位会被复用，因此插入、删除和移动节点都是 O(1)。
+pub(crate) struct LinkedSlab<T> {
+    nodes: Vec<Node<T>>,
+    free: Vec<usize>,
+}
+
+impl<T> LinkedSlab<T> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        LinkedSlab {
+            nodes: Vec::with_capacity(capacity),
+            free: Vec::new(),
+        }
+    }
+
+    /// 分配一个未链接的节点，返回其下标。
+    pub(crate) fn insert(&mut self, value: T) -> usize {
+        let node = Node { value: Some(value), prev: NIL, next: NIL };
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// 释放一个节点并取出其值，调用前节点必须已经从链表中摘除。
+    pub(crate) fn remove(&mut self, index: usize) -> T {
+        let value = self.nodes[index].value.take().expect("节点已被释放");
+        self.free.push(index);
+        value
+    }
+
+    pub(crate) fn get(&self, index: usize) -> &T {
+        self.nodes[index].value.as_ref().expect("节点已被释放")
+    }
+
+    pub(crate) fn get_mut(&mut self, index: usize) -> &mut T {
+        self.nodes[index].value.as_mut().expect("节点已被释放")
+    }
+
+    /// 将节点链接到链表尾部。
+    pub(crate) fn push_back(&mut self, list: &mut List, index: usize) {
+        self.nodes[index].prev = list.tail;
+        self.nodes[index].next = NIL;
+        if list.tail != NIL {
+            self.nodes[list.tail].next = index;
+        } else {
+            list.head = index;
+        }
+        list.tail = index;
+        list.len += 1;
+    }
+
+    /// 将节点链接到 `prev` 之后，`prev` 为 `NIL` 时链接到链表头部。
+    pub(crate) fn insert_after(&mut self, list: &mut List, prev: usize, index: usize) {
+        let next = if prev != NIL { self.nodes[prev].next } else { list.head };
+        self.nodes[index].prev = prev;
+        self.nodes[index].next = next;
+        if prev != NIL {
+            self.nodes[prev].next = index;
+        } else {
+            list.head = index;
+        }
+        if next != NIL {
+            self.nodes[next].prev = index;
+        } else {
+            list.tail = index;
+        }
+        list.len += 1;
+    }
+
+    /// 返回节点在链表中的后继。
+    pub(crate) fn next(&self, index: usize) -> Option<usize> {
+        let next = self.nodes[index].next;
+        (next != NIL).then_some(next)
+    }
+
+    /// 将节点从链表中摘除，节点本身仍然保留在存储中。
+    pub(crate) fn unlink(&mut self, list: &mut List, index: usize) {
+        let Node { prev, next, .. } = self.nodes[index];
+        if prev != NIL {
+            self.nodes[prev].next = next;
+        } else {
+            list.head = next;
+        }
+        if next != NIL {
+            self.nodes[next].prev = prev;
+        } else {
+            list.tail = prev;
+        }
+        self.nodes[index].prev = NIL;
+        self.nodes[index].next = NIL;
+        list.len -= 1;
+    }
+
+    /// 将已在链表中的节点移动到链表尾部。
+    pub(crate) fn move_to_back(&mut self, list: &mut List, index: usize) {
+        if list.tail != index {
+            self.unlink(list, index);
+            self.push_back(list, index);
+        }
+    }
+
+    /// 从头到尾遍历链表中的节点下标。
+    pub(crate) fn indices<'a>(&'a self, list: &List) -> impl Iterator<Item = usize> + 'a {
+        let mut cursor = list.head;
+        std::iter::from_fn(move || {
+            if cursor == NIL {
+                return None;
+            }
+            let index = cursor;
+            cursor = self.nodes[index].next;
+            Some(index)
+        })
+    }
+
+    /// 从头到尾遍历链表中的节点值。
+    pub(crate) fn iter<'a>(&'a self, list: &List) -> impl Iterator<Item = &'a T> + 'a {
+        self.indices(list).map(move |index| self.get(index))
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+    }
+}
@@ -0,0 +1,590 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::hash::Hash;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cache::{Cache, CachePolicy};
+use crate::cache::concurrent_cache::{lock, ConcurrentCache};
+use crate::cache::expiry::{Clock, SystemClock};
+use crate::cache::stats::CacheStats;
+
+/// 写入时刻的记录至少达到该项数才清理。
+const MIN_PRUNE_AT: usize = 64;
+
+/// 后台刷新时重新加载值的函数，返回None表示刷新失败，保留旧值。
+pub type Refresher<K, V> = Arc<dyn Fn(&K) -> Option<V> + Send + Sync>;
+
+/// 一次正在进行的加载，等待同一个键的调用方阻塞在这里。
+struct Flight<V> {
+    outcome: Mutex<Outcome<V>>,
+    done: Condvar,
+}
+
+enum Outcome<V> {
+    Pending,
+    Loaded(V),
+    Failed,
+}
+
+impl<V: Clone> Flight<V> {
+    fn new() -> Self {
+        Flight { outcome: Mutex::new(Outcome::Pending), done: Condvar::new() }
+    }
+
+    /// 等待加载结束，加载失败时返回None。
+    fn wait(&self) -> Option<V> {
+        let mut outcome = lock(&self.outcome);
+        loop {
+            match &*outcome {
+                Outcome::Pending => outcome = self.done.wait(outcome).unwrap_or_else(|poisoned| poisoned.into_inner()),
+                Outcome::Loaded(value) => return Some(value.clone()),
+                Outcome::Failed => return None,
+            }
+        }
+    }
+
+    /// 记录加载结果并唤醒所有等待者，只有第一次调用生效。
+    fn finish(&self, result: Outcome<V>) {
+        let mut outcome = lock(&self.outcome);
+        if let Outcome::Pending = *outcome {
+            *outcome = result;
+            self.done.notify_all();
+        }
+    }
+}
+
+/// 负责加载的调用方持有的守卫，加载器返回错误或发生 panic 时也能唤醒等待者。
+struct LoadGuard<'a, K: Eq + Hash, V: Clone> {
+    in_flight: &'a Mutex<HashMap<K, Arc<Flight<V>>>>,
+    key: &'a K,
+    flight: Arc<Flight<V>>,
+}
+
+impl<K: Eq + Hash, V: Clone> Drop for LoadGuard<'_, K, V> {
+    fn drop(&mut self) {
+        lock(self.in_flight).remove(self.key);
+        self.flight.finish(Outcome::Failed);
+    }
+}
+
+enum Role<V> {
+    Leader(Arc<Flight<V>>),
+    Follower(Arc<Flight<V>>),
+}
+
+/// 刷新线程处理完一个键时移除它的刷新标记，刷新函数或写入时的回调发生 panic 也不会让这个键再也无法刷新。
+struct RefreshGuard<'a, K: Eq + Hash> {
+    refreshing: &'a Mutex<HashSet<K>>,
+    key: &'a K,
+}
+
+impl<K: Eq + Hash> Drop for RefreshGuard<'_, K> {
+    fn drop(&mut self) {
+        lock(self.refreshing).remove(self.key);
+    }
+}
+
+struct Refresh<K, V> {
+    interval: Duration,
+    refresher: Refresher<K, V>,
+    // 后台刷新线程的任务队列，第一次需要刷新时创建线程
+    queue: OnceLock<Sender<(K, u64)>>,
+}
+
+/// 加载线程与后台刷新线程共享的状态。
+struct Shared<K, V, C> {
+    cache: ConcurrentCache<K, V, C>,
+    in_flight: Mutex<HashMap<K, Arc<Flight<V>>>>,
+    // 各键最近一次写入的时刻和写入代数，仅在开启刷新时记录
+    written_at: Mutex<HashMap<K, (Instant, u64)>>,
+    // `written_at` 的项数超过该值时清理一次，只在持有 `written_at` 的锁时修改
+    prune_at: AtomicUsize,
+    generation: AtomicU64,
+    refreshing: Mutex<HashSet<K>>,
+    load_stats: Mutex<CacheStats>,
+}
+
+impl<K, V, C> Shared<K, V, C>
+    where
+        K: Eq + Hash + Clone,
+        V: Clone,
+        C: Cache<K, V>,
+{
+    fn record_load(&self, elapsed: Duration) {
+        let mut stats = lock(&self.load_stats);
+        stats.loads += 1;
+        stats.total_load_time += elapsed;
+    }
+
+    fn record_write(&self, key: K, now: Instant) {
+        let mut written_at = lock(&self.written_at);
+        written_at.insert(key, (now, self.generation.fetch_add(1, Ordering::Relaxed)));
+        // 被淘汰的键不会通知到这里，记录数翻倍时清理一次，均摊到每次写入的开销为常数，
+        // 平时不读取缓存的项数，避免每次写入都锁住所有分片
+        if written_at.len() > self.prune_at.load(Ordering::Relaxed) {
+            written_at.retain(|key, _| self.cache.contains_key(key));
+            self.prune_at.store(2 * written_at.len() + MIN_PRUNE_AT, Ordering::Relaxed);
+        }
+    }
+
+    /// 写回刷新结果。检查和写入都在 `written_at` 的锁内完成，刷新期间被移除、清空或重新写入的键不会被旧的刷新结果覆盖。
+    fn put_refreshed(&self, key: K, value: V, generation: u64, now: Instant) {
+        let mut written_at = lock(&self.written_at);
+        let current = written_at.get(&key).is_some_and(|&(_, written)| written == generation);
+        if current && self.cache.contains_key(&key) {
+            written_at.insert(key.clone(), (now, self.generation.fetch_add(1, Ordering::Relaxed)));
+            self.cache.put(key, value);
+        }
+    }
+}
+
+/// 自动加载的线程安全缓存。
+///
+/// 在 `ConcurrentCache` 之上封装“读取，未命中则计算并写入”的流程：同一个键同时未命中时，
+/// 只有一个调用方执行加载器，其余调用方等待并共享加载结果。可选地在写入一段时间后
+/// 于后台刷新值，刷新期间读取仍然返回旧值。
+pub struct LoadingCache<K, V, C = Box<dyn Cache<K, V> + Send>> {
+    shared: Arc<Shared<K, V, C>>,
+    refresh: Option<Refresh<K, V>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<K, V> LoadingCache<K, V>
+    where
+        K: Eq + Hash + Clone + Send + Sync + 'static,
+        V: Clone + Send + Sync + 'static,
+{
+    /// 按指定淘汰策略创建加载缓存，参数与 `ConcurrentCache::new` 相同。
+    ///
+    /// # 参数
+    ///
+    /// * `policy`: CachePolicy - 每个分段使用的淘汰策略
+    /// * `capacity`: usize - 缓存的总容量
+    /// * `shard_count`: usize - 分段数量，至少为1
+    ///
+    /// 返回值：LoadingCache<K, V> - 新创建的加载缓存实例
+    pub fn new(policy: CachePolicy, capacity: usize, shard_count: usize) -> Self {
+        LoadingCache::from_cache(ConcurrentCache::new(policy, capacity, shard_count))
+    }
+}
+
+impl<K, V, C> LoadingCache<K, V, C>
+    where
+        K: Eq + Hash + Clone + Send + Sync + 'static,
+        V: Clone + Send + Sync + 'static,
+        C: Cache<K, V> + Send + 'static,
+{
+    /// 在已有的分段缓存之上创建加载缓存，分段缓存的过期时间、权重等设置保持不变。
+    ///
+    /// # 参数
+    ///
+    /// * `cache`: ConcurrentCache<K, V, C> - 实际存放数据的分段缓存
+    ///
+    /// 返回值：LoadingCache<K, V, C> - 新创建的加载缓存实例
+    pub fn from_cache(cache: ConcurrentCache<K, V, C>) -> Self {
+        LoadingCache {
+            shared: Arc::new(Shared {
+                cache,
+                in_flight: Mutex::new(HashMap::new()),
+                written_at: Mutex::new(HashMap::new()),
+                prune_at: AtomicUsize::new(MIN_PRUNE_AT),
+                generation: AtomicU64::new(0),
+                refreshing: Mutex::new(HashSet::new()),
+                load_stats: Mutex::new(CacheStats::default()),
+            }),
+            refresh: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// 开启写入后刷新：值写入超过 `interval` 后，下一次读取仍返回旧值，
+    /// 同时交给后台刷新线程调用 `refresher` 重新加载。所有键共用一个刷新线程，依次刷新；
+    /// 刷新失败或 `refresher` 发生 panic 时保留旧值，之后的读取会再次尝试。
+    ///
+    /// # 参数
+    ///
+    /// * `interval`: Duration - 写入后多久需要刷新
+    /// * `refresher`: Fn(&K) -> Option<V> - 重新加载值的函数
+    pub fn with_refresh_after_write<F>(mut self, interval: Duration, refresher: F) -> Self
+        where
+            F: Fn(&K) -> Option<V> + Send + Sync + 'static,
+    {
+        self.refresh = Some(Refresh { interval, refresher: Arc::new(refresher), queue: OnceLock::new() });
+        self
+    }
+
+    /// 替换判断是否需要刷新所用的时钟，测试中可以传入 `ManualClock`。
+    ///
+    /// # 参数
+    ///
+    /// * `clock`: Arc<dyn Clock> - 时钟
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 获取指定键的值的克隆，不会触发加载，但可能触发后台刷新。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要获取的键的引用
+    ///
+    /// 返回值：Option<V> - 如果存在则返回值的克隆，否则返回None
+    pub fn get(&self, key: &K) -> Option<V> {
+        let value = self.shared.cache.get(key)?;
+        self.refresh_if_due(key);
+        Some(value)
+    }
+
+    /// 获取指定键的值，未命中时调用 `loader` 加载并写入缓存。
+    ///
+    /// 多个线程同时对同一个键未命中时，只有一个线程执行 `loader`，其余线程等待并得到同一个值。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要获取的键的引用
+    /// * `loader`: FnOnce() -> V - 未命中时计算值的函数
+    ///
+    /// 返回值：V - 缓存中的值或新加载的值
+    pub fn get_or_load<F>(&self, key: &K, loader: F) -> V
+        where
+            F: FnOnce() -> V,
+    {
+        match self.try_get_or_load(key, || Ok::<V, Infallible>(loader())) {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// 获取指定键的值，未命中时调用可能失败的 `loader` 加载并写入缓存，加载失败的结果不会被缓存。
+    ///
+    /// 与 `get_or_load` 一样只有一个线程执行加载；如果它加载失败，等待中的线程会各自重新尝试加载，
+    /// 从而得到自己的错误。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要获取的键的引用
+    /// * `loader`: FnOnce() -> Result<V, E> - 未命中时计算值的函数
+    ///
+    /// 返回值：Result<V, E> - 缓存中的值或新加载的值，加载失败时返回加载器的错误
+    pub fn try_get_or_load<E, F>(&self, key: &K, loader: F) -> Result<V, E>
+        where
+            F: FnOnce() -> Result<V, E>,
+    {
+        let flight = loop {
+            if let Some(value) = self.get(key) {
+                return Ok(value);
+            }
+            match self.join_or_lead(key) {
+                Role::Leader(flight) => break flight,
+                Role::Follower(flight) => {
+                    if let Some(value) = flight.wait() {
+                        return Ok(value);
+                    }
+                }
+            }
+        };
+
+        let guard = LoadGuard { in_flight: &self.shared.in_flight, key, flight };
+        // 在未命中与登记加载之间，其他线程可能刚好完成了加载
+        if let Some(value) = self.shared.cache.peek(key) {
+            guard.flight.finish(Outcome::Loaded(value.clone()));
+            return Ok(value);
+        }
+
+        let start = Instant::now();
+        let result = loader();
+        self.shared.record_load(start.elapsed());
+        let value = result?;
+        // 先写入缓存再结束加载，新来的调用方要么看到加载中的记录，要么直接命中
+        self.put(key.clone(), value.clone());
+        guard.flight.finish(Outcome::Loaded(value.clone()));
+        Ok(value)
+    }
+
+    /// 将键值对写入缓存。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要写入的键
+    /// * `value`: V - 要写入的值
+    ///
+    /// 返回值：Option<V> - 如果键已存在则返回被替换的旧值，否则返回None
+    pub fn put(&self, key: K, value: V) -> Option<V> {
+        if self.refresh.is_some() {
+            self.shared.record_write(key.clone(), self.clock.now());
+        }
+        self.shared.cache.put(key, value)
+    }
+
+    /// 从缓存中移除指定键，返回被移除的值。正在进行的后台刷新不会把它写回。
+    pub fn remove(&self, key: &K) -> Option<V> {
+        let mut written_at = lock(&self.shared.written_at);
+        written_at.remove(key);
+        self.shared.cache.remove(key)
+    }
+
+    /// 返回统计信息，包括底层缓存的命中情况以及加载次数和加载耗时。
+    pub fn stats(&self) -> CacheStats {
+        self.shared.cache.stats() + *lock(&self.shared.load_stats)
+    }
+
+    /// 返回缓存中的项数。
+    pub fn len(&self) -> usize {
+        self.shared.cache.len()
+    }
+
+    /// 判断缓存是否为空。
+    pub fn is_empty(&self) -> bool {
+        self.shared.cache.is_empty()
+    }
+
+    /// 清空缓存。
+    pub fn clear(&self) {
+        let mut written_at = lock(&self.shared.written_at);
+        written_at.clear();
+        self.shared.cache.clear();
+    }
+
+    /// 返回正在进行的加载，没有时登记一个由当前线程负责的加载。
+    fn join_or_lead(&self, key: &K) -> Role<V> {
+        let mut in_flight = lock(&self.shared.in_flight);
+        match in_flight.get(key) {
+            Some(flight) => Role::Follower(Arc::clone(flight)),
+            None => {
+                let flight = Arc::new(Flight::new());
+                in_flight.insert(key.clone(), Arc::clone(&flight));
+                Role::Leader(flight)
+            }
+        }
+    }
+
+    /// 值写入已超过刷新间隔且没有正在进行的刷新时，交给后台刷新线程。
+    fn refresh_if_due(&self, key: &K) {
+        let Some(refresh) = &self.refresh else {
+            return;
+        };
+        let now = self.clock.now();
+        let generation = match lock(&self.shared.written_at).get(key) {
+            Some(&(written_at, generation)) if now.saturating_duration_since(written_at) >= refresh.interval => generation,
+            _ => return,
+        };
+        if !lock(&self.shared.refreshing).insert(key.clone()) {
+            return;
+        }
+        let queue = refresh.queue.get_or_init(|| {
+            spawn_refresher(Arc::clone(&self.shared), Arc::clone(&refresh.refresher), Arc::clone(&self.clock))
+        });
+        if queue.send((key.clone(), generation)).is_err() {
+            lock(&self.shared.refreshing).remove(key);
+        }
+    }
+}
+
+/// 启动后台刷新线程，依次处理队列中的键，加载缓存被丢弃、队列关闭后退出。
+fn spawn_refresher<K, V, C>(shared: Arc<Shared<K, V, C>>, refresher: Refresher<K, V>, clock: Arc<dyn Clock>) -> Sender<(K, u64)>
+    where
+        K: Eq + Hash + Clone + Send + Sync + 'static,
+        V: Clone + Send + Sync + 'static,
+        C: Cache<K, V> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel::<(K, u64)>();
+    thread::spawn(move || {
+        for (key, generation) in receiver {
+            let _guard = RefreshGuard { refreshing: &shared.refreshing, key: &key };
+            // 单个键的刷新发生 panic 时视为刷新失败，刷新线程继续处理其他键
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                let start = Instant::now();
+                let value = refresher(&key);
+                shared.record_load(start.elapsed());
+                if let Some(value) = value {
+                    shared.put_refreshed(key.clone(), value, generation, clock.now());
+                }
+            }));
+        }
+    });
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Barrier;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::cache::expiry::ManualClock;
+
+    #[test]
+    fn test_loading_cache() {
+        let cache = LoadingCache::new(CachePolicy::Lru, 16, 4);
+        let calls = AtomicUsize::new(0);
+        let load = || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            "value".to_string()
+        };
+
+        assert_eq!(cache.get_or_load(&1, load), "value");
+        assert_eq!(cache.get_or_load(&1, load), "value");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.stats().loads, 1);
+
+        // 加载失败的结果不会被缓存
+        assert_eq!(cache.try_get_or_load(&2, || Err("timeout")), Err("timeout"));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.try_get_or_load(&2, || Ok::<_, &str>("two".to_string())), Ok("two".to_string()));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats().loads, 3);
+    }
+
+    #[test]
+    fn test_loading_cache_single_flight() {
+        let cache = LoadingCache::new(CachePolicy::Lru, 16, 4);
+        let calls = AtomicUsize::new(0);
+        let barrier = Barrier::new(8);
+
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    barrier.wait();
+                    let value = cache.get_or_load(&"key", || {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(100));
+                        42
+                    });
+                    assert_eq!(value, 42);
+                });
+            }
+        });
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_loading_cache_failed_leader_wakes_waiters() {
+        let cache = LoadingCache::new(CachePolicy::Lru, 16, 1);
+        let barrier = Barrier::new(2);
+
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                let result = cache.try_get_or_load(&"key", || {
+                    barrier.wait();
+                    thread::sleep(Duration::from_millis(100));
+                    Err("unavailable")
+                });
+                assert_eq!(result, Err("unavailable"));
+            });
+            scope.spawn(|| {
+                barrier.wait();
+                // 负责加载的线程失败后，等待中的线程自己重新加载
+                assert_eq!(cache.try_get_or_load(&"key", || Ok::<_, &str>(7)), Ok(7));
+            });
+        });
+
+        assert_eq!(cache.get(&"key"), Some(7));
+    }
+
+    #[test]
+    fn test_loading_cache_refresh_after_write() {
+        let clock = ManualClock::new();
+        let version = Arc::new(AtomicUsize::new(1));
+        let source = Arc::clone(&version);
+        let cache = LoadingCache::new(CachePolicy::Lru, 16, 1)
+            .with_clock(Arc::new(clock.clone()))
+            .with_refresh_after_write(Duration::from_secs(60), move |_: &&str| Some(source.load(Ordering::SeqCst)));
+
+        assert_eq!(cache.get_or_load(&"config", || version.load(Ordering::SeqCst)), 1);
+        version.store(2, Ordering::SeqCst);
+        assert_eq!(cache.get(&"config"), Some(1));
+
+        // 超过刷新间隔后仍先返回旧值，后台刷新完成后返回新值
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(cache.get(&"config"), Some(1));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.get(&"config") != Some(2) {
+            assert!(Instant::now() < deadline, "后台刷新超时");
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(cache.stats().loads, 2);
+    }
+
+    fn wait_for_refreshes<K: Eq + Hash, V, C>(cache: &LoadingCache<K, V, C>) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !lock(&cache.shared.refreshing).is_empty() {
+            assert!(Instant::now() < deadline, "后台刷新超时");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_loading_cache_refresh_panic() {
+        let clock = ManualClock::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let cache = LoadingCache::new(CachePolicy::Lru, 16, 1)
+            .with_clock(Arc::new(clock.clone()))
+            .with_refresh_after_write(Duration::from_secs(60), move |_: &&str| {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("refresh failed");
+                }
+                Some(2)
+            });
+        cache.put("config", 1);
+        clock.advance(Duration::from_secs(60));
+
+        // 第一次刷新发生 panic，保留旧值，之后的读取可以再次刷新
+        assert_eq!(cache.get(&"config"), Some(1));
+        wait_for_refreshes(&cache);
+        assert_eq!(cache.get(&"config"), Some(1));
+        wait_for_refreshes(&cache);
+        assert_eq!(cache.get(&"config"), Some(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_loading_cache_refresh_does_not_resurrect() {
+        let clock = ManualClock::new();
+        let started = Arc::new(Barrier::new(2));
+        let release = Arc::new(Barrier::new(2));
+        let (started_in, release_in) = (Arc::clone(&started), Arc::clone(&release));
+        let cache = LoadingCache::new(CachePolicy::Lru, 16, 1)
+            .with_clock(Arc::new(clock.clone()))
+            .with_refresh_after_write(Duration::from_secs(60), move |_: &&str| {
+                started_in.wait();
+                release_in.wait();
+                Some(2)
+            });
+
+        for newer in [None, Some(3)] {
+            cache.put("config", 1);
+            clock.advance(Duration::from_secs(60));
+            assert_eq!(cache.get(&"config"), Some(1));
+            started.wait();
+            // 刷新进行期间移除，或写入了更新的值
+            match newer {
+                None => assert_eq!(cache.remove(&"config"), Some(1)),
+                Some(value) => assert_eq!(cache.put("config", value), Some(1)),
+            }
+            release.wait();
+            wait_for_refreshes(&cache);
+            assert_eq!(cache.get(&"config"), newer);
+        }
+    }
+
+    #[test]
+    fn test_loading_cache_prunes_write_times() {
+        let cache = LoadingCache::new(CachePolicy::Lru, 16, 1)
+            .with_refresh_after_write(Duration::from_secs(60), |key: &u32| Some(*key));
+        for key in 0..1000u32 {
+            cache.put(key, key);
+        }
+        // 被淘汰的键的写入时刻按记录数翻倍清理，记录数不超过缓存项数的两倍加上清理阈值
+        let written = lock(&cache.shared.written_at).len();
+        assert!(written <= 2 * 16 + 2 * MIN_PRUNE_AT, "{}", written);
+        assert!(lock(&cache.shared.written_at).contains_key(&999));
+    }
+}
@@ -1,50 +1,198 @@
-use std::collections::{HashMap, LinkedList};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::cache::Cache;
+use crate::cache::cache_core::{impl_cache, CacheCore, Policy};
+use crate::cache::linked_slab::List;
+use crate::cache::snapshot::{self, Codec, SnapshotError, SnapshotKind};
 
 pub struct LRUCache<K, V> {
-    capacity: usize,
-    cache: HashMap<K, (V, usize)>,
-    order: LinkedList<K>,
+    core: CacheCore<K, V, ()>,
+    // 访问顺序，头部为最久未使用，尾部为最近使用
+    order: List,
 }
 
 impl<K, V> LRUCache<K, V>
     where
         K: Eq + std::hash::Hash + Clone,
 {
+    /// 创建一个新的LRU缓存实例。
+    ///
+    /// # 参数
+    ///
+    /// * `capacity`: usize - 缓存的容量
+    ///
+    /// 返回值：LRUCache<K, V> - 新创建的LRU缓存实例
     pub fn new(capacity: usize) -> Self {
-        LRUCache {
-            capacity,
-            cache: HashMap::with_capacity(capacity),
-            order: LinkedList::new(),
-        }
+        LRUCache { core: CacheCore::new(capacity), order: List::new() }
+    }
+
+    /// 创建一个按权重限制容量的LRU缓存实例，缓存项的权重由 `weigher` 计算，
+    /// 淘汰会持续到所有项的权重之和不超过 `max_weight`。
+    ///
+    /// 权重超过 `max_weight` 的项不会放入缓存，而是直接以 `RemovalCause::Capacity` 通知移除回调。
+    ///
+    /// # 参数
+    ///
+    /// * `max_weight`: usize - 最大总权重
+    /// * `weigher`: Fn(&K, &V) -> usize - 计算缓存项权重的函数
+    ///
+    /// 返回值：LRUCache<K, V> - 新创建的LRU缓存实例
+    pub fn with_weigher<F>(max_weight: usize, weigher: F) -> Self
+        where
+            F: Fn(&K, &V) -> usize + Send + Sync + 'static,
+    {
+        LRUCache { core: CacheCore::weighted(max_weight, Box::new(weigher)), order: List::new() }
     }
 
+    /// 获取缓存中指定键的值，并将该键标记为最近使用。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要获取的键的引用
+    ///
+    /// 返回值：Option<&V> - 如果存在则返回对应值的引用，否则返回None
     pub fn get(&mut self, key: &K) -> Option<&V> {
-        if let Some((value, timestamp)) = self.cache.get_mut(key) {
-            *timestamp = self.order.len();
-            let popped_key = self.order.pop_front().unwrap();
-            self.order.push_back(popped_key);
-            Some(value)
-        } else {
-            None
+        self.lookup(key)
+    }
+
+    /// 将键值对插入缓存中，如果缓存已满则淘汰最久未使用的项。
+    ///
+    /// 如果键已存在，则替换值并将该键标记为最近使用。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    ///
+    /// 返回值：Option<V> - 如果键已存在则返回被替换的旧值，否则返回None
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value, None)
+    }
+
+    /// 将键值对插入缓存中，并为该项单独指定存活时间。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    /// * `ttl`: Duration - 该项的存活时间，覆盖默认存活时间
+    ///
+    /// 返回值：Option<V> - 如果键已存在且未过期则返回被替换的旧值，否则返回None
+    pub fn put_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        self.insert(key, value, Some(ttl))
+    }
+
+    /// 从最久未使用的项开始淘汰，直到再放入 `incoming` 的权重也不超出最大总权重，
+    /// `keep` 指向的项不会被淘汰。
+    fn evict_until_fits(&mut self, incoming: usize, keep: Option<usize>) {
+        while self.core.weights.overflows(incoming) {
+            let Some(index) = self.core.entries.indices(&self.order).find(|&index| Some(index) != keep) else {
+                break;
+            };
+            self.evict_index(index);
         }
     }
+}
 
-    pub fn put(&mut self, key: K, value: V) {
-        if self.cache.len() >= self.capacity {
-            if let Some(oldest_key) = self.order.pop_front() {
-                self.cache.remove(&oldest_key);
-            }
+impl<K, V> LRUCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone + Codec,
+        V: Codec,
+{
+    /// 将缓存中未过期的项保存到快照文件，保留访问顺序和剩余的过期时间。
+    ///
+    /// 先写入同目录下的临时文件再重命名，保存中途失败不会破坏已有的快照。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 快照文件路径
+    ///
+    /// 返回值：Result<(), SnapshotError> - 写入失败时返回错误
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        snapshot::save(path.as_ref(), SnapshotKind::Lru, 0, &self.snapshot_entries(|&meta| meta))
+    }
+
+    /// 从快照文件加载缓存项，替换缓存中现有的内容。
+    ///
+    /// 快照全部校验通过后才会修改缓存，加载失败时缓存保持不变。过期时间按保存时的剩余时长重新计算，
+    /// 超出容量的项按淘汰策略淘汰，加载过程不计入统计信息。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 快照文件路径
+    ///
+    /// 返回值：Result<usize, SnapshotError> - 加载后缓存中的项数
+    pub fn load_from<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, SnapshotError> {
+        let (_, entries) = snapshot::load::<K, V, ()>(path.as_ref(), SnapshotKind::Lru)?;
+        Cache::clear(self);
+        for (key, value, remaining, ()) in entries {
+            self.restore(key, value, remaining, ());
         }
+        self.evict_until_fits(0, None);
+        Ok(self.core.cache.len())
+    }
+}
+
+impl<K, V> Policy<K, V> for LRUCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone,
+{
+    type Meta = ();
+
+    fn core(&self) -> &CacheCore<K, V, ()> {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut CacheCore<K, V, ()> {
+        &mut self.core
+    }
+
+    fn link(&mut self, index: usize) {
+        self.core.entries.push_back(&mut self.order, index);
+    }
+
+    fn unlink(&mut self, index: usize) {
+        self.core.entries.unlink(&mut self.order, index);
+    }
+
+    fn on_hit(&mut self, index: usize) {
+        // 将键移动到顺序列表的末尾（最近使用）
+        self.core.entries.move_to_back(&mut self.order, index);
+    }
+
+    fn on_update(&mut self, index: usize, _old_weight: usize) {
+        self.core.entries.move_to_back(&mut self.order, index);
+        // 新值更重时可能需要淘汰其他项
+        self.evict_until_fits(0, Some(index));
+    }
 
-        let timestamp = self.order.len();
-        self.cache.insert(key.clone(), (value, timestamp));
-        self.order.push_back(key);
+    fn make_room_for(&mut self, _key: &K, weight: usize) {
+        self.evict_until_fits(weight, None);
+    }
+
+    /// 从最久未使用到最近使用。
+    fn order(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(self.core.entries.indices(&self.order))
+    }
+
+    fn reset(&mut self) {
+        self.order = List::new();
     }
 }
 
+impl_cache!(LRUCache);
+
+
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
+    use std::sync::{Arc, Mutex};
+
     use super::*;
+    use crate::cache::expiry::ManualClock;
+    use crate::cache::stats::{CacheStats, RemovalCause};
 
     #[test]
     fn test_lru_cache() {
@@ -58,10 +206,248 @@ mod tests {
 
         lru_cache.put("four", 4);
 
-        assert_eq!(lru_cache.cache.len(), 3);
+        assert_eq!(lru_cache.core.cache.len(), 3);
         assert_eq!(lru_cache.get(&"one"), Some(&1));
         assert_eq!(lru_cache.get(&"two"), None);
         assert_eq!(lru_cache.get(&"three"), Some(&3));
         assert_eq!(lru_cache.get(&"four"), Some(&4));
     }
+
+    #[test]
+    fn test_lru_cache_reorders_accessed_key() {
+        let mut lru_cache = LRUCache::new(3);
+
+        lru_cache.put("one", 1);
+        lru_cache.put("two", 2);
+        lru_cache.put("three", 3);
+
+        // 访问中间的 "two"，最久未使用的应该仍然是 "one"
+        assert_eq!(lru_cache.get(&"two"), Some(&2));
+        // 更新 "one" 会刷新其位置，最久未使用的变为 "three"
+        assert_eq!(lru_cache.put("one", 10), Some(1));
+        assert_eq!(lru_cache.len(), 3);
+
+        lru_cache.put("four", 4);
+        assert_eq!(lru_cache.get(&"three"), None);
+        let keys: Vec<_> = lru_cache.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec!["two", "one", "four"]);
+    }
+
+    #[test]
+    fn test_lru_cache_expiry() {
+        let clock = ManualClock::new();
+        let mut lru_cache = LRUCache::new(3)
+            .with_ttl(Duration::from_secs(10))
+            .with_clock(Arc::new(clock.clone()));
+
+        lru_cache.put("one", 1);
+        lru_cache.put_with_ttl("two", 2, Duration::from_secs(2));
+        lru_cache.put("three", 3);
+
+        clock.advance(Duration::from_secs(2));
+        // "two" 已过期，读取时被惰性清理
+        assert!(!lru_cache.contains_key(&"two"));
+        assert_eq!(lru_cache.get(&"two"), None);
+        assert_eq!(lru_cache.len(), 2);
+
+        clock.advance(Duration::from_secs(8));
+        assert_eq!(lru_cache.peek(&"one"), None);
+        assert_eq!(lru_cache.purge_expired(), 2);
+        assert!(lru_cache.is_empty());
+    }
+
+    #[test]
+    fn test_lru_cache_time_to_idle() {
+        let clock = ManualClock::new();
+        let mut lru_cache = LRUCache::new(3)
+            .with_time_to_idle(Duration::from_secs(5))
+            .with_clock(Arc::new(clock.clone()));
+
+        lru_cache.put("one", 1);
+        lru_cache.put("two", 2);
+
+        // 访问 "one" 会顺延其空闲过期时间
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(lru_cache.get(&"one"), Some(&1));
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(lru_cache.get(&"one"), Some(&1));
+        assert_eq!(lru_cache.get(&"two"), None);
+    }
+
+    #[test]
+    fn test_lru_cache_stats_and_listener() {
+        let clock = ManualClock::new();
+        let removed = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&removed);
+        let mut lru_cache = LRUCache::new(2)
+            .with_stats()
+            .with_clock(Arc::new(clock.clone()))
+            .with_removal_listener(move |k: &&str, v: &i32, cause| sink.lock().unwrap().push((*k, *v, cause)));
+
+        lru_cache.put("one", 1);
+        lru_cache.put("two", 2);
+        lru_cache.put("one", 10);
+        lru_cache.put("three", 3);
+        assert_eq!(lru_cache.get(&"one"), Some(&10));
+        assert_eq!(lru_cache.get(&"two"), None);
+        lru_cache.remove(&"one");
+        lru_cache.put_with_ttl("four", 4, Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
+        lru_cache.purge_expired();
+
+        assert_eq!(*removed.lock().unwrap(), vec![
+            ("one", 1, RemovalCause::Replaced),
+            ("two", 2, RemovalCause::Capacity),
+            ("one", 10, RemovalCause::Explicit),
+            ("four", 4, RemovalCause::Expired),
+        ]);
+        let stats = lru_cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        assert_eq!((stats.insertions, stats.evictions, stats.expirations), (5, 1, 1));
+        assert_eq!(stats.hit_ratio(), 0.5);
+    }
+
+    #[test]
+    fn test_lru_cache_snapshot() {
+        let path = std::env::temp_dir().join(format!("r-tool-lru-{}.snapshot", std::process::id()));
+        let clock = ManualClock::new();
+        let mut lru_cache = LRUCache::new(3).with_clock(Arc::new(clock.clone()));
+        lru_cache.put(1u32, "one".to_string());
+        lru_cache.put_with_ttl(2, "two".to_string(), Duration::from_secs(10));
+        lru_cache.put(3, "three".to_string());
+        lru_cache.get(&1);
+        clock.advance(Duration::from_secs(4));
+        lru_cache.save_to(&path).unwrap();
+
+        // 加载替换原有内容，并保留访问顺序
+        let mut restored = LRUCache::new(3).with_clock(Arc::new(clock.clone()));
+        restored.put(9, "nine".to_string());
+        assert_eq!(restored.load_from(&path).unwrap(), 3);
+        assert_eq!(restored.iter().map(|(key, _)| *key).collect::<Vec<_>>(), vec![2, 3, 1]);
+        assert_eq!(restored.stats(), CacheStats::default());
+        // 剩余的存活时间为6秒
+        clock.advance(Duration::from_secs(5));
+        assert!(restored.contains_key(&2));
+        clock.advance(Duration::from_secs(1));
+        assert!(!restored.contains_key(&2));
+
+        // 容量更小时淘汰最久未使用的项
+        let mut smaller: LRUCache<u32, String> = LRUCache::new(2).with_clock(Arc::new(clock.clone()));
+        assert_eq!(smaller.load_from(&path).unwrap(), 2);
+        assert_eq!(smaller.iter().map(|(key, _)| *key).collect::<Vec<_>>(), vec![3, 1]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_lru_cache_weighted() {
+        let removed = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&removed);
+        let mut lru_cache = LRUCache::with_weigher(10, |_: &&str, v: &String| v.len())
+            .with_removal_listener(move |k: &&str, _: &String, cause| sink.lock().unwrap().push((*k, cause)));
+        assert_eq!(lru_cache.capacity(), 10);
+
+        lru_cache.put("one", "aaaa".to_string());
+        lru_cache.put("two", "bbb".to_string());
+        lru_cache.put("three", "cc".to_string());
+        assert_eq!(lru_cache.total_weight(), 9);
+
+        // 需要连续淘汰两个最久未使用的项才能放下
+        lru_cache.put("four", "dddddd".to_string());
+        assert_eq!(lru_cache.total_weight(), 8);
+        let keys: Vec<_> = lru_cache.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec!["three", "four"]);
+
+        // 比最大总权重还重的项被拒绝，其余项保持不变
+        assert_eq!(lru_cache.put("five", "e".repeat(11)), None);
+        assert_eq!(lru_cache.len(), 2);
+
+        // 更新为更重的值时淘汰其他项，更新后的值放不下时旧值也被移除
+        assert_eq!(lru_cache.put("three", "cccc".to_string()), Some("cc".to_string()));
+        assert_eq!(lru_cache.total_weight(), 10);
+        assert_eq!(lru_cache.put("four", "d".repeat(11)), Some("dddddd".to_string()));
+        assert_eq!(lru_cache.total_weight(), 4);
+
+        assert_eq!(*removed.lock().unwrap(), vec![
+            ("one", RemovalCause::Capacity),
+            ("two", RemovalCause::Capacity),
+            ("five", RemovalCause::Capacity),
+            ("three", RemovalCause::Replaced),
+            ("four", RemovalCause::Replaced),
+            ("four", RemovalCause::Capacity),
+        ]);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Get(u8),
+        Peek(u8),
+        Put(u8, u32),
+        Remove(u8),
+    }
+
+    fn op_strategy() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            (0u8..8).prop_map(Op::Get),
+            (0u8..8).prop_map(Op::Peek),
+            (0u8..8, any::<u32>()).prop_map(|(k, v)| Op::Put(k, v)),
+            (0u8..8).prop_map(Op::Remove),
+        ]
+    }
+
+    /// 朴素的参考实现：按最久未使用到最近使用的顺序保存键值对。
+    struct Model {
+        capacity: usize,
+        entries: Vec<(u8, u32)>,
+    }
+
+    impl Model {
+        fn touch(&mut self, key: u8) -> Option<u32> {
+            let position = self.entries.iter().position(|(k, _)| *k == key)?;
+            let entry = self.entries.remove(position);
+            self.entries.push(entry);
+            Some(entry.1)
+        }
+
+        fn put(&mut self, key: u8, value: u32) -> Option<u32> {
+            if let Some(old) = self.touch(key) {
+                self.entries.last_mut().unwrap().1 = value;
+                return Some(old);
+            }
+            if self.capacity == 0 {
+                return None;
+            }
+            if self.entries.len() >= self.capacity {
+                self.entries.remove(0);
+            }
+            self.entries.push((key, value));
+            None
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_lru_cache_matches_model(capacity in 0usize..6, ops in prop::collection::vec(op_strategy(), 0..200)) {
+            let mut lru_cache = LRUCache::new(capacity);
+            let mut model = Model { capacity, entries: Vec::new() };
+
+            for op in ops {
+                match op {
+                    Op::Get(k) => prop_assert_eq!(lru_cache.get(&k).copied(), model.touch(k)),
+                    Op::Peek(k) => {
+                        let expected = model.entries.iter().find(|(mk, _)| *mk == k).map(|(_, v)| *v);
+                        prop_assert_eq!(lru_cache.peek(&k).copied(), expected);
+                    }
+                    Op::Put(k, v) => prop_assert_eq!(lru_cache.put(k, v), model.put(k, v)),
+                    Op::Remove(k) => {
+                        let expected = model.entries.iter().position(|(mk, _)| *mk == k)
+                            .map(|position| model.entries.remove(position).1);
+                        prop_assert_eq!(lru_cache.remove(&k), expected);
+                    }
+                }
+                let actual: Vec<_> = lru_cache.iter().map(|(k, v)| (*k, *v)).collect();
+                prop_assert_eq!(&actual, &model.entries);
+                prop_assert_eq!(lru_cache.len(), model.entries.len());
+            }
+        }
+    }
 }
@@ -1,3 +1,22 @@
+pub mod arc_cache;
+#[cfg(any(test, feature = "bench"))]
+pub mod bench;
+pub mod bloom_filter;
+pub mod cache_trait;
+pub mod concurrent_cache;
+pub mod expiry;
 pub mod fifo_cache;
 pub mod lfu_cache;
+pub mod loading_cache;
 pub mod lru_cache;
+pub mod snapshot;
+pub mod stats;
+pub mod tiered_cache;
+pub mod tiny_lfu_cache;
+pub mod weigher;
+mod cache_core;
+mod disk_store;
+mod frequency_sketch;
+mod linked_slab;
+
+pub use cache_trait::{Cache, CachePolicy};
@@ -0,0 +1,450 @@
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+
+use crate::cache::expiry::Remaining;
+
+/// 快照文件开头的魔数。
+const MAGIC: &[u8; 4] = b"RTCS";
+
+/// 当前的快照格式版本。
+const VERSION: u16 = 1;
+
+/// 文件末尾 SHA-256 校验和的长度。
+const CHECKSUM_LEN: usize = 32;
+
+/// 快照的键和值的二进制编码，整数按小端序写入。
+///
+/// 已为常用的整数、字符串、字节数组、`Option` 和二元组实现，
+/// 自定义类型实现该 trait 后即可保存到快照中。
+pub trait Codec: Sized {
+    /// 将自身追加写入 `out`。
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// 从 `input` 的开头读取一个值并前移 `input`，数据不完整或不合法时返回None。
+    fn decode(input: &mut &[u8]) -> Option<Self>;
+}
+
+/// 从输入开头取出指定长度的字节。
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if input.len() < len {
+        return None;
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Some(head)
+}
+
+macro_rules! impl_codec_for_int {
+    ($($ty:ty),*) => {
+        $(
+            impl Codec for $ty {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(input: &mut &[u8]) -> Option<Self> {
+                    let bytes = take(input, std::mem::size_of::<$ty>())?;
+                    Some(<$ty>::from_le_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_codec_for_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl Codec for usize {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Self> {
+        usize::try_from(u64::decode(input)?).ok()
+    }
+}
+
+impl Codec for isize {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as i64).encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Self> {
+        isize::try_from(i64::decode(input)?).ok()
+    }
+}
+
+impl Codec for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Self> {
+        match u8::decode(input)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Codec for () {
+    fn encode(&self, _out: &mut Vec<u8>) {}
+
+    fn decode(_input: &mut &[u8]) -> Option<Self> {
+        Some(())
+    }
+}
+
+impl Codec for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Self> {
+        let len = usize::decode(input)?;
+        String::from_utf8(take(input, len)?.to_vec()).ok()
+    }
+}
+
+impl<T: Codec> Codec for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        for item in self {
+            item.encode(out);
+        }
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Self> {
+        let len = usize::decode(input)?;
+        // 长度可能来自损坏的数据，预分配不超过剩余字节数
+        let mut items = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+            items.push(T::decode(input)?);
+        }
+        Some(items)
+    }
+}
+
+impl<T: Codec> Codec for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Some(value) => {
+                out.push(1);
+                value.encode(out);
+            }
+            None => out.push(0),
+        }
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Self> {
+        match u8::decode(input)? {
+            0 => Some(None),
+            1 => Some(Some(T::decode(input)?)),
+            _ => None,
+        }
+    }
+}
+
+impl<A: Codec, B: Codec> Codec for (A, B) {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+        self.1.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Self> {
+        Some((A::decode(input)?, B::decode(input)?))
+    }
+}
+
+impl Codec for Duration {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_secs().encode(out);
+        self.subsec_nanos().encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Option<Self> {
+        let secs = u64::decode(input)?;
+        let nanos = u32::decode(input)?;
+        (nanos < 1_000_000_000).then(|| Duration::new(secs, nanos))
+    }
+}
+
+/// 保存或加载快照时的错误。
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// 读写文件失败
+    Io(io::Error),
+    /// 文件不是缓存快照
+    InvalidFormat,
+    /// 快照的格式版本不受支持
+    UnsupportedVersion(u16),
+    /// 快照由另一种淘汰策略的缓存保存
+    PolicyMismatch,
+    /// 快照不完整或内容已损坏，例如写入中途被中断
+    Corrupted,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "读写快照文件失败: {}", err),
+            SnapshotError::InvalidFormat => write!(f, "不是缓存快照文件"),
+            SnapshotError::UnsupportedVersion(version) => write!(f, "不支持的快照版本: {}", version),
+            SnapshotError::PolicyMismatch => write!(f, "快照的淘汰策略与缓存不一致"),
+            SnapshotError::Corrupted => write!(f, "快照不完整或已损坏"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        SnapshotError::Io(err)
+    }
+}
+
+/// 保存快照的缓存类型，加载时必须一致。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SnapshotKind {
+    Lru = 1,
+    Lfu = 2,
+    Fifo = 3,
+    Arc = 4,
+    TinyLfu = 5,
+}
+
+/// 快照中的一项：键、值、剩余的过期时长以及淘汰策略相关的元数据。
+pub(crate) type SnapshotEntry<K, V, M> = (K, V, Remaining, M);
+
+/// 解析后的快照：策略相关的头部数据和各项。
+pub(crate) type Snapshot<K, V, M> = (u64, Vec<SnapshotEntry<K, V, M>>);
+
+/// 将缓存项写入快照文件。
+///
+/// 文件格式为：魔数、版本、缓存类型、策略相关的头部数据、项数、各项，最后是此前所有字节的
+/// SHA-256 校验和。通过 `write_atomic` 写入，写入中途失败不会破坏已有的快照。
+pub(crate) fn save<K, V, M>(path: &Path, kind: SnapshotKind, header: u64, entries: &[SnapshotEntry<&K, &V, M>]) -> Result<(), SnapshotError>
+    where
+        K: Codec,
+        V: Codec,
+        M: Codec,
+{
+    let mut buf = Vec::new();
+    buf.extend_from_slice(MAGIC);
+    VERSION.encode(&mut buf);
+    (kind as u8).encode(&mut buf);
+    header.encode(&mut buf);
+    entries.len().encode(&mut buf);
+    for (key, value, remaining, meta) in entries {
+        key.encode(&mut buf);
+        value.encode(&mut buf);
+        remaining.encode(&mut buf);
+        meta.encode(&mut buf);
+    }
+    let checksum = sha256(&buf);
+    buf.extend_from_slice(&checksum);
+
+    write_atomic(path, &buf)?;
+    Ok(())
+}
+
+/// 读取并校验快照文件，全部解析成功后才返回，不会得到部分数据。
+pub(crate) fn load<K, V, M>(path: &Path, kind: SnapshotKind) -> Result<Snapshot<K, V, M>, SnapshotError>
+    where
+        K: Codec,
+        V: Codec,
+        M: Codec,
+{
+    let data = fs::read(path)?;
+    if data.len() < MAGIC.len() || &data[..MAGIC.len()] != MAGIC {
+        return Err(SnapshotError::InvalidFormat);
+    }
+    let mut input = &data[MAGIC.len()..];
+    let version = u16::decode(&mut input).ok_or(SnapshotError::Corrupted)?;
+    if version != VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+    if data.len() < MAGIC.len() + 2 + CHECKSUM_LEN {
+        return Err(SnapshotError::Corrupted);
+    }
+    let (content, checksum) = data.split_at(data.len() - CHECKSUM_LEN);
+    if sha256(content) != checksum {
+        return Err(SnapshotError::Corrupted);
+    }
+
+    let mut input = &content[MAGIC.len() + 2..];
+    let decoded = decode_body(&mut input, kind)?;
+    if !input.is_empty() {
+        return Err(SnapshotError::Corrupted);
+    }
+    Ok(decoded)
+}
+
+fn decode_body<K, V, M>(input: &mut &[u8], kind: SnapshotKind) -> Result<Snapshot<K, V, M>, SnapshotError>
+    where
+        K: Codec,
+        V: Codec,
+        M: Codec,
+{
+    let found = u8::decode(input).ok_or(SnapshotError::Corrupted)?;
+    if found != kind as u8 {
+        return Err(SnapshotError::PolicyMismatch);
+    }
+    let header = u64::decode(input).ok_or(SnapshotError::Corrupted)?;
+    let len = usize::decode(input).ok_or(SnapshotError::Corrupted)?;
+    // 项数可能来自损坏的数据，预分配不超过剩余字节数
+    let mut entries = Vec::with_capacity(len.min(input.len()));
+    for _ in 0..len {
+        entries.push(decode_entry(input).ok_or(SnapshotError::Corrupted)?);
+    }
+    Ok((header, entries))
+}
+
+fn decode_entry<K: Codec, V: Codec, M: Codec>(input: &mut &[u8]) -> Option<SnapshotEntry<K, V, M>> {
+    Some((K::decode(input)?, V::decode(input)?, Remaining::decode(input)?, M::decode(input)?))
+}
+
+/// 先写入同目录下的 `<文件名>.<进程号>-<随机数>.tmp` 并刷到磁盘，再重命名覆盖目标文件并同步目录，
+/// 写入中途失败时目标文件保持不变，临时文件被删除。多个进程同时保存时各自使用不同的临时文件，最后完成的覆盖之前的结果。
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp_path = tmp_path(path);
+    let result = write_file(&tmp_path, data).and_then(|_| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result?;
+    sync_dir(path)
+}
+
+fn write_file(path: &Path, data: &[u8]) -> io::Result<()> {
+    // 临时文件名已经存在时报错，而不是覆盖其他进程正在写入的文件
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(|name| name.to_os_string()).unwrap_or_default();
+    name.push(format!(".{}-{:016x}.tmp", std::process::id(), rand::random::<u64>()));
+    path.with_file_name(name)
+}
+
+/// 文件名符合 `write_atomic` 的临时文件格式时，返回它对应的目标文件名。
+pub(crate) fn tmp_target(name: &str) -> Option<&str> {
+    let (target, suffix) = name.strip_suffix(".tmp")?.rsplit_once('.')?;
+    let (pid, random) = suffix.split_once('-')?;
+    let is_pid = !pid.is_empty() && pid.bytes().all(|b| b.is_ascii_digit());
+    let is_random = random.len() == 16 && random.bytes().all(|b| b.is_ascii_hexdigit());
+    (is_pid && is_random).then_some(target)
+}
+
+/// 同步目标文件所在的目录，保证重命名在断电后不会丢失。
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => File::open(dir)?.sync_all(),
+        _ => File::open(".")?.sync_all(),
+    }
+}
+
+/// Windows 不能打开目录进行同步，重命名由文件系统保证。
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn sha256(data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut hasher = Sha256::new();
+    hasher.input(data);
+    let mut checksum = [0; CHECKSUM_LEN];
+    hasher.result(&mut checksum);
+    checksum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::Cache;
+    use crate::cache::lfu_cache::LFUCache;
+    use crate::cache::lru_cache::LRUCache;
+
+    fn snapshot_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("r-tool-{}-{}.snapshot", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_codec_round_trip() {
+        let value = (vec!["one".to_string(), "二".to_string()], (Some(-7i64), (true, Duration::from_millis(1500))));
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        let mut input = buf.as_slice();
+        assert_eq!(Codec::decode(&mut input), Some(value));
+        assert!(input.is_empty());
+
+        // 数据不完整时解码失败
+        let mut input = &buf[..buf.len() - 1];
+        assert_eq!(<(Vec<String>, (Option<i64>, (bool, Duration)))>::decode(&mut input), None);
+    }
+
+    #[test]
+    fn test_snapshot_rejects_bad_files() {
+        let path = snapshot_path("bad-files");
+        let mut cache = LRUCache::new(4);
+        cache.put("one".to_string(), 1u32);
+        cache.put("two".to_string(), 2u32);
+        cache.save_to(&path).unwrap();
+        // 临时文件名各不相同，保存后不留下临时文件
+        assert_ne!(tmp_path(&path), tmp_path(&path));
+        let tmp_name = tmp_path(&path).file_name().unwrap().to_string_lossy().into_owned();
+        assert_eq!(tmp_target(&tmp_name), path.file_name().unwrap().to_str());
+        assert_eq!(tmp_target("notes.tmp"), None);
+        assert_eq!(tmp_target("cache.bin.12-notrandom.tmp"), None);
+        let prefix = format!("{}.", path.file_name().unwrap().to_string_lossy());
+        let leftovers = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+            .count();
+        assert_eq!(leftovers, 0);
+        let data = fs::read(&path).unwrap();
+
+        let mut restored: LRUCache<String, u32> = LRUCache::new(4);
+        // 写入中途中断导致的截断
+        fs::write(&path, &data[..data.len() - 5]).unwrap();
+        assert!(matches!(restored.load_from(&path), Err(SnapshotError::Corrupted)));
+        // 内容被篡改
+        let mut flipped = data.clone();
+        flipped[12] ^= 0xff;
+        fs::write(&path, &flipped).unwrap();
+        assert!(matches!(restored.load_from(&path), Err(SnapshotError::Corrupted)));
+        // 版本不受支持
+        let mut future = data.clone();
+        future[4] = 9;
+        fs::write(&path, &future).unwrap();
+        assert!(matches!(restored.load_from(&path), Err(SnapshotError::UnsupportedVersion(9))));
+        fs::write(&path, b"not a snapshot").unwrap();
+        assert!(matches!(restored.load_from(&path), Err(SnapshotError::InvalidFormat)));
+        assert!(restored.is_empty());
+
+        fs::write(&path, &data).unwrap();
+        let mut lfu_cache: LFUCache<String, u32> = LFUCache::new(4);
+        assert!(matches!(lfu_cache.load_from(&path), Err(SnapshotError::PolicyMismatch)));
+        assert_eq!(restored.load_from(&path).unwrap(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,156 @@
+use std::ops::Add;
+use std::time::Duration;
+
+/// 缓存项被移除的原因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemovalCause {
+    /// 缓存已满，按淘汰策略被淘汰
+    Capacity,
+    /// 超过存活时间或空闲时间而过期
+    Expired,
+    /// 被 `remove`、`clear` 等显式移除
+    Explicit,
+    /// 被同一个键的新值替换
+    Replaced,
+}
+
+/// 缓存项被移除时的回调，参数为被移除的键、值和移除原因。
+pub type RemovalListener<K, V> = Box<dyn Fn(&K, &V, RemovalCause) + Send + Sync>;
+
+/// 缓存统计信息的快照。
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    /// 命中次数
+    pub hits: u64,
+    /// 未命中次数
+    pub misses: u64,
+    /// 写入次数，包括替换已有的键
+    pub insertions: u64,
+    /// 因容量不足被淘汰的项数
+    pub evictions: u64,
+    /// 因过期被清理的项数
+    pub expirations: u64,
+    /// 加载次数，仅由加载型缓存记录
+    pub loads: u64,
+    /// 加载耗时总和
+    pub total_load_time: Duration,
+}
+
+impl CacheStats {
+    /// 返回请求总数，即命中与未命中次数之和。
+    pub fn requests(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    /// 返回命中率，没有任何请求时返回1.0。
+    pub fn hit_ratio(&self) -> f64 {
+        match self.requests() {
+            0 => 1.0,
+            requests => self.hits as f64 / requests as f64,
+        }
+    }
+
+    /// 返回平均加载耗时，没有加载记录时返回0。
+    pub fn average_load_time(&self) -> Duration {
+        match self.loads {
+            0 => Duration::ZERO,
+            // 按纳秒计算，避免次数截断为 u32 后为0
+            loads => Duration::from_nanos((self.total_load_time.as_nanos() / loads as u128) as u64),
+        }
+    }
+}
+
+impl Add for CacheStats {
+    type Output = CacheStats;
+
+    fn add(self, other: CacheStats) -> CacheStats {
+        CacheStats {
+            hits: self.hits + other.hits,
+            misses: self.misses + other.misses,
+            insertions: self.insertions + other.insertions,
+            evictions: self.evictions + other.evictions,
+            expirations: self.expirations + other.expirations,
+            loads: self.loads + other.loads,
+            total_load_time: self.total_load_time + other.total_load_time,
+        }
+    }
+}
+
+/// 记录统计信息并通知移除回调，被各淘汰策略共享。
+pub(crate) struct Recorder<K, V> {
+    stats: Option<CacheStats>,
+    listener: Option<RemovalListener<K, V>>,
+}
+
+impl<K, V> Recorder<K, V> {
+    pub(crate) fn new() -> Self {
+        Recorder { stats: None, listener: None }
+    }
+
+    pub(crate) fn enable_stats(&mut self) {
+        self.stats.get_or_insert_with(CacheStats::default);
+    }
+
+    pub(crate) fn set_listener(&mut self, listener: RemovalListener<K, V>) {
+        self.listener = Some(listener);
+    }
+
+    pub(crate) fn snapshot(&self) -> CacheStats {
+        self.stats.unwrap_or_default()
+    }
+
+    pub(crate) fn record_hit(&mut self) {
+        if let Some(stats) = &mut self.stats {
+            stats.hits += 1;
+        }
+    }
+
+    pub(crate) fn record_miss(&mut self) {
+        if let Some(stats) = &mut self.stats {
+            stats.misses += 1;
+        }
+    }
+
+    pub(crate) fn record_insertion(&mut self) {
+        if let Some(stats) = &mut self.stats {
+            stats.insertions += 1;
+        }
+    }
+
+    /// 记录一次移除并通知回调。
+    pub(crate) fn record_removal(&mut self, key: &K, value: &V, cause: RemovalCause) {
+        if let Some(stats) = &mut self.stats {
+            match cause {
+                RemovalCause::Capacity => stats.evictions += 1,
+                RemovalCause::Expired => stats.expirations += 1,
+                RemovalCause::Explicit | RemovalCause::Replaced => {}
+            }
+        }
+        if let Some(listener) = &self.listener {
+            listener(key, value, cause);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_stats() {
+        let stats = CacheStats::default();
+        assert_eq!(stats.hit_ratio(), 1.0);
+        assert_eq!(stats.average_load_time(), Duration::ZERO);
+
+        let stats = CacheStats { hits: 3, misses: 1, loads: 2, total_load_time: Duration::from_millis(10), ..stats };
+        assert_eq!(stats.requests(), 4);
+        assert_eq!(stats.hit_ratio(), 0.75);
+        assert_eq!(stats.average_load_time(), Duration::from_millis(5));
+        let many = CacheStats { loads: 1 << 32, total_load_time: Duration::from_secs(1 << 32), ..stats };
+        assert_eq!(many.average_load_time(), Duration::from_secs(1));
+
+        let total = stats + CacheStats { misses: 4, evictions: 1, ..CacheStats::default() };
+        assert_eq!(total.hit_ratio(), 3.0 / 8.0);
+        assert_eq!(total.evictions, 1);
+    }
+}
@@ -0,0 +1,218 @@
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::cache::Cache;
+use crate::cache::concurrent_cache::lock;
+use crate::cache::disk_store::DiskStore;
+use crate::cache::lru_cache::LRUCache;
+use crate::cache::snapshot::Codec;
+use crate::cache::stats::RemovalCause;
+
+/// 被内存层淘汰、等待写入磁盘的项，值已编码。
+type Spilled<K> = Arc<Mutex<Vec<(K, Vec<u8>)>>>;
+
+/// 内存加磁盘的两级缓存。
+///
+/// 热点数据保存在内存中的 `LRUCache`，被内存层因容量不足淘汰的项写入按总字节数限制大小的磁盘目录，
+/// 磁盘层有自己的 LRU 顺序。读取磁盘层中的项时将其移回内存，同时可能把内存中最久未使用的项挤到磁盘。
+/// 一个键只会存在于其中一层。
+///
+/// 磁盘读写失败时对应的项被丢弃，相当于被淘汰，不会返回错误。过期或被显式移除的项不会写入磁盘，
+/// 写入磁盘的项不再过期。
+pub struct TieredCache<K, V> {
+    memory: LRUCache<K, V>,
+    spilled: Spilled<K>,
+    disk: DiskStore<K>,
+}
+
+impl<K, V> TieredCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone + Codec + Send + 'static,
+        V: Codec,
+{
+    /// 打开两级缓存，磁盘目录不存在时创建。
+    ///
+    /// 目录中已有的缓存项会被重新索引，写入中断留下的临时文件和无法识别的缓存项文件会被删除，
+    /// 目录中的其他文件保持不动。
+    ///
+    /// # 参数
+    ///
+    /// * `dir`: P - 磁盘层的目录，不应与其他缓存共享
+    /// * `memory`: LRUCache<K, V> - 内存层，可以预先设置权重函数、过期时间和统计，其移除回调会被替换
+    /// * `disk_max_bytes`: u64 - 磁盘层所有文件的最大总字节数
+    ///
+    /// 返回值：io::Result<TieredCache<K, V>> - 创建目录或清理文件失败时返回错误
+    pub fn open<P: AsRef<Path>>(dir: P, memory: LRUCache<K, V>, disk_max_bytes: u64) -> io::Result<Self> {
+        let disk = DiskStore::open(dir.as_ref(), disk_max_bytes)?;
+        let spilled: Spilled<K> = Arc::new(Mutex::new(Vec::new()));
+        let queue = Arc::clone(&spilled);
+        let memory = memory.with_removal_listener(move |key: &K, value: &V, cause| {
+            if cause == RemovalCause::Capacity {
+                let mut bytes = Vec::new();
+                value.encode(&mut bytes);
+                lock(&queue).push((key.clone(), bytes));
+            }
+        });
+        Ok(TieredCache { memory, spilled, disk })
+    }
+
+    /// 获取指定键的值，值在磁盘层时移回内存层。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要获取的键的引用
+    ///
+    /// 返回值：Option<&V> - 如果存在则返回对应值的引用，否则返回None
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if !self.memory.contains_key(key) {
+            if let Some(value) = self.disk.take(key).and_then(|bytes| decode(&bytes)) {
+                self.memory.put(key.clone(), value);
+                self.spill();
+            }
+        }
+        self.memory.get(key)
+    }
+
+    /// 将键值对写入内存层，磁盘层中同一个键的旧值被删除。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    pub fn put(&mut self, key: K, value: V) {
+        self.disk.remove(&key);
+        self.memory.put(key, value);
+        self.spill();
+    }
+
+    /// 从两级缓存中移除指定的键。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要移除的键的引用
+    ///
+    /// 返回值：Option<V> - 如果存在则返回被移除的值，否则返回None
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        match self.memory.remove(key) {
+            Some(value) => Some(value),
+            None => self.disk.take(key).and_then(|bytes| decode(&bytes)),
+        }
+    }
+
+    /// 检查任一层中是否存在指定的键，不会移动该项。
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.memory.contains_key(key) || self.disk.contains_key(key)
+    }
+
+    /// 返回两级缓存中的总项数。
+    pub fn len(&self) -> usize {
+        self.memory.len() + self.disk.len()
+    }
+
+    /// 检查两级缓存是否都为空。
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 返回内存层中的项数。
+    pub fn memory_len(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// 返回磁盘层中的项数。
+    pub fn disk_len(&self) -> usize {
+        self.disk.len()
+    }
+
+    /// 返回磁盘层所有文件的总字节数。
+    pub fn disk_bytes(&self) -> u64 {
+        self.disk.total_bytes()
+    }
+
+    /// 清空两级缓存，并删除磁盘层的所有缓存项文件。
+    pub fn clear(&mut self) {
+        self.memory.clear();
+        self.disk.clear();
+    }
+
+    /// 将内存层刚淘汰的项写入磁盘层。
+    fn spill(&mut self) {
+        let spilled = std::mem::take(&mut *lock(&self.spilled));
+        for (key, bytes) in spilled {
+            self.disk.insert(key, &bytes);
+        }
+    }
+}
+
+/// 解码磁盘中的值，有多余的字节时视为损坏。
+fn decode<V: Codec>(bytes: &[u8]) -> Option<V> {
+    let mut input = bytes;
+    V::decode(&mut input).filter(|_| input.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_tiered_cache_spill_and_promote() {
+        let dir = std::env::temp_dir().join(format!("r-tool-tiered-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut cache = TieredCache::open(&dir, LRUCache::new(2), 1024).unwrap();
+        cache.put(1u32, "one".to_string());
+        cache.put(2, "two".to_string());
+        cache.put(3, "three".to_string());
+
+        // 最久未使用的 1 被挤到磁盘
+        assert_eq!(cache.memory_len(), 2);
+        assert_eq!(cache.disk_len(), 1);
+        assert!(cache.contains_key(&1));
+
+        // 读取时移回内存，2 被挤到磁盘
+        assert_eq!(cache.get(&1), Some(&"one".to_string()));
+        assert_eq!(cache.disk_len(), 1);
+        assert!(cache.disk.contains_key(&2));
+
+        // 重新写入磁盘中的键会删除磁盘上的旧值
+        cache.put(2, "deux".to_string());
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.remove(&3), Some("three".to_string()));
+        assert_eq!(cache.get(&2), Some(&"deux".to_string()));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tiered_cache_reopen() {
+        let dir = std::env::temp_dir().join(format!("r-tool-tiered-reopen-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut cache = TieredCache::open(&dir, LRUCache::new(1), 1024).unwrap();
+        for key in 0..4u32 {
+            cache.put(key, vec![key as u8; 16]);
+        }
+        assert_eq!(cache.disk_len(), 3);
+        let disk_bytes = cache.disk_bytes();
+        drop(cache);
+
+        // 写入中断留下的临时文件在启动时被清理，磁盘上已有的项重新可用
+        let orphan = format!("{}.entry.1-{:016x}.tmp", "0".repeat(64), 0);
+        fs::write(dir.join(&orphan), b"partial").unwrap();
+        let mut cache: TieredCache<u32, Vec<u8>> = TieredCache::open(&dir, LRUCache::new(1), 1024).unwrap();
+        assert_eq!(cache.disk_len(), 3);
+        assert_eq!(cache.disk_bytes(), disk_bytes);
+        assert!(!dir.join(&orphan).exists());
+        assert_eq!(cache.get(&0), Some(&vec![0; 16]));
+
+        // 磁盘上限变小时按修改时间删除最旧的文件
+        drop(cache);
+        let cache: TieredCache<u32, Vec<u8>> = TieredCache::open(&dir, LRUCache::new(1), disk_bytes / 3 * 2).unwrap();
+        assert_eq!(cache.disk_len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
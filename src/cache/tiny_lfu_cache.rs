@@ -0,0 +1,445 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::cache::Cache;
+use crate::cache::cache_core::{impl_cache, CacheCore, Policy};
+use crate::cache::frequency_sketch::FrequencySketch;
+use crate::cache::linked_slab::List;
+use crate::cache::snapshot::{self, Codec, SnapshotError, SnapshotKind};
+
+/// 缓存项所在的区域。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Segment {
+    // 新写入的项先进入窗口，约占容量的1%
+    Window = 0,
+    // 主区中只被访问过一次的项，淘汰时优先从这里选择
+    Probation = 1,
+    // 主区中被再次访问过的项，约占主区的80%
+    Protected = 2,
+}
+
+/// 缓存项上记录的键哈希和所在区域。
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EntryMeta {
+    hash: u64,
+    segment: Segment,
+}
+
+/// W-TinyLFU 缓存。
+///
+/// 新写入的项先进入一个很小的 LRU 窗口，被挤出窗口后要与主区中最先被淘汰的项比较访问频率，
+/// 频率更高者才能留在主区。访问频率由 Count-Min Sketch 估算，包括已经不在缓存中的键，
+/// 因此既能保留长期的热点数据，又不会让一次性的顺序扫描冲掉缓存。主区是分段 LRU：
+/// 再次被访问的项从试用区晋升到保护区。
+pub struct TinyLFUCache<K, V> {
+    core: CacheCore<K, V, EntryMeta>,
+    // 按 Segment 取下标，每个链表头部为最久未使用
+    lists: [List; 3],
+    segment_weights: [usize; 3],
+    sketch: FrequencySketch,
+    hasher: RandomState,
+}
+
+impl<K, V> TinyLFUCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone,
+{
+    /// 创建一个新的W-TinyLFU缓存实例。
+    ///
+    /// # 参数
+    ///
+    /// * `capacity`: usize - 缓存的容量
+    ///
+    /// 返回值：TinyLFUCache<K, V> - 新创建的W-TinyLFU缓存实例
+    pub fn new(capacity: usize) -> Self {
+        TinyLFUCache {
+            core: CacheCore::new(capacity),
+            lists: [List::new(); 3],
+            segment_weights: [0; 3],
+            sketch: FrequencySketch::with_capacity(capacity),
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// 创建一个按权重限制容量的W-TinyLFU缓存实例，缓存项的权重由 `weigher` 计算，
+    /// 淘汰会持续到所有项的权重之和不超过 `max_weight`。
+    ///
+    /// 权重超过 `max_weight` 的项不会放入缓存，而是直接以 `RemovalCause::Capacity` 通知移除回调。
+    ///
+    /// # 参数
+    ///
+    /// * `max_weight`: usize - 最大总权重
+    /// * `weigher`: Fn(&K, &V) -> usize - 计算缓存项权重的函数
+    ///
+    /// 返回值：TinyLFUCache<K, V> - 新创建的W-TinyLFU缓存实例
+    pub fn with_weigher<F>(max_weight: usize, weigher: F) -> Self
+        where
+            F: Fn(&K, &V) -> usize + Send + Sync + 'static,
+    {
+        TinyLFUCache { core: CacheCore::weighted(max_weight, Box::new(weigher)), ..TinyLFUCache::new(0) }
+    }
+
+    /// 获取缓存中指定键的值，并记录一次访问，未命中的键同样会被记录。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &K - 要获取的键的引用
+    ///
+    /// 返回值：Option<&V> - 如果存在则返回对应值的引用，否则返回None
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.sketch.increment(self.hasher.hash_one(key));
+        self.lookup(key)
+    }
+
+    /// 将键值对插入缓存中。新项先进入窗口，被挤出窗口时与主区中最先被淘汰的项比较访问频率，
+    /// 频率较低的一方被淘汰。
+    ///
+    /// 如果键已存在，则替换值，并与 `get` 一样计为一次访问。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    ///
+    /// 返回值：Option<V> - 如果键已存在则返回被替换的旧值，否则返回None
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        self.write(key, value, None)
+    }
+
+    /// 将键值对插入缓存中，并为该项单独指定存活时间。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: K - 要插入的键
+    /// * `value`: V - 要插入的值
+    /// * `ttl`: Duration - 该项的存活时间，覆盖默认存活时间
+    ///
+    /// 返回值：Option<V> - 如果键已存在且未过期则返回被替换的旧值，否则返回None
+    pub fn put_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        self.write(key, value, Some(ttl))
+    }
+
+    /// 写入前记录一次访问，被拒绝的写入同样计入访问频率。
+    fn write(&mut self, key: K, value: V, ttl: Option<Duration>) -> Option<V> {
+        self.sketch.increment(self.hasher.hash_one(&key));
+        self.insert(key, value, ttl)
+    }
+
+    /// 处理一次命中：窗口和保护区内移动到末尾，试用区的项晋升到保护区。
+    fn on_access(&mut self, index: usize) {
+        match self.core.entries.get(index).meta.segment {
+            Segment::Window => self.relink(index, Segment::Window),
+            Segment::Probation => {
+                self.relink(index, Segment::Protected);
+                // 保护区超出配额时，把其中最久未使用的项降级回试用区
+                while self.segment_weights[Segment::Protected as usize] > self.protected_max() {
+                    let Some(demoted) = self.first_except(Segment::Protected, &[index]) else {
+                        break;
+                    };
+                    self.relink(demoted, Segment::Probation);
+                }
+            }
+            Segment::Protected => self.relink(index, Segment::Protected),
+        }
+    }
+
+    /// 淘汰项直到总权重不超出上限，`keep` 指向的项不会被淘汰。
+    fn evict(&mut self, keep: Option<usize>) {
+        let keep: &[usize] = match &keep {
+            Some(index) => std::slice::from_ref(index),
+            None => &[],
+        };
+        // 窗口超出配额时，窗口中最久未使用的项作为候选者尝试进入主区
+        while self.segment_weights[Segment::Window as usize] > self.window_max() {
+            let Some(candidate) = self.first_except(Segment::Window, keep) else {
+                break;
+            };
+            self.admit(candidate, keep);
+        }
+        // 单项权重较大时主区可能放不下，按试用区、保护区、窗口的顺序继续淘汰
+        while self.core.weights.overflows(0) {
+            let victim = [Segment::Probation, Segment::Protected, Segment::Window]
+                .into_iter()
+                .find_map(|segment| self.first_except(segment, keep));
+            let Some(index) = victim else {
+                break;
+            };
+            self.evict_index(index);
+        }
+    }
+
+    /// 候选者进入试用区，主区超出配额时与主区中最先被淘汰的项比较访问频率，淘汰频率较低的一方。
+    fn admit(&mut self, candidate: usize, keep: &[usize]) {
+        self.relink(candidate, Segment::Probation);
+        let candidate_frequency = self.sketch.frequency(self.core.entries.get(candidate).meta.hash);
+        let mut excluded = keep.to_vec();
+        excluded.push(candidate);
+        while self.segment_weights[Segment::Probation as usize] + self.segment_weights[Segment::Protected as usize] > self.main_max() {
+            let victim = [Segment::Probation, Segment::Protected]
+                .into_iter()
+                .find_map(|segment| self.first_except(segment, &excluded));
+            let Some(victim) = victim else {
+                break;
+            };
+            if candidate_frequency > self.sketch.frequency(self.core.entries.get(victim).meta.hash) {
+                self.evict_index(victim);
+            } else {
+                self.evict_index(candidate);
+                return;
+            }
+        }
+    }
+
+    /// 返回指定区域中最久未使用且不在 `excluded` 中的项。
+    fn first_except(&self, segment: Segment, excluded: &[usize]) -> Option<usize> {
+        self.core.entries.indices(&self.lists[segment as usize]).find(|index| !excluded.contains(index))
+    }
+
+    fn window_max(&self) -> usize {
+        (self.core.weights.max_weight() / 100).max(1)
+    }
+
+    fn main_max(&self) -> usize {
+        self.core.weights.max_weight().saturating_sub(self.window_max())
+    }
+
+    fn protected_max(&self) -> usize {
+        self.main_max() * 4 / 5
+    }
+
+    /// 将节点移动到指定区域的末尾。
+    fn relink(&mut self, index: usize, to: Segment) {
+        let entry = self.core.entries.get(index);
+        let (from, weight) = (entry.meta.segment, entry.weight);
+        self.core.entries.unlink(&mut self.lists[from as usize], index);
+        self.segment_weights[from as usize] -= weight;
+        self.core.entries.push_back(&mut self.lists[to as usize], index);
+        self.segment_weights[to as usize] += weight;
+        self.core.entries.get_mut(index).meta.segment = to;
+    }
+}
+
+impl<K, V> TinyLFUCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone + Codec,
+        V: Codec,
+{
+    /// 将缓存中未过期的项保存到快照文件，保留各项所在的区域、区域内的顺序、
+    /// 估算的访问频率和剩余的过期时间。不在缓存中的键的访问频率不会保存。
+    ///
+    /// 先写入同目录下的临时文件再重命名，保存中途失败不会破坏已有的快照。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 快照文件路径
+    ///
+    /// 返回值：Result<(), SnapshotError> - 写入失败时返回错误
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        let entries = self.snapshot_entries(|meta| (meta.segment as u8, self.sketch.frequency(meta.hash)));
+        snapshot::save(path.as_ref(), SnapshotKind::TinyLfu, 0, &entries)
+    }
+
+    /// 从快照文件加载缓存项，替换缓存中现有的内容。
+    ///
+    /// 快照全部校验通过后才会修改缓存，加载失败时缓存保持不变。各项恢复到保存时所在的区域，
+    /// 并按保存的访问频率补记到频率估算中，过期时间按保存时的剩余时长重新计算，
+    /// 超出容量的项按淘汰策略淘汰，加载过程不计入统计信息。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 快照文件路径
+    ///
+    /// 返回值：Result<usize, SnapshotError> - 加载后缓存中的项数
+    pub fn load_from<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, SnapshotError> {
+        let (_, entries) = snapshot::load::<K, V, (u8, u8)>(path.as_ref(), SnapshotKind::TinyLfu)?;
+        Cache::clear(self);
+        self.sketch.ensure_capacity(entries.len());
+        for (key, value, remaining, (segment, frequency)) in entries {
+            let segment = match segment {
+                1 => Segment::Probation,
+                2 => Segment::Protected,
+                _ => Segment::Window,
+            };
+            // 哈希种子每个实例不同，需要重新计算，放入缓存的项补记保存时的访问频率
+            let hash = self.hasher.hash_one(&key);
+            if self.restore(key, value, remaining, EntryMeta { hash, segment }).is_some() {
+                for _ in self.sketch.frequency(hash)..frequency {
+                    self.sketch.increment(hash);
+                }
+            }
+        }
+        self.evict(None);
+        Ok(self.core.cache.len())
+    }
+}
+
+impl<K, V> Policy<K, V> for TinyLFUCache<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone,
+{
+    type Meta = EntryMeta;
+
+    fn core(&self) -> &CacheCore<K, V, EntryMeta> {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut CacheCore<K, V, EntryMeta> {
+        &mut self.core
+    }
+
+    fn link(&mut self, index: usize) {
+        let entry = self.core.entries.get(index);
+        let (segment, weight) = (entry.meta.segment, entry.weight);
+        self.core.entries.push_back(&mut self.lists[segment as usize], index);
+        self.segment_weights[segment as usize] += weight;
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let entry = self.core.entries.get(index);
+        let (segment, weight) = (entry.meta.segment, entry.weight);
+        self.core.entries.unlink(&mut self.lists[segment as usize], index);
+        self.segment_weights[segment as usize] -= weight;
+    }
+
+    fn on_hit(&mut self, index: usize) {
+        self.on_access(index);
+    }
+
+    fn on_update(&mut self, index: usize, old_weight: usize) {
+        let entry = self.core.entries.get(index);
+        let (segment, weight) = (entry.meta.segment, entry.weight);
+        self.segment_weights[segment as usize] = self.segment_weights[segment as usize] - old_weight + weight;
+        self.on_access(index);
+        // 新值更重时可能需要淘汰其他项
+        self.evict(Some(index));
+    }
+
+    /// 新项先进入窗口，写入之后再按窗口和主区的配额淘汰。
+    fn make_room_for(&mut self, key: &K, _weight: usize) -> EntryMeta {
+        EntryMeta { hash: self.hasher.hash_one(key), segment: Segment::Window }
+    }
+
+    fn after_insert(&mut self, _index: usize) {
+        self.sketch.ensure_capacity(self.core.cache.len());
+        self.evict(None);
+    }
+
+    /// 按试用区、保护区、窗口的顺序，区域内从最久未使用到最近使用。
+    fn order(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(
+            [Segment::Probation, Segment::Protected, Segment::Window]
+                .into_iter()
+                .flat_map(move |segment| self.core.entries.indices(&self.lists[segment as usize])),
+        )
+    }
+
+    /// 已记录的访问频率保留。
+    fn reset(&mut self) {
+        self.lists = [List::new(); 3];
+        self.segment_weights = [0; 3];
+    }
+}
+
+impl_cache!(TinyLFUCache);
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::cache::expiry::ManualClock;
+
+    #[test]
+    fn test_tiny_lfu_cache() {
+        let mut tiny_lfu_cache = TinyLFUCache::new(3);
+
+        tiny_lfu_cache.put("one", 1);
+        tiny_lfu_cache.put("two", 2);
+        tiny_lfu_cache.put("three", 3);
+        assert_eq!(tiny_lfu_cache.len(), 3);
+        assert_eq!(tiny_lfu_cache.get(&"one"), Some(&1));
+        assert_eq!(tiny_lfu_cache.get(&"one"), Some(&1));
+
+        // 访问频率更高的 "one" 不会被只访问过一次的新项挤掉
+        tiny_lfu_cache.put("four", 4);
+        tiny_lfu_cache.put("five", 5);
+        assert_eq!(tiny_lfu_cache.len(), 3);
+        assert_eq!(tiny_lfu_cache.peek(&"one"), Some(&1));
+
+        assert_eq!(tiny_lfu_cache.put("one", 10), Some(1));
+        assert_eq!(tiny_lfu_cache.remove(&"one"), Some(10));
+        assert!(!tiny_lfu_cache.contains_key(&"one"));
+        assert_eq!(tiny_lfu_cache.len(), 2);
+    }
+
+    #[test]
+    fn test_tiny_lfu_cache_scan_resistance() {
+        let mut tiny_lfu_cache = TinyLFUCache::new(100);
+
+        for _ in 0..5 {
+            for key in 0..50u32 {
+                if tiny_lfu_cache.get(&key).is_none() {
+                    tiny_lfu_cache.put(key, key);
+                }
+            }
+        }
+        // 热点数据持续被访问时，夹杂其中的一次性顺序扫描无法通过准入过滤
+        for key in 1000..11_000u32 {
+            for key in [key, key % 50] {
+                if tiny_lfu_cache.get(&key).is_none() {
+                    tiny_lfu_cache.put(key, key);
+                }
+            }
+        }
+
+        let hot = (0..50u32).filter(|key| tiny_lfu_cache.contains_key(key)).count();
+        assert_eq!(hot, 50);
+        assert_eq!(tiny_lfu_cache.len(), 100);
+    }
+
+    #[test]
+    fn test_tiny_lfu_cache_snapshot() {
+        let path = std::env::temp_dir().join(format!("r-tool-tiny-lfu-{}.snapshot", std::process::id()));
+        let mut tiny_lfu_cache = TinyLFUCache::new(10);
+        for key in 0..10u32 {
+            tiny_lfu_cache.put(key, key as u64);
+        }
+        for _ in 0..4 {
+            tiny_lfu_cache.get(&3);
+        }
+        tiny_lfu_cache.save_to(&path).unwrap();
+
+        let mut restored = TinyLFUCache::new(10);
+        assert_eq!(restored.load_from(&path).unwrap(), 10);
+        assert_eq!(restored.iter().collect::<Vec<_>>(), tiny_lfu_cache.iter().collect::<Vec<_>>());
+        let index = restored.core.cache[&3];
+        assert_eq!(restored.core.entries.get(index).meta.segment, Segment::Protected);
+        // 访问频率补记到新实例的频率估算中
+        assert!(restored.sketch.frequency(restored.core.entries.get(index).meta.hash) >= 5);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_tiny_lfu_cache_expiry_and_weight() {
+        let clock = ManualClock::new();
+        let mut tiny_lfu_cache = TinyLFUCache::with_weigher(10, |_: &&str, v: &String| v.len())
+            .with_time_to_idle(Duration::from_secs(5))
+            .with_clock(Arc::new(clock.clone()));
+
+        tiny_lfu_cache.put("one", "aaaa".to_string());
+        tiny_lfu_cache.put("two", "bbbb".to_string());
+        assert_eq!(tiny_lfu_cache.put("big", "c".repeat(11)), None);
+        assert_eq!(tiny_lfu_cache.total_weight(), 8);
+
+        tiny_lfu_cache.put("three", "ccccc".to_string());
+        assert!(tiny_lfu_cache.total_weight() <= 10);
+
+        clock.advance(Duration::from_secs(5));
+        tiny_lfu_cache.purge_expired();
+        assert!(tiny_lfu_cache.is_empty());
+        assert_eq!(tiny_lfu_cache.total_weight(), 0);
+    }
+}
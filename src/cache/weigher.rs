@@ -0,0 +1,80 @@
+/// 计算缓存项权重的函数，参数为键和值，返回该项占用的容量，例如值的字节数。
+pub type Weigher<K, V> = Box<dyn Fn(&K, &V) -> usize + Send + Sync>;
+
+/// 记录缓存的总权重，被各淘汰策略共享。
+///
+/// 未设置权重函数时每项权重为1，此时总权重就是项数，最大总权重就是容量。
+pub(crate) struct Weights<K, V> {
+    weigher: Option<Weigher<K, V>>,
+    max_weight: usize,
+    total_weight: usize,
+}
+
+impl<K, V> Weights<K, V> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Weights { weigher: None, max_weight: capacity, total_weight: 0 }
+    }
+
+    pub(crate) fn weighted(max_weight: usize, weigher: Weigher<K, V>) -> Self {
+        Weights { weigher: Some(weigher), max_weight, total_weight: 0 }
+    }
+
+    pub(crate) fn weigh(&self, key: &K, value: &V) -> usize {
+        self.weigher.as_ref().map_or(1, |weigher| weigher(key, value))
+    }
+
+    pub(crate) fn max_weight(&self) -> usize {
+        self.max_weight
+    }
+
+    pub(crate) fn total_weight(&self) -> usize {
+        self.total_weight
+    }
+
+    /// 判断单个项能否放入缓存，比最大总权重还重的项会被直接拒绝。
+    pub(crate) fn admits(&self, weight: usize) -> bool {
+        weight <= self.max_weight
+    }
+
+    /// 判断再加入指定权重后是否超出最大总权重。
+    pub(crate) fn overflows(&self, incoming: usize) -> bool {
+        self.total_weight.saturating_add(incoming) > self.max_weight
+    }
+
+    pub(crate) fn add(&mut self, weight: usize) {
+        self.total_weight += weight;
+    }
+
+    pub(crate) fn sub(&mut self, weight: usize) {
+        self.total_weight -= weight;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.total_weight = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weights() {
+        let mut weights: Weights<&str, i32> = Weights::new(2);
+        assert_eq!(weights.weigh(&"one", &1), 1);
+        weights.add(1);
+        assert!(!weights.overflows(1));
+        weights.add(1);
+        assert!(weights.overflows(1));
+
+        let mut weights: Weights<&str, String> = Weights::weighted(10, Box::new(|_, v: &String| v.len()));
+        assert_eq!(weights.weigh(&"one", &"hello".to_string()), 5);
+        assert!(weights.admits(10));
+        assert!(!weights.admits(11));
+        weights.add(8);
+        assert!(weights.overflows(3));
+        weights.sub(8);
+        assert_eq!(weights.total_weight(), 0);
+        assert_eq!(weights.max_weight(), 10);
+    }
+}
@@ -61,6 +61,17 @@ impl Convertor {
         String::from_utf8(decoded_bytes).unwrap()
     }
 
+    /// 将Base64编码的字符串解码为字节数组，适用于非UTF-8的二进制数据
+    ///
+    /// # 参数
+    ///
+    /// * `data`: &str - Base64编码的字符串
+    ///
+    /// 返回值：Result<Vec<u8>, base64::DecodeError> - 解码后的字节数组或错误
+    pub fn base64_to_vec(data: &str) -> Result<Vec<u8>, base64::DecodeError> {
+        decode(data.as_bytes())
+    }
+
     /// 将字符串转换为十六进制表示
     ///
     /// # 参数
@@ -250,6 +261,10 @@ mod tests {
         let string = Convertor::base64_to_bytes("aGVsbG8gd29ybGQ=");
 
         assert_eq!(string, "hello world");
+
+        let bytes = Convertor::base64_to_vec(&Convertor::bytes_to_base64(&[0, 159, 255])).unwrap();
+        assert_eq!(bytes, vec![0, 159, 255]);
+        assert!(Convertor::base64_to_vec("not base64!").is_err());
     }
 
     #[test]
@@ -1,5 +1,12 @@
-use rand::Rng;
+use rand::distributions::Alphanumeric;
+use rand::rngs::{OsRng, StdRng};
+use rand::{Rng, RngCore, SeedableRng};
 
+/// 随机数工具。
+///
+/// `random_*` 系列函数使用线程本地的伪随机数生成器，适合测试数据、抽样等普通场景，不保证可以用于安全用途；
+/// 令牌、验证码、密钥等需要不可预测的场景使用 `secure_*` 系列函数，它们直接从操作系统的密码学安全随机数生成器读取。
+/// 需要重现随机序列时使用 `SeededRandom`。
 pub struct RandomUtil;
 
 impl RandomUtil {
@@ -14,8 +21,7 @@ impl RandomUtil {
     ///
     /// 返回生成的随机整数。
     pub fn random_int(min: i32, max: i32) -> i32 {
-        let mut rng = rand::thread_rng();
-        rng.gen_range(min..=max)
+        random_int(&mut rand::thread_rng(), min, max)
     }
 
     /// 生成指定范围内的随机浮点数。
@@ -29,8 +35,7 @@ impl RandomUtil {
     ///
     /// 返回生成的随机浮点数。
     pub fn random_float(min: f64, max: f64) -> f64 {
-        let mut rng = rand::thread_rng();
-        rng.gen_range(min..=max)
+        random_float(&mut rand::thread_rng(), min, max)
     }
 
     /// 生成随机布尔值。
@@ -39,8 +44,7 @@ impl RandomUtil {
     ///
     /// 返回生成的随机布尔值。
     pub fn random_bool() -> bool {
-        let mut rng = rand::thread_rng();
-        rng.gen()
+        random_bool(&mut rand::thread_rng())
     }
 
     /// 生成随机小写字母。
@@ -49,12 +53,13 @@ impl RandomUtil {
     ///
     /// 返回生成的随机小写字母。
     pub fn random_char() -> char {
-        let mut rng = rand::thread_rng();
-        rng.gen_range(b'a'..=b'z') as char
+        random_char(&mut rand::thread_rng())
     }
 
     /// 生成指定长度的随机字符串，包含小写字母、大写字母和数字。
     ///
+    /// 结果不保证不可预测，不要用作令牌或密码，请使用 `secure_token`。
+    ///
     /// # 参数
     ///
     /// * `length`: 字符串的长度。
@@ -63,23 +68,142 @@ impl RandomUtil {
     ///
     /// 返回生成的随机字符串。
     pub fn random_string(length: usize) -> String {
-        let mut rng = rand::thread_rng();
-        let characters: Vec<char> = (0..length)
-            .map(|_| {
-                let category = rng.gen_range(0..3);
-                match category {
-                    0 => rng.gen_range(b'a'..=b'z') as char,
-                    1 => rng.gen_range(b'A'..=b'Z') as char,
-                    2 => rng.gen_range(b'0'..=b'9') as char,
-                    _ => unreachable!(),
-                }
-            })
-            .collect();
-
-        characters.into_iter().collect()
+        random_string(&mut rand::thread_rng(), length)
+    }
+
+    /// 从操作系统的密码学安全随机数生成器读取随机字节，可以用作密钥、盐等。
+    ///
+    /// # 参数
+    ///
+    /// * `n`: 字节数。
+    ///
+    /// # 返回值
+    ///
+    /// 返回生成的随机字节。操作系统随机数生成器不可用时 panic。
+    pub fn secure_bytes(n: usize) -> Vec<u8> {
+        let mut bytes = vec![0; n];
+        OsRng.fill_bytes(&mut bytes);
+        bytes
+    }
+
+    /// 生成由大小写字母和数字组成的安全令牌，每个字符从62个字符中均匀选取，约含5.95位熵。
+    ///
+    /// # 参数
+    ///
+    /// * `length`: 令牌的长度，用作会话标识、重置密码链接等时建议不少于22。
+    ///
+    /// # 返回值
+    ///
+    /// 返回生成的令牌。操作系统随机数生成器不可用时 panic。
+    pub fn secure_token(length: usize) -> String {
+        OsRng.sample_iter(&Alphanumeric).take(length).map(char::from).collect()
+    }
+
+    /// 生成可以直接放在 URL 和文件名中的安全令牌，为随机字节的 Base64url 编码（不带填充）。
+    ///
+    /// # 参数
+    ///
+    /// * `n`: 随机字节数，令牌长度约为其4/3倍，建议不少于16。
+    ///
+    /// # 返回值
+    ///
+    /// 返回生成的令牌。操作系统随机数生成器不可用时 panic。
+    pub fn secure_token_urlsafe(n: usize) -> String {
+        base64::encode_config(Self::secure_bytes(n), base64::URL_SAFE_NO_PAD)
+    }
+
+    /// 生成十六进制的安全令牌。
+    ///
+    /// # 参数
+    ///
+    /// * `n`: 随机字节数，令牌长度为其2倍，建议不少于16。
+    ///
+    /// # 返回值
+    ///
+    /// 返回小写十六进制的令牌。操作系统随机数生成器不可用时 panic。
+    pub fn secure_token_hex(n: usize) -> String {
+        Self::secure_bytes(n).iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+/// 使用固定种子的随机数工具，相同的种子总是生成相同的序列，用于在测试中重现随机数据。
+///
+/// 方法与 `RandomUtil` 的 `random_*` 系列一一对应。生成器为 `rand::rngs::StdRng`，
+/// 序列在升级 rand 的版本后可能变化。结果可以预测，不能用于任何安全用途，因此没有 `secure_*` 系列方法。
+pub struct SeededRandom {
+    rng: StdRng,
+}
+
+impl SeededRandom {
+    /// 使用指定的种子创建。
+    ///
+    /// # 参数
+    ///
+    /// * `seed`: 种子。
+    ///
+    /// # 返回值
+    ///
+    /// 返回新创建的随机数工具。
+    pub fn new(seed: u64) -> Self {
+        SeededRandom { rng: StdRng::seed_from_u64(seed) }
+    }
+
+    /// 生成指定范围内的随机整数，见 `RandomUtil::random_int`。
+    pub fn random_int(&mut self, min: i32, max: i32) -> i32 {
+        random_int(&mut self.rng, min, max)
+    }
+
+    /// 生成指定范围内的随机浮点数，见 `RandomUtil::random_float`。
+    pub fn random_float(&mut self, min: f64, max: f64) -> f64 {
+        random_float(&mut self.rng, min, max)
+    }
+
+    /// 生成随机布尔值，见 `RandomUtil::random_bool`。
+    pub fn random_bool(&mut self) -> bool {
+        random_bool(&mut self.rng)
+    }
+
+    /// 生成随机小写字母，见 `RandomUtil::random_char`。
+    pub fn random_char(&mut self) -> char {
+        random_char(&mut self.rng)
+    }
+
+    /// 生成指定长度的随机字符串，见 `RandomUtil::random_string`。
+    pub fn random_string(&mut self, length: usize) -> String {
+        random_string(&mut self.rng, length)
     }
 }
 
+fn random_int<R: Rng>(rng: &mut R, min: i32, max: i32) -> i32 {
+    rng.gen_range(min..=max)
+}
+
+fn random_float<R: Rng>(rng: &mut R, min: f64, max: f64) -> f64 {
+    rng.gen_range(min..=max)
+}
+
+fn random_bool<R: Rng>(rng: &mut R) -> bool {
+    rng.gen()
+}
+
+fn random_char<R: Rng>(rng: &mut R) -> char {
+    rng.gen_range(b'a'..=b'z') as char
+}
+
+fn random_string<R: Rng>(rng: &mut R, length: usize) -> String {
+    (0..length)
+        .map(|_| {
+            let category = rng.gen_range(0..3);
+            match category {
+                0 => rng.gen_range(b'a'..=b'z') as char,
+                1 => rng.gen_range(b'A'..=b'Z') as char,
+                2 => rng.gen_range(b'0'..=b'9') as char,
+                _ => unreachable!(),
+            }
+        })
+        .collect()
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -114,4 +238,37 @@ mod tests {
         let random_string = RandomUtil::random_string(10);
         assert_eq!(random_string.len(), 10);
     }
+
+    #[test]
+    fn test_secure() {
+        assert_eq!(RandomUtil::secure_bytes(32).len(), 32);
+        assert_ne!(RandomUtil::secure_bytes(16), RandomUtil::secure_bytes(16));
+
+        let token = RandomUtil::secure_token(22);
+        assert_eq!(token.len(), 22);
+        assert!(token.bytes().all(|b| b.is_ascii_alphanumeric()));
+
+        let token = RandomUtil::secure_token_urlsafe(32);
+        assert_eq!(token.len(), 43);
+        assert!(token.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+
+        let token = RandomUtil::secure_token_hex(16);
+        assert_eq!(token.len(), 32);
+        assert!(token.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)));
+    }
+
+    #[test]
+    fn test_seeded() {
+        let sample = |seed| {
+            let mut random = SeededRandom::new(seed);
+            (random.random_int(1, 100), random.random_float(1.0, 100.0), random.random_bool(), random.random_char(), random.random_string(10))
+        };
+        assert_eq!(sample(42), sample(42));
+        assert_ne!(sample(42), sample(43));
+
+        let mut random = SeededRandom::new(7);
+        assert!((1..=100).contains(&random.random_int(1, 100)));
+        assert!(random.random_char().is_ascii_lowercase());
+        assert_eq!(random.random_string(12).len(), 12);
+    }
 }
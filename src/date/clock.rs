@@ -0,0 +1,96 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 缓存、一次性密码等使用的时钟，可替换为 `ManualClock` 以便在测试中推进时间而无需等待。
+pub trait Clock: Send + Sync {
+    /// 返回当前时刻。
+    fn now(&self) -> Instant;
+
+    /// 返回当前的系统时间，用于需要日历时间的场景，例如 TOTP。
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    /// 返回当前的 Unix 时间戳（秒），系统时间早于1970年时返回0。
+    fn unix_timestamp(&self) -> u64 {
+        self.system_time().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+    }
+}
+
+/// 使用系统单调时钟的默认实现。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// 手动推进的时钟，克隆出的实例共享同一个时间。
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    base: Instant,
+    system_base: SystemTime,
+    offset: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    /// 创建一个新的手动时钟，初始时刻为创建时的系统时刻。
+    pub fn new() -> Self {
+        ManualClock::starting_at(SystemTime::now())
+    }
+
+    /// 创建一个系统时间从指定时间开始的手动时钟。
+    ///
+    /// # 参数
+    ///
+    /// * `time`: SystemTime - 初始的系统时间，例如 `UNIX_EPOCH + Duration::from_secs(59)`
+    pub fn starting_at(time: SystemTime) -> Self {
+        ManualClock {
+            base: Instant::now(),
+            system_base: time,
+            offset: Arc::new(Mutex::new(Duration::ZERO)),
+        }
+    }
+
+    /// 将时钟向前推进指定的时长。
+    ///
+    /// # 参数
+    ///
+    /// * `duration`: Duration - 推进的时长
+    pub fn advance(&self, duration: Duration) {
+        *self.offset.lock().unwrap() += duration;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.base + *self.offset.lock().unwrap()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.system_base + *self.offset.lock().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_system_time() {
+        let clock = ManualClock::starting_at(UNIX_EPOCH + Duration::from_secs(59));
+        assert_eq!(clock.unix_timestamp(), 59);
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(clock.unix_timestamp(), 89);
+        assert!(SystemClock.unix_timestamp() > 1_600_000_000);
+    }
+}
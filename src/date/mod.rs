@@ -1,3 +1,2 @@
+pub mod clock;
 pub mod date_util;
-
-
@@ -0,0 +1,470 @@
+use std::fmt;
+use std::str::FromStr;
+
+use openssl::error::ErrorStack;
+use openssl::rand::rand_bytes;
+use openssl::symm::{self, Cipher};
+
+use crate::convertor::convertor::Convertor;
+
+/// 信封格式的魔数。
+const MAGIC: &[u8; 4] = b"RTAE";
+
+/// 信封格式的版本号。
+const VERSION: u8 = 1;
+
+/// GCM 模式认证标签的字节数。
+const TAG_LEN: usize = 16;
+
+/// AES 加密错误。
+#[derive(Debug)]
+pub enum CipherError {
+    /// 密钥长度不是16、24或32字节
+    InvalidKeyLength(usize),
+    /// IV 长度与模式不匹配
+    InvalidIvLength { expected: usize, actual: usize },
+    /// 只有 GCM 模式支持附加认证数据
+    AadUnsupported(AesMode),
+    /// 信封格式错误，例如魔数、版本或字段长度不正确
+    InvalidEnvelope(String),
+    /// 信封的模式与调用方要求的不一致，可能被改写为不认证的模式
+    ModeMismatch { expected: AesMode, actual: AesMode },
+    /// 解密失败：密钥错误，或者密文、IV、标签、附加认证数据被篡改
+    DecryptFailed,
+    /// OpenSSL 返回的其他错误
+    OpenSsl(ErrorStack),
+}
+
+impl fmt::Display for CipherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CipherError::InvalidKeyLength(len) => write!(f, "AES 密钥长度必须为16、24或32字节，实际为{}字节", len),
+            CipherError::InvalidIvLength { expected, actual } => {
+                write!(f, "IV 长度必须为{}字节，实际为{}字节", expected, actual)
+            }
+            CipherError::AadUnsupported(mode) => write!(f, "{} 模式不支持附加认证数据", mode),
+            CipherError::InvalidEnvelope(msg) => write!(f, "密文格式错误: {}", msg),
+            CipherError::ModeMismatch { expected, actual } => write!(f, "密文使用 {} 模式，要求为 {} 模式", actual, expected),
+            CipherError::DecryptFailed => write!(f, "解密失败，密钥错误或数据已被篡改"),
+            CipherError::OpenSsl(e) => write!(f, "OpenSSL 错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CipherError {}
+
+impl From<ErrorStack> for CipherError {
+    fn from(e: ErrorStack) -> Self {
+        CipherError::OpenSsl(e)
+    }
+}
+
+/// AES 工作模式。
+///
+/// 新代码应优先使用 `Gcm`，它同时保证机密性和完整性；`Cbc` 和 `Ctr` 不能发现密文被篡改，需要自行配合 HMAC 使用。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AesMode {
+    /// 认证加密，12字节随机数，16字节标签，支持附加认证数据
+    Gcm,
+    /// 16字节 IV，PKCS#7 填充
+    Cbc,
+    /// 16字节初始计数器，不填充，密文与明文等长
+    Ctr,
+}
+
+impl AesMode {
+    /// 返回模式要求的 IV（GCM 中称为随机数）字节数。
+    pub fn iv_len(&self) -> usize {
+        match self {
+            AesMode::Gcm => 12,
+            AesMode::Cbc | AesMode::Ctr => 16,
+        }
+    }
+
+    /// 返回模式的名称，例如 `GCM`。
+    pub fn name(&self) -> &'static str {
+        match self {
+            AesMode::Gcm => "GCM",
+            AesMode::Cbc => "CBC",
+            AesMode::Ctr => "CTR",
+        }
+    }
+
+    /// 使用密码学安全的随机数生成器生成该模式所需长度的 IV。
+    ///
+    /// 同一个密钥下 IV 绝不能重复使用，GCM 模式中重复的随机数会泄露认证密钥。
+    ///
+    /// 返回值：Result<Vec<u8>, CipherError> - 随机 IV
+    pub fn generate_iv(&self) -> Result<Vec<u8>, CipherError> {
+        random_bytes(self.iv_len())
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            AesMode::Gcm => 1,
+            AesMode::Cbc => 2,
+            AesMode::Ctr => 3,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(AesMode::Gcm),
+            2 => Some(AesMode::Cbc),
+            3 => Some(AesMode::Ctr),
+            _ => None,
+        }
+    }
+
+    fn cipher(&self, key: &[u8]) -> Result<Cipher, CipherError> {
+        let cipher = match (self, key.len()) {
+            (AesMode::Gcm, 16) => Cipher::aes_128_gcm(),
+            (AesMode::Gcm, 24) => Cipher::aes_192_gcm(),
+            (AesMode::Gcm, 32) => Cipher::aes_256_gcm(),
+            (AesMode::Cbc, 16) => Cipher::aes_128_cbc(),
+            (AesMode::Cbc, 24) => Cipher::aes_192_cbc(),
+            (AesMode::Cbc, 32) => Cipher::aes_256_cbc(),
+            (AesMode::Ctr, 16) => Cipher::aes_128_ctr(),
+            (AesMode::Ctr, 24) => Cipher::aes_192_ctr(),
+            (AesMode::Ctr, 32) => Cipher::aes_256_ctr(),
+            (_, len) => return Err(CipherError::InvalidKeyLength(len)),
+        };
+        Ok(cipher)
+    }
+}
+
+impl fmt::Display for AesMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for AesMode {
+    type Err = String;
+
+    /// 从模式名称解析，不区分大小写，例如 `gcm`、`CBC`。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "gcm" => Ok(AesMode::Gcm),
+            "cbc" => Ok(AesMode::Cbc),
+            "ctr" => Ok(AesMode::Ctr),
+            _ => Err(format!("不支持的 AES 模式: {}", s)),
+        }
+    }
+}
+
+/// 生成指定位数的随机 AES 密钥。
+///
+/// # 参数
+///
+/// * `bits`: usize - 密钥位数，只能是128、192或256
+///
+/// 返回值：Result<Vec<u8>, CipherError> - 随机密钥
+pub fn generate_key(bits: usize) -> Result<Vec<u8>, CipherError> {
+    match bits {
+        128 | 192 | 256 => random_bytes(bits / 8),
+        _ => Err(CipherError::InvalidKeyLength(bits / 8)),
+    }
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, CipherError> {
+    let mut buf = vec![0; len];
+    rand_bytes(&mut buf)?;
+    Ok(buf)
+}
+
+/// 自描述的密文信封，包含解密所需的模式、IV、认证标签和密文，但不包含密钥。
+///
+/// 序列化格式：魔数 `RTAE`、1字节版本号、1字节模式、1字节 IV 长度、IV、1字节标签长度、标签、密文。
+/// 附加认证数据不会写入信封，解密时需要由调用方再次提供。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    mode: AesMode,
+    iv: Vec<u8>,
+    tag: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+impl Envelope {
+    /// 使用随机 IV 加密数据。
+    ///
+    /// # 参数
+    ///
+    /// * `mode`: AesMode - 工作模式
+    /// * `key`: &[u8] - 16、24或32字节的密钥，分别对应 AES-128、AES-192 和 AES-256
+    /// * `plaintext`: &[u8] - 明文
+    /// * `aad`: &[u8] - 附加认证数据，只参与认证而不加密，仅 GCM 模式支持，其他模式必须为空
+    ///
+    /// 返回值：Result<Envelope, CipherError> - 密文信封
+    pub fn seal(mode: AesMode, key: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Self, CipherError> {
+        let iv = mode.generate_iv()?;
+        Envelope::seal_with_iv(mode, key, &iv, plaintext, aad)
+    }
+
+    /// 使用指定的 IV 加密数据，调用方需要保证同一个密钥下 IV 不重复。
+    ///
+    /// # 参数
+    ///
+    /// * `mode`: AesMode - 工作模式
+    /// * `key`: &[u8] - 16、24或32字节的密钥
+    /// * `iv`: &[u8] - IV，长度必须为 `mode.iv_len()`
+    /// * `plaintext`: &[u8] - 明文
+    /// * `aad`: &[u8] - 附加认证数据，仅 GCM 模式支持
+    ///
+    /// 返回值：Result<Envelope, CipherError> - 密文信封
+    pub fn seal_with_iv(mode: AesMode, key: &[u8], iv: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Self, CipherError> {
+        let cipher = mode.cipher(key)?;
+        check_params(mode, iv, aad)?;
+        let (ciphertext, tag) = match mode {
+            AesMode::Gcm => {
+                let mut tag = vec![0; TAG_LEN];
+                let ciphertext = symm::encrypt_aead(cipher, key, Some(iv), aad, plaintext, &mut tag)?;
+                (ciphertext, tag)
+            }
+            AesMode::Cbc | AesMode::Ctr => (symm::encrypt(cipher, key, Some(iv), plaintext)?, Vec::new()),
+        };
+        Ok(Envelope { mode, iv: iv.to_vec(), tag, ciphertext })
+    }
+
+    /// 解密信封。
+    ///
+    /// 信封中的模式字段不受认证保护，攻击者可以把 GCM 信封改写为 CTR 模式并去掉标签，从而篡改明文而不被发现，
+    /// 因此调用方必须指定加密时使用的模式，与信封不一致时返回 `ModeMismatch`。
+    ///
+    /// # 参数
+    ///
+    /// * `mode`: AesMode - 加密时使用的模式
+    /// * `key`: &[u8] - 加密时使用的密钥
+    /// * `aad`: &[u8] - 加密时使用的附加认证数据
+    ///
+    /// 返回值：Result<Vec<u8>, CipherError> - 明文；GCM 模式下密钥错误或任何数据被篡改时返回 `DecryptFailed`
+    pub fn open(&self, mode: AesMode, key: &[u8], aad: &[u8]) -> Result<Vec<u8>, CipherError> {
+        if self.mode != mode {
+            return Err(CipherError::ModeMismatch { expected: mode, actual: self.mode });
+        }
+        let cipher = self.mode.cipher(key)?;
+        check_params(self.mode, &self.iv, aad)?;
+        match self.mode {
+            AesMode::Gcm => symm::decrypt_aead(cipher, key, Some(&self.iv), aad, &self.ciphertext, &self.tag)
+                .map_err(|_| CipherError::DecryptFailed),
+            // CBC 填充校验失败同样视为解密失败，不区分原因以免成为填充预言
+            AesMode::Cbc => symm::decrypt(cipher, key, Some(&self.iv), &self.ciphertext).map_err(|_| CipherError::DecryptFailed),
+            AesMode::Ctr => Ok(symm::decrypt(cipher, key, Some(&self.iv), &self.ciphertext)?),
+        }
+    }
+
+    /// 返回工作模式。
+    pub fn mode(&self) -> AesMode {
+        self.mode
+    }
+
+    /// 返回 IV。
+    pub fn iv(&self) -> &[u8] {
+        &self.iv
+    }
+
+    /// 返回认证标签，非 GCM 模式为空。
+    pub fn tag(&self) -> &[u8] {
+        &self.tag
+    }
+
+    /// 返回密文。
+    pub fn ciphertext(&self) -> &[u8] {
+        &self.ciphertext
+    }
+
+    /// 序列化为字节。
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(MAGIC.len() + 4 + self.iv.len() + self.tag.len() + self.ciphertext.len());
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.push(self.mode.to_byte());
+        out.push(self.iv.len() as u8);
+        out.extend_from_slice(&self.iv);
+        out.push(self.tag.len() as u8);
+        out.extend_from_slice(&self.tag);
+        out.extend_from_slice(&self.ciphertext);
+        out
+    }
+
+    /// 从 `to_bytes` 的结果中恢复信封。
+    ///
+    /// # 参数
+    ///
+    /// * `bytes`: &[u8] - 序列化的信封
+    ///
+    /// 返回值：Result<Envelope, CipherError> - 信封，格式不正确时返回 `InvalidEnvelope`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CipherError> {
+        let invalid = |msg: &str| CipherError::InvalidEnvelope(msg.to_string());
+        let rest = bytes.strip_prefix(MAGIC.as_slice()).ok_or_else(|| invalid("魔数不匹配"))?;
+        let (&version, rest) = rest.split_first().ok_or_else(|| invalid("数据不完整"))?;
+        if version != VERSION {
+            return Err(CipherError::InvalidEnvelope(format!("不支持的版本 {}", version)));
+        }
+        let (&mode, rest) = rest.split_first().ok_or_else(|| invalid("数据不完整"))?;
+        let mode = AesMode::from_byte(mode).ok_or_else(|| invalid("未知的模式"))?;
+        let (iv, rest) = take_field(rest).ok_or_else(|| invalid("数据不完整"))?;
+        let (tag, ciphertext) = take_field(rest).ok_or_else(|| invalid("数据不完整"))?;
+        if iv.len() != mode.iv_len() {
+            return Err(invalid("IV 长度与模式不匹配"));
+        }
+        let expected_tag = if mode == AesMode::Gcm { TAG_LEN } else { 0 };
+        if tag.len() != expected_tag {
+            return Err(invalid("认证标签长度与模式不匹配"));
+        }
+        Ok(Envelope { mode, iv: iv.to_vec(), tag: tag.to_vec(), ciphertext: ciphertext.to_vec() })
+    }
+
+    /// 序列化为标准 Base64 字符串，便于写入配置文件。
+    pub fn to_base64(&self) -> String {
+        Convertor::bytes_to_base64(&self.to_bytes())
+    }
+
+    /// 从 `to_base64` 的结果中恢复信封。
+    ///
+    /// # 参数
+    ///
+    /// * `input`: &str - Base64 字符串
+    ///
+    /// 返回值：Result<Envelope, CipherError> - 信封，格式不正确时返回 `InvalidEnvelope`
+    pub fn from_base64(input: &str) -> Result<Self, CipherError> {
+        let bytes = Convertor::base64_to_vec(input.trim()).map_err(|e| CipherError::InvalidEnvelope(e.to_string()))?;
+        Envelope::from_bytes(&bytes)
+    }
+}
+
+fn check_params(mode: AesMode, iv: &[u8], aad: &[u8]) -> Result<(), CipherError> {
+    if iv.len() != mode.iv_len() {
+        return Err(CipherError::InvalidIvLength { expected: mode.iv_len(), actual: iv.len() });
+    }
+    if mode != AesMode::Gcm && !aad.is_empty() {
+        return Err(CipherError::AadUnsupported(mode));
+    }
+    Ok(())
+}
+
+/// 读取1字节长度前缀的字段，返回字段和剩余数据。
+fn take_field(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&len, rest) = bytes.split_first()?;
+    let len = len as usize;
+    if rest.len() < len {
+        return None;
+    }
+    Some(rest.split_at(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure::secure::SecureUtil;
+
+    #[test]
+    fn test_aes_known_vectors() {
+        // NIST SP 800-38A F.2.1 和 F.5.1 的第一个分组
+        let key = SecureUtil::from_hex("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let plaintext = SecureUtil::from_hex("6bc1bee22e409f96e93d7e117393172a").unwrap();
+        let iv = SecureUtil::from_hex("000102030405060708090a0b0c0d0e0f").unwrap();
+        let cbc = Envelope::seal_with_iv(AesMode::Cbc, &key, &iv, &plaintext, b"").unwrap();
+        assert_eq!(SecureUtil::to_hex(&cbc.ciphertext()[..16]), "7649abac8119b246cee98e9b12e9197d");
+        // 整分组的明文仍会追加一个填充分组
+        assert_eq!(cbc.ciphertext().len(), 32);
+
+        let counter = SecureUtil::from_hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff").unwrap();
+        let ctr = Envelope::seal_with_iv(AesMode::Ctr, &key, &counter, &plaintext, b"").unwrap();
+        assert_eq!(SecureUtil::to_hex(ctr.ciphertext()), "874d6191b620e3261bef6864990db6ce");
+
+        // GCM 规范测试用例 2：全零密钥、随机数和明文
+        let key = [0u8; 16];
+        let gcm = Envelope::seal_with_iv(AesMode::Gcm, &key, &[0; 12], &[0; 16], b"").unwrap();
+        assert_eq!(SecureUtil::to_hex(gcm.ciphertext()), "0388dace60b6a392f328c2b971b2fe78");
+        assert_eq!(SecureUtil::to_hex(gcm.tag()), "ab6e47d42cec13bdf53a67b21257bddf");
+    }
+
+    #[test]
+    fn test_envelope_round_trip() {
+        let plaintext = b"database.password=s3cr3t";
+        for bits in [128, 192, 256] {
+            let key = generate_key(bits).unwrap();
+            for mode in [AesMode::Gcm, AesMode::Cbc, AesMode::Ctr] {
+                let aad: &[u8] = if mode == AesMode::Gcm { b"prod" } else { b"" };
+                let envelope = Envelope::seal(mode, &key, plaintext, aad).unwrap();
+                let encoded = envelope.to_base64();
+                let decoded = Envelope::from_base64(&encoded).unwrap();
+                assert_eq!(decoded, envelope);
+                assert_eq!(decoded.open(mode, &key, aad).unwrap(), plaintext, "{} {}", bits, mode);
+            }
+        }
+
+        // 每次加密使用新的随机 IV
+        let key = generate_key(256).unwrap();
+        let first = Envelope::seal(AesMode::Gcm, &key, plaintext, b"").unwrap();
+        let second = Envelope::seal(AesMode::Gcm, &key, plaintext, b"").unwrap();
+        assert_ne!(first.iv(), second.iv());
+        assert_ne!(first.ciphertext(), second.ciphertext());
+    }
+
+    #[test]
+    fn test_gcm_detects_tampering() {
+        let key = generate_key(128).unwrap();
+        let envelope = Envelope::seal(AesMode::Gcm, &key, b"secret", b"context").unwrap();
+
+        assert!(matches!(envelope.open(AesMode::Gcm, &key, b"other"), Err(CipherError::DecryptFailed)));
+        assert!(matches!(envelope.open(AesMode::Gcm, &generate_key(128).unwrap(), b"context"), Err(CipherError::DecryptFailed)));
+
+        let mut bytes = envelope.to_bytes();
+        *bytes.last_mut().unwrap() ^= 1;
+        let tampered = Envelope::from_bytes(&bytes).unwrap();
+        assert!(matches!(tampered.open(AesMode::Gcm, &key, b"context"), Err(CipherError::DecryptFailed)));
+    }
+
+    #[test]
+    fn test_rejects_mode_downgrade() {
+        let key = generate_key(256).unwrap();
+        let envelope = Envelope::seal(AesMode::Gcm, &key, b"amount=100", b"").unwrap();
+
+        // GCM 用 随机数 || 00000002 作为第一个分组的计数器，改写为 CTR 并去掉标签后可以解密，且能逐位篡改明文
+        let mut counter = envelope.iv().to_vec();
+        counter.extend_from_slice(&[0, 0, 0, 2]);
+        let mut ciphertext = envelope.ciphertext().to_vec();
+        ciphertext[7] ^= b'1' ^ b'9';
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[VERSION, AesMode::Ctr.to_byte(), 16]);
+        bytes.extend_from_slice(&counter);
+        bytes.push(0);
+        bytes.extend_from_slice(&ciphertext);
+        let forged = Envelope::from_bytes(&bytes).unwrap();
+        assert_eq!(forged.open(AesMode::Ctr, &key, b"").unwrap(), b"amount=900");
+
+        assert!(matches!(
+            forged.open(AesMode::Gcm, &key, b""),
+            Err(CipherError::ModeMismatch { expected: AesMode::Gcm, actual: AesMode::Ctr })
+        ));
+        assert!(matches!(
+            SecureUtil::aes_decrypt(AesMode::Gcm, &key, &forged.to_base64(), b""),
+            Err(CipherError::ModeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        assert!(matches!(generate_key(64), Err(CipherError::InvalidKeyLength(8))));
+        assert!(matches!(
+            Envelope::seal(AesMode::Cbc, &[0; 15], b"data", b""),
+            Err(CipherError::InvalidKeyLength(15))
+        ));
+        assert!(matches!(
+            Envelope::seal(AesMode::Ctr, &[0; 16], b"data", b"aad"),
+            Err(CipherError::AadUnsupported(AesMode::Ctr))
+        ));
+        assert!(matches!(
+            Envelope::seal_with_iv(AesMode::Gcm, &[0; 16], &[0; 16], b"data", b""),
+            Err(CipherError::InvalidIvLength { expected: 12, actual: 16 })
+        ));
+
+        let bytes = Envelope::seal(AesMode::Gcm, &[0; 16], b"data", b"").unwrap().to_bytes();
+        assert!(matches!(Envelope::from_bytes(&bytes[..10]), Err(CipherError::InvalidEnvelope(_))));
+        assert!(matches!(Envelope::from_bytes(b"XXXX"), Err(CipherError::InvalidEnvelope(_))));
+        assert!(matches!(Envelope::from_base64("not base64!"), Err(CipherError::InvalidEnvelope(_))));
+        assert_eq!("gcm".parse::<AesMode>(), Ok(AesMode::Gcm));
+    }
+}
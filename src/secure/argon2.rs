@@ -0,0 +1,280 @@
+use crypto::blake2b::Blake2b;
+use crypto::digest::Digest;
+
+/// 算法版本 0x13。
+pub(crate) const VERSION: u32 = 0x13;
+
+/// Argon2id 的类型编号。
+const TYPE_ID: u32 = 2;
+
+/// 每个内存块的64位字数，即1KiB。
+const BLOCK_WORDS: usize = 128;
+
+/// 每条通道划分的片段数。
+const SYNC_POINTS: usize = 4;
+
+/// 每个地址块能提供的伪随机数个数。
+const ADDRESSES_PER_BLOCK: usize = BLOCK_WORDS;
+
+type Block = [u64; BLOCK_WORDS];
+
+/// Argon2id 参数（RFC 9106）。
+pub(crate) struct Params<'a> {
+    /// 内存大小，单位KiB，不小于 8 * lanes
+    pub memory: u32,
+    /// 迭代次数
+    pub passes: u32,
+    /// 通道数
+    pub lanes: u32,
+    /// 输出字节数
+    pub tag_len: usize,
+    /// 可选的密钥
+    pub secret: &'a [u8],
+    /// 可选的附加数据
+    pub associated: &'a [u8],
+}
+
+/// 计算 Argon2id 哈希。调用方需要保证参数有效，各通道按顺序在当前线程中计算。
+pub(crate) fn argon2id(password: &[u8], salt: &[u8], params: &Params) -> Vec<u8> {
+    let lanes = params.lanes as usize;
+    let segment_len = params.memory as usize / (SYNC_POINTS * lanes);
+    let lane_len = segment_len * SYNC_POINTS;
+    let mut memory: Vec<Block> = vec![[0; BLOCK_WORDS]; lane_len * lanes];
+
+    let mut h0 = Blake2b::new(64);
+    for value in [params.lanes, params.tag_len as u32, params.memory, params.passes, VERSION, TYPE_ID] {
+        h0.input(&value.to_le_bytes());
+    }
+    for data in [password, salt, params.secret, params.associated] {
+        h0.input(&(data.len() as u32).to_le_bytes());
+        h0.input(data);
+    }
+    let mut seed = [0u8; 72];
+    h0.result(&mut seed[..64]);
+
+    for lane in 0..lanes {
+        seed[68..].copy_from_slice(&(lane as u32).to_le_bytes());
+        for column in 0..2u32 {
+            seed[64..68].copy_from_slice(&column.to_le_bytes());
+            memory[lane * lane_len + column as usize] = block_from_bytes(&variable_hash(&seed, BLOCK_WORDS * 8));
+        }
+    }
+
+    for pass in 0..params.passes as usize {
+        for slice in 0..SYNC_POINTS {
+            for lane in 0..lanes {
+                fill_segment(&mut memory, params, pass, slice, lane, segment_len);
+            }
+        }
+    }
+
+    let mut last = memory[lane_len - 1];
+    for lane in 1..lanes {
+        xor_into(&mut last, &memory[lane * lane_len + lane_len - 1]);
+    }
+    variable_hash(&block_to_bytes(&last), params.tag_len)
+}
+
+fn fill_segment(memory: &mut [Block], params: &Params, pass: usize, slice: usize, lane: usize, segment_len: usize) {
+    let lanes = params.lanes as usize;
+    let lane_len = segment_len * SYNC_POINTS;
+    // Argon2id 在第一轮的前两个片段中使用与数据无关的寻址
+    let data_independent = pass == 0 && slice < SYNC_POINTS / 2;
+
+    let mut input: Block = [0; BLOCK_WORDS];
+    let mut addresses: Block = [0; BLOCK_WORDS];
+    if data_independent {
+        input[..6].copy_from_slice(&[
+            pass as u64,
+            lane as u64,
+            slice as u64,
+            memory.len() as u64,
+            params.passes as u64,
+            TYPE_ID as u64,
+        ]);
+    }
+
+    let start = if pass == 0 && slice == 0 { 2 } else { 0 };
+    if data_independent && start == 2 {
+        next_addresses(&mut input, &mut addresses);
+    }
+
+    for index in start..segment_len {
+        let column = slice * segment_len + index;
+        let current = lane * lane_len + column;
+        let previous = if column == 0 { current + lane_len - 1 } else { current - 1 };
+
+        let pseudo_random = if data_independent {
+            if index % ADDRESSES_PER_BLOCK == 0 {
+                next_addresses(&mut input, &mut addresses);
+            }
+            addresses[index % ADDRESSES_PER_BLOCK]
+        } else {
+            memory[previous][0]
+        };
+
+        let ref_lane = if pass == 0 && slice == 0 { lane } else { (pseudo_random >> 32) as usize % lanes };
+        let same_lane = ref_lane == lane;
+        // 可引用的块数：已经完成的片段，同一通道时再加上当前片段中已计算的块，但不包括前一个块
+        let area = match (pass, same_lane) {
+            (0, true) => slice * segment_len + index - 1,
+            (0, false) => slice * segment_len - usize::from(index == 0),
+            (_, true) => lane_len - segment_len + index - 1,
+            (_, false) => lane_len - segment_len - usize::from(index == 0),
+        } as u64;
+        let j1 = pseudo_random & 0xffff_ffff;
+        let x = (j1 * j1) >> 32;
+        let relative = area - 1 - ((area * x) >> 32);
+        let window_start = if pass == 0 || slice == SYNC_POINTS - 1 { 0 } else { (slice + 1) * segment_len };
+        let reference = ref_lane * lane_len + (window_start + relative as usize) % lane_len;
+
+        let block = compress(&memory[previous], &memory[reference]);
+        if pass == 0 {
+            memory[current] = block;
+        } else {
+            xor_into(&mut memory[current], &block);
+        }
+    }
+}
+
+/// 生成下一组与数据无关的伪随机数：G(0, G(0, input))，每次调用前计数器加1。
+fn next_addresses(input: &mut Block, addresses: &mut Block) {
+    input[6] += 1;
+    let zero = [0; BLOCK_WORDS];
+    *addresses = compress(&zero, &compress(&zero, input));
+}
+
+/// 压缩函数 G。
+fn compress(x: &Block, y: &Block) -> Block {
+    let mut r = *x;
+    xor_into(&mut r, y);
+    let mut q = r;
+    for row in 0..8 {
+        let mut v: [u64; 16] = q[row * 16..row * 16 + 16].try_into().expect("每行16个字");
+        permute(&mut v);
+        q[row * 16..row * 16 + 16].copy_from_slice(&v);
+    }
+    for column in 0..8 {
+        let mut v = [0u64; 16];
+        for i in 0..8 {
+            v[2 * i] = q[16 * i + 2 * column];
+            v[2 * i + 1] = q[16 * i + 2 * column + 1];
+        }
+        permute(&mut v);
+        for i in 0..8 {
+            q[16 * i + 2 * column] = v[2 * i];
+            q[16 * i + 2 * column + 1] = v[2 * i + 1];
+        }
+    }
+    xor_into(&mut q, &r);
+    q
+}
+
+/// 基于 BLAKE2b 轮函数的置换 P。
+fn permute(v: &mut [u64; 16]) {
+    mix(v, 0, 4, 8, 12);
+    mix(v, 1, 5, 9, 13);
+    mix(v, 2, 6, 10, 14);
+    mix(v, 3, 7, 11, 15);
+    mix(v, 0, 5, 10, 15);
+    mix(v, 1, 6, 11, 12);
+    mix(v, 2, 7, 8, 13);
+    mix(v, 3, 4, 9, 14);
+}
+
+fn mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize) {
+    let fblamka = |x: u64, y: u64| x.wrapping_add(y).wrapping_add(2u64.wrapping_mul(x & 0xffff_ffff).wrapping_mul(y & 0xffff_ffff));
+    v[a] = fblamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = fblamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = fblamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = fblamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// 变长哈希函数 H'。
+fn variable_hash(input: &[u8], len: usize) -> Vec<u8> {
+    let prefix = (len as u32).to_le_bytes();
+    if len <= 64 {
+        let mut hasher = Blake2b::new(len);
+        hasher.input(&prefix);
+        hasher.input(input);
+        let mut out = vec![0; len];
+        hasher.result(&mut out);
+        return out;
+    }
+
+    let mut out = Vec::with_capacity(len);
+    let mut v = [0u8; 64];
+    let mut hasher = Blake2b::new(64);
+    hasher.input(&prefix);
+    hasher.input(input);
+    hasher.result(&mut v);
+    // 每个中间结果只取前32字节，剩余不超过64字节时由最后一次哈希直接输出
+    out.extend_from_slice(&v[..32]);
+    while len - out.len() > 64 {
+        let mut hasher = Blake2b::new(64);
+        hasher.input(&v);
+        hasher.result(&mut v);
+        out.extend_from_slice(&v[..32]);
+    }
+    let rest = len - out.len();
+    let mut hasher = Blake2b::new(rest);
+    hasher.input(&v);
+    let mut last = vec![0; rest];
+    hasher.result(&mut last);
+    out.extend(last);
+    out
+}
+
+fn xor_into(target: &mut Block, other: &Block) {
+    for (t, o) in target.iter_mut().zip(other) {
+        *t ^= o;
+    }
+}
+
+fn block_from_bytes(bytes: &[u8]) -> Block {
+    let mut block = [0; BLOCK_WORDS];
+    for (word, chunk) in block.iter_mut().zip(bytes.chunks_exact(8)) {
+        *word = u64::from_le_bytes(chunk.try_into().expect("每个字8字节"));
+    }
+    block
+}
+
+fn block_to_bytes(block: &Block) -> Vec<u8> {
+    block.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure::secure::SecureUtil;
+
+    #[test]
+    fn test_argon2id_rfc_vector() {
+        // RFC 9106 5.3
+        let params = Params { memory: 32, passes: 3, lanes: 4, tag_len: 32, secret: &[3; 8], associated: &[4; 12] };
+        assert_eq!(
+            SecureUtil::to_hex(&argon2id(&[1; 32], &[2; 16], &params)),
+            "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659"
+        );
+
+        // 与 OpenSSL 的 ARGON2ID 对照：单通道，以及内存不是 4 * 通道数的整数倍、输出超过64字节的情况
+        let params = Params { memory: 64, passes: 2, lanes: 1, tag_len: 32, secret: &[], associated: &[] };
+        assert_eq!(
+            SecureUtil::to_hex(&argon2id(b"password", b"somesaltsomesalt", &params)),
+            "1a98fba8e6394425c9bdc333a9c750aaf811df070f5fb4a5788e1cf4db5793a1"
+        );
+        let params = Params { memory: 301, passes: 1, lanes: 3, tag_len: 100, secret: &[], associated: &[] };
+        assert_eq!(
+            SecureUtil::to_hex(&argon2id(b"password", b"somesaltsomesalt", &params)),
+            concat!(
+                "d2d36da957826b6f4ed1865ec1e8ac247b1d06fed08794899c67def33dca7246f4e6964de9e48d39010352fe6391efda",
+                "9899a65464a14a17785c97f29dd9f3d1ff4b84cd89f7d152c9a1924e6d7422f26d58f56a1728ef499243dd902047b2c7",
+                "cf8621f2"
+            )
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crypto::blake2b::Blake2b;
+use crypto::digest::Digest;
+use crypto::md5::Md5;
+use crypto::sha1::Sha1;
+use crypto::sha2::{Sha224, Sha256, Sha384, Sha512};
+use crypto::sha3::Sha3;
+use openssl::hash::MessageDigest;
+
+use crate::secure::sm3::Sm3;
+
+/// 摘要算法。
+///
+/// `Md5` 和 `Sha1` 已不再安全，仅用于兼容旧系统，新代码应使用 SHA-2、SHA-3、BLAKE 系列或 SM3。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DigestAlgorithm {
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha3_256,
+    Sha3_512,
+    /// 输出64字节的 BLAKE2b-512
+    Blake2b,
+    /// 输出32字节的 BLAKE3
+    Blake3,
+    /// 国密 SM3，输出32字节
+    Sm3,
+}
+
+impl DigestAlgorithm {
+    /// 返回摘要的字节数。
+    pub fn output_len(&self) -> usize {
+        match self {
+            DigestAlgorithm::Md5 => 16,
+            DigestAlgorithm::Sha1 => 20,
+            DigestAlgorithm::Sha224 => 28,
+            DigestAlgorithm::Sha256 | DigestAlgorithm::Sha3_256 | DigestAlgorithm::Blake3 | DigestAlgorithm::Sm3 => 32,
+            DigestAlgorithm::Sha384 => 48,
+            DigestAlgorithm::Sha512 | DigestAlgorithm::Sha3_512 | DigestAlgorithm::Blake2b => 64,
+        }
+    }
+
+    /// 返回算法每次处理的分组字节数，HMAC 按该长度填充密钥。
+    pub fn block_size(&self) -> usize {
+        match self {
+            DigestAlgorithm::Md5
+            | DigestAlgorithm::Sha1
+            | DigestAlgorithm::Sha224
+            | DigestAlgorithm::Sha256
+            | DigestAlgorithm::Blake3
+            | DigestAlgorithm::Sm3 => 64,
+            DigestAlgorithm::Sha384 | DigestAlgorithm::Sha512 | DigestAlgorithm::Blake2b => 128,
+            DigestAlgorithm::Sha3_256 => 136,
+            DigestAlgorithm::Sha3_512 => 72,
+        }
+    }
+
+    /// 返回算法的标准名称，例如 `SHA-256`。
+    pub fn name(&self) -> &'static str {
+        match self {
+            DigestAlgorithm::Md5 => "MD5",
+            DigestAlgorithm::Sha1 => "SHA-1",
+            DigestAlgorithm::Sha224 => "SHA-224",
+            DigestAlgorithm::Sha256 => "SHA-256",
+            DigestAlgorithm::Sha384 => "SHA-384",
+            DigestAlgorithm::Sha512 => "SHA-512",
+            DigestAlgorithm::Sha3_256 => "SHA3-256",
+            DigestAlgorithm::Sha3_512 => "SHA3-512",
+            DigestAlgorithm::Blake2b => "BLAKE2b",
+            DigestAlgorithm::Blake3 => "BLAKE3",
+            DigestAlgorithm::Sm3 => "SM3",
+        }
+    }
+
+    /// 返回 OpenSSL 中对应的摘要算法，BLAKE 系列不用于签名，返回None。
+    pub(crate) fn message_digest(&self) -> Option<MessageDigest> {
+        match self {
+            DigestAlgorithm::Md5 => Some(MessageDigest::md5()),
+            DigestAlgorithm::Sha1 => Some(MessageDigest::sha1()),
+            DigestAlgorithm::Sha224 => Some(MessageDigest::sha224()),
+            DigestAlgorithm::Sha256 => Some(MessageDigest::sha256()),
+            DigestAlgorithm::Sha384 => Some(MessageDigest::sha384()),
+            DigestAlgorithm::Sha512 => Some(MessageDigest::sha512()),
+            DigestAlgorithm::Sha3_256 => Some(MessageDigest::sha3_256()),
+            DigestAlgorithm::Sha3_512 => Some(MessageDigest::sha3_512()),
+            DigestAlgorithm::Sm3 => Some(MessageDigest::sm3()),
+            DigestAlgorithm::Blake2b | DigestAlgorithm::Blake3 => None,
+        }
+    }
+
+    fn digest(&self) -> Box<dyn Digest + Send + Sync> {
+        match self {
+            DigestAlgorithm::Md5 => Box::new(Md5::new()),
+            DigestAlgorithm::Sha1 => Box::new(Sha1::new()),
+            DigestAlgorithm::Sha224 => Box::new(Sha224::new()),
+            DigestAlgorithm::Sha256 => Box::new(Sha256::new()),
+            DigestAlgorithm::Sha384 => Box::new(Sha384::new()),
+            DigestAlgorithm::Sha512 => Box::new(Sha512::new()),
+            DigestAlgorithm::Sha3_256 => Box::new(Sha3::sha3_256()),
+            DigestAlgorithm::Sha3_512 => Box::new(Sha3::sha3_512()),
+            DigestAlgorithm::Blake2b => Box::new(Blake2b::new(64)),
+            DigestAlgorithm::Blake3 => Box::new(Blake3(blake3::Hasher::new())),
+            DigestAlgorithm::Sm3 => Box::new(Sm3::new()),
+        }
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for DigestAlgorithm {
+    type Err = String;
+
+    /// 从算法名称解析，不区分大小写，连字符和下划线可以省略，例如 `sha256`、`SHA-256`、`sha3_256`。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name: String = s.chars().filter(|c| *c != '-' && *c != '_').collect::<String>().to_ascii_lowercase();
+        match name.as_str() {
+            "md5" => Ok(DigestAlgorithm::Md5),
+            "sha1" => Ok(DigestAlgorithm::Sha1),
+            "sha224" => Ok(DigestAlgorithm::Sha224),
+            "sha256" => Ok(DigestAlgorithm::Sha256),
+            "sha384" => Ok(DigestAlgorithm::Sha384),
+            "sha512" => Ok(DigestAlgorithm::Sha512),
+            "sha3256" => Ok(DigestAlgorithm::Sha3_256),
+            "sha3512" => Ok(DigestAlgorithm::Sha3_512),
+            "blake2b" | "blake2b512" => Ok(DigestAlgorithm::Blake2b),
+            "blake3" => Ok(DigestAlgorithm::Blake3),
+            "sm3" => Ok(DigestAlgorithm::Sm3),
+            _ => Err(format!("不支持的摘要算法: {}", s)),
+        }
+    }
+}
+
+/// 增量计算摘要的哈希器，适合数据分多次到达的场景。
+///
+/// 实现了 `Write`，可以配合 `io::copy` 从任意读取器中流式计算摘要。
+pub struct Hasher {
+    algorithm: DigestAlgorithm,
+    digest: Box<dyn Digest + Send + Sync>,
+}
+
+impl Hasher {
+    /// 创建指定算法的哈希器。
+    ///
+    /// # 参数
+    ///
+    /// * `algorithm`: DigestAlgorithm - 摘要算法
+    ///
+    /// 返回值：Hasher - 新创建的哈希器
+    pub fn new(algorithm: DigestAlgorithm) -> Self {
+        Hasher { algorithm, digest: algorithm.digest() }
+    }
+
+    /// 返回哈希器使用的算法。
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
+    /// 追加数据。
+    ///
+    /// # 参数
+    ///
+    /// * `data`: &[u8] - 追加的数据
+    pub fn update(&mut self, data: &[u8]) {
+        self.digest.input(data);
+    }
+
+    /// 返回此前追加的所有数据的摘要，并将哈希器重置为初始状态，可以继续计算下一份数据。
+    ///
+    /// 返回值：Vec<u8> - 摘要，长度为 `algorithm().output_len()`
+    pub fn finalize_reset(&mut self) -> Vec<u8> {
+        let mut out = vec![0; self.algorithm.output_len()];
+        self.digest.result(&mut out);
+        self.digest.reset();
+        out
+    }
+
+    /// 返回此前追加的所有数据的摘要。
+    ///
+    /// 返回值：Vec<u8> - 摘要，长度为 `algorithm().output_len()`
+    pub fn finalize(mut self) -> Vec<u8> {
+        self.finalize_reset()
+    }
+}
+
+impl Write for Hasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 让 BLAKE3 与 rust-crypto 的其他摘要算法使用相同的接口。
+struct Blake3(blake3::Hasher);
+
+impl Digest for Blake3 {
+    fn input(&mut self, input: &[u8]) {
+        self.0.update(input);
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        out.copy_from_slice(self.0.finalize().as_bytes());
+    }
+
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+
+    fn output_bits(&self) -> usize {
+        256
+    }
+
+    fn block_size(&self) -> usize {
+        64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure::secure::SecureUtil;
+
+    #[test]
+    fn test_digest_algorithms() {
+        let cases = [
+            (DigestAlgorithm::Sha224, "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"),
+            (DigestAlgorithm::Sha256, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+            (
+                DigestAlgorithm::Sha384,
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+            ),
+            (
+                DigestAlgorithm::Sha512,
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            ),
+            (DigestAlgorithm::Sha3_256, "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"),
+            (
+                DigestAlgorithm::Sha3_512,
+                "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+            ),
+            (
+                DigestAlgorithm::Blake2b,
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+            ),
+            (DigestAlgorithm::Blake3, "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"),
+        ];
+        for (algorithm, expected) in cases {
+            let digest = SecureUtil::digest(algorithm, b"abc");
+            assert_eq!(digest.len(), algorithm.output_len(), "{}", algorithm);
+            assert_eq!(SecureUtil::to_hex(&digest), expected, "{}", algorithm);
+        }
+    }
+
+    #[test]
+    fn test_hasher_streaming() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 253) as u8).collect();
+        for algorithm in [DigestAlgorithm::Sha256, DigestAlgorithm::Sha3_512, DigestAlgorithm::Blake3] {
+            let mut hasher = Hasher::new(algorithm);
+            for chunk in data.chunks(7919) {
+                hasher.update(chunk);
+            }
+            let expected = SecureUtil::digest(algorithm, &data);
+            assert_eq!(hasher.finalize_reset(), expected);
+
+            // 重置后可以继续使用，也可以作为 Write 的目标
+            io::copy(&mut data.as_slice(), &mut hasher).unwrap();
+            assert_eq!(hasher.finalize(), expected);
+        }
+    }
+
+    #[test]
+    fn test_digest_algorithm_from_str() {
+        assert_eq!("SHA-256".parse::<DigestAlgorithm>(), Ok(DigestAlgorithm::Sha256));
+        assert_eq!("sha3_512".parse::<DigestAlgorithm>(), Ok(DigestAlgorithm::Sha3_512));
+        assert_eq!("blake3".parse::<DigestAlgorithm>(), Ok(DigestAlgorithm::Blake3));
+        assert!("sha3".parse::<DigestAlgorithm>().is_err());
+        for algorithm in [DigestAlgorithm::Md5, DigestAlgorithm::Sha1, DigestAlgorithm::Sha224, DigestAlgorithm::Blake2b, DigestAlgorithm::Sm3] {
+            assert_eq!(algorithm.to_string().parse::<DigestAlgorithm>(), Ok(algorithm));
+        }
+    }
+}
@@ -0,0 +1,834 @@
+use std::fmt;
+
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::derive::Deriver;
+use openssl::ec::{EcGroup, EcKey, EcPoint, PointConversionForm};
+use openssl::ecdsa::EcdsaSig;
+use openssl::error::ErrorStack;
+use openssl::nid::Nid;
+use openssl::pkey::{Id, PKey, Private, Public};
+use openssl::sign::{Signer, Verifier};
+
+use crate::secure::digest::DigestAlgorithm;
+use crate::secure::hmac::hkdf;
+
+/// Ed25519 和 X25519 密钥的字节数。
+pub const CURVE25519_KEY_LEN: usize = 32;
+
+/// Ed25519 签名的字节数。
+pub const ED25519_SIGNATURE_LEN: usize = 64;
+
+/// 椭圆曲线错误。
+#[derive(Debug)]
+pub enum EcError {
+    /// 密钥类型或曲线不匹配，或者 PEM/DER/原始字节格式无法解析
+    InvalidKey(String),
+    /// 摘要算法不能用于签名，例如 BLAKE 系列
+    UnsupportedDigest(DigestAlgorithm),
+    /// 签名格式错误
+    InvalidSignature(String),
+    /// 密钥协商的结果全为0，对方公钥是小阶点
+    KeyAgreementFailed,
+    /// 派生的密钥长度为0或超过 HKDF 的上限
+    InvalidKeyLength(usize),
+    /// OpenSSL 返回的其他错误
+    OpenSsl(ErrorStack),
+}
+
+impl fmt::Display for EcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EcError::InvalidKey(msg) => write!(f, "无效的椭圆曲线密钥: {}", msg),
+            EcError::UnsupportedDigest(algorithm) => write!(f, "{} 不能用于 ECDSA 签名", algorithm),
+            EcError::InvalidSignature(msg) => write!(f, "签名格式错误: {}", msg),
+            EcError::KeyAgreementFailed => write!(f, "密钥协商失败，对方公钥无效"),
+            EcError::InvalidKeyLength(len) => write!(f, "无效的派生密钥长度: {}", len),
+            EcError::OpenSsl(e) => write!(f, "OpenSSL 错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EcError {}
+
+impl From<ErrorStack> for EcError {
+    fn from(e: ErrorStack) -> Self {
+        EcError::OpenSsl(e)
+    }
+}
+
+/// ECDSA 使用的曲线。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EcCurve {
+    /// NIST P-256（secp256r1、prime256v1）
+    P256,
+    /// NIST P-384（secp384r1）
+    P384,
+}
+
+impl EcCurve {
+    /// 返回曲线名称。
+    pub fn name(&self) -> &'static str {
+        match self {
+            EcCurve::P256 => "P-256",
+            EcCurve::P384 => "P-384",
+        }
+    }
+
+    /// 返回私钥和坐标的字节数，签名 `r || s` 的长度是它的2倍。
+    pub fn field_len(&self) -> usize {
+        match self {
+            EcCurve::P256 => 32,
+            EcCurve::P384 => 48,
+        }
+    }
+
+    /// 返回与曲线安全强度相当的摘要算法：P-256 对应 SHA-256，P-384 对应 SHA-384。
+    pub fn digest(&self) -> DigestAlgorithm {
+        match self {
+            EcCurve::P256 => DigestAlgorithm::Sha256,
+            EcCurve::P384 => DigestAlgorithm::Sha384,
+        }
+    }
+
+    fn nid(&self) -> Nid {
+        match self {
+            EcCurve::P256 => Nid::X9_62_PRIME256V1,
+            EcCurve::P384 => Nid::SECP384R1,
+        }
+    }
+
+    fn group(&self) -> Result<EcGroup, EcError> {
+        Ok(EcGroup::from_curve_name(self.nid())?)
+    }
+
+    fn from_nid(nid: Option<Nid>) -> Result<Self, EcError> {
+        match nid {
+            Some(Nid::X9_62_PRIME256V1) => Ok(EcCurve::P256),
+            Some(Nid::SECP384R1) => Ok(EcCurve::P384),
+            _ => Err(EcError::InvalidKey("只支持 P-256 和 P-384 曲线".to_string())),
+        }
+    }
+}
+
+impl fmt::Display for EcCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// ECDSA 私钥。
+#[derive(Clone)]
+pub struct EcdsaPrivateKey {
+    curve: EcCurve,
+    key: PKey<Private>,
+}
+
+/// ECDSA 公钥。
+#[derive(Clone)]
+pub struct EcdsaPublicKey {
+    curve: EcCurve,
+    key: PKey<Public>,
+}
+
+impl EcdsaPrivateKey {
+    /// 生成新的密钥对。
+    ///
+    /// 返回值：Result<EcdsaPrivateKey, EcError> - 私钥，公钥可以通过 `public_key` 取得
+    pub fn generate(curve: EcCurve) -> Result<Self, EcError> {
+        let group = curve.group()?;
+        Ok(EcdsaPrivateKey { curve, key: PKey::from_ec_key(EcKey::generate(&group)?)? })
+    }
+
+    /// 从 PEM 导入私钥，支持 PKCS#8（`BEGIN PRIVATE KEY`）和 SEC1（`BEGIN EC PRIVATE KEY`）格式。
+    ///
+    /// # 参数
+    ///
+    /// * `pem`: &[u8] - PEM 格式的私钥
+    ///
+    /// 返回值：Result<EcdsaPrivateKey, EcError> - 私钥
+    pub fn from_pem(pem: &[u8]) -> Result<Self, EcError> {
+        EcdsaPrivateKey::from_pkey(PKey::private_key_from_pem(pem).map_err(invalid_key)?)
+    }
+
+    /// 从 DER 导入私钥，支持 PKCS#8 和 SEC1 格式。
+    ///
+    /// # 参数
+    ///
+    /// * `der`: &[u8] - DER 格式的私钥
+    ///
+    /// 返回值：Result<EcdsaPrivateKey, EcError> - 私钥
+    pub fn from_der(der: &[u8]) -> Result<Self, EcError> {
+        EcdsaPrivateKey::from_pkey(PKey::private_key_from_der(der).map_err(invalid_key)?)
+    }
+
+    /// 从原始的私钥标量导入。
+    ///
+    /// # 参数
+    ///
+    /// * `curve`: EcCurve - 曲线
+    /// * `bytes`: &[u8] - 大端序的私钥，长度为 `curve.field_len()`
+    ///
+    /// 返回值：Result<EcdsaPrivateKey, EcError> - 私钥，取值不在 [1, n-1] 内时返回错误
+    pub fn from_bytes(curve: EcCurve, bytes: &[u8]) -> Result<Self, EcError> {
+        if bytes.len() != curve.field_len() {
+            return Err(EcError::InvalidKey(format!("{} 私钥应为{}字节，实际为{}字节", curve, curve.field_len(), bytes.len())));
+        }
+        let group = curve.group()?;
+        let mut ctx = BigNumContext::new()?;
+        let d = BigNum::from_slice(bytes)?;
+        let mut order = BigNum::new()?;
+        group.order(&mut order, &mut ctx)?;
+        if d.num_bits() == 0 || d >= order {
+            return Err(EcError::InvalidKey("私钥超出范围".to_string()));
+        }
+        let mut point = EcPoint::new(&group)?;
+        point.mul_generator(&group, &d, &ctx)?;
+        let key = EcKey::from_private_components(&group, &d, &point)?;
+        Ok(EcdsaPrivateKey { curve, key: PKey::from_ec_key(key)? })
+    }
+
+    fn from_pkey(key: PKey<Private>) -> Result<Self, EcError> {
+        let ec = key.ec_key().map_err(|_| EcError::InvalidKey("不是 EC 密钥".to_string()))?;
+        Ok(EcdsaPrivateKey { curve: EcCurve::from_nid(ec.group().curve_name())?, key })
+    }
+
+    /// 导出为原始的私钥标量，长度为 `curve.field_len()`。
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EcError> {
+        Ok(self.key.ec_key()?.private_key().to_vec_padded(self.curve.field_len() as i32)?)
+    }
+
+    /// 导出为 PKCS#8 PEM（`BEGIN PRIVATE KEY`）。
+    pub fn to_pem(&self) -> Result<String, EcError> {
+        pem_string(self.key.private_key_to_pem_pkcs8()?)
+    }
+
+    /// 导出为 PKCS#8 DER。
+    pub fn to_der(&self) -> Result<Vec<u8>, EcError> {
+        Ok(self.key.private_key_to_pkcs8()?)
+    }
+
+    /// 导出为 SEC1 PEM（`BEGIN EC PRIVATE KEY`）。
+    pub fn to_sec1_pem(&self) -> Result<String, EcError> {
+        pem_string(self.key.ec_key()?.private_key_to_pem()?)
+    }
+
+    /// 返回曲线。
+    pub fn curve(&self) -> EcCurve {
+        self.curve
+    }
+
+    /// 返回对应的公钥。
+    pub fn public_key(&self) -> Result<EcdsaPublicKey, EcError> {
+        Ok(EcdsaPublicKey { curve: self.curve, key: PKey::public_key_from_der(&self.key.public_key_to_der()?)? })
+    }
+
+    /// 对数据签名。
+    ///
+    /// # 参数
+    ///
+    /// * `algorithm`: DigestAlgorithm - 摘要算法，一般使用 `curve.digest()`
+    /// * `data`: &[u8] - 被签名的数据
+    ///
+    /// 返回值：Result<Vec<u8>, EcError> - `r || s` 格式的签名，长度为 `2 * curve.field_len()`，
+    /// 需要 DER 格式时使用 `signature_to_der` 转换
+    pub fn sign(&self, algorithm: DigestAlgorithm, data: &[u8]) -> Result<Vec<u8>, EcError> {
+        let md = algorithm.message_digest().ok_or(EcError::UnsupportedDigest(algorithm))?;
+        let der = Signer::new(md, &self.key)?.sign_oneshot_to_vec(data)?;
+        signature_from_der(self.curve, &der)
+    }
+
+    pub(crate) fn pkey(&self) -> &PKey<Private> {
+        &self.key
+    }
+}
+
+impl fmt::Debug for EcdsaPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 不输出密钥内容
+        write!(f, "EcdsaPrivateKey({})", self.curve)
+    }
+}
+
+impl EcdsaPublicKey {
+    /// 从 X.509 SubjectPublicKeyInfo PEM（`BEGIN PUBLIC KEY`）导入公钥。
+    ///
+    /// # 参数
+    ///
+    /// * `pem`: &[u8] - PEM 格式的公钥
+    ///
+    /// 返回值：Result<EcdsaPublicKey, EcError> - 公钥
+    pub fn from_pem(pem: &[u8]) -> Result<Self, EcError> {
+        EcdsaPublicKey::from_pkey(PKey::public_key_from_pem(pem).map_err(invalid_key)?)
+    }
+
+    /// 从 X.509 SubjectPublicKeyInfo DER 导入公钥。
+    ///
+    /// # 参数
+    ///
+    /// * `der`: &[u8] - DER 格式的公钥
+    ///
+    /// 返回值：Result<EcdsaPublicKey, EcError> - 公钥
+    pub fn from_der(der: &[u8]) -> Result<Self, EcError> {
+        EcdsaPublicKey::from_pkey(PKey::public_key_from_der(der).map_err(invalid_key)?)
+    }
+
+    /// 从 SEC1 编码的点导入公钥。
+    ///
+    /// # 参数
+    ///
+    /// * `curve`: EcCurve - 曲线
+    /// * `bytes`: &[u8] - `04` 开头的未压缩点，或 `02`、`03` 开头的压缩点
+    ///
+    /// 返回值：Result<EcdsaPublicKey, EcError> - 公钥，点不在曲线上时返回错误
+    pub fn from_bytes(curve: EcCurve, bytes: &[u8]) -> Result<Self, EcError> {
+        let group = curve.group()?;
+        let mut ctx = BigNumContext::new()?;
+        let point = EcPoint::from_bytes(&group, bytes, &mut ctx).map_err(invalid_key)?;
+        let key = EcKey::from_public_key(&group, &point)?;
+        key.check_key().map_err(invalid_key)?;
+        Ok(EcdsaPublicKey { curve, key: PKey::from_ec_key(key)? })
+    }
+
+    fn from_pkey(key: PKey<Public>) -> Result<Self, EcError> {
+        let ec = key.ec_key().map_err(|_| EcError::InvalidKey("不是 EC 密钥".to_string()))?;
+        Ok(EcdsaPublicKey { curve: EcCurve::from_nid(ec.group().curve_name())?, key })
+    }
+
+    /// 导出为 SEC1 编码的点。
+    ///
+    /// # 参数
+    ///
+    /// * `compressed`: bool - true 时导出压缩点（`1 + field_len` 字节），否则导出未压缩点（`1 + 2 * field_len` 字节）
+    pub fn to_bytes(&self, compressed: bool) -> Result<Vec<u8>, EcError> {
+        let ec = self.key.ec_key()?;
+        let form = if compressed { PointConversionForm::COMPRESSED } else { PointConversionForm::UNCOMPRESSED };
+        let mut ctx = BigNumContext::new()?;
+        Ok(ec.public_key().to_bytes(ec.group(), form, &mut ctx)?)
+    }
+
+    /// 导出为 X.509 SubjectPublicKeyInfo PEM（`BEGIN PUBLIC KEY`）。
+    pub fn to_pem(&self) -> Result<String, EcError> {
+        pem_string(self.key.public_key_to_pem()?)
+    }
+
+    /// 导出为 X.509 SubjectPublicKeyInfo DER。
+    pub fn to_der(&self) -> Result<Vec<u8>, EcError> {
+        Ok(self.key.public_key_to_der()?)
+    }
+
+    /// 返回曲线。
+    pub fn curve(&self) -> EcCurve {
+        self.curve
+    }
+
+    /// 验证签名。
+    ///
+    /// # 参数
+    ///
+    /// * `algorithm`: DigestAlgorithm - 签名时使用的摘要算法
+    /// * `data`: &[u8] - 被签名的数据
+    /// * `signature`: &[u8] - `r || s` 格式的签名
+    ///
+    /// 返回值：Result<bool, EcError> - 签名正确时返回true，摘要算法不支持时返回错误
+    pub fn verify(&self, algorithm: DigestAlgorithm, data: &[u8], signature: &[u8]) -> Result<bool, EcError> {
+        let md = algorithm.message_digest().ok_or(EcError::UnsupportedDigest(algorithm))?;
+        // 长度错误等格式问题同样视为签名无效
+        let der = match signature_to_der(self.curve, signature) {
+            Ok(der) => der,
+            Err(_) => return Ok(false),
+        };
+        Ok(Verifier::new(md, &self.key)?.verify_oneshot(&der, data).unwrap_or(false))
+    }
+
+    pub(crate) fn pkey(&self) -> &PKey<Public> {
+        &self.key
+    }
+}
+
+impl fmt::Debug for EcdsaPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EcdsaPublicKey({})", self.curve)
+    }
+}
+
+/// 将 `r || s` 格式的 ECDSA 签名转换为 ASN.1 DER 格式，Java 的 `SHA256withECDSA` 等使用该格式。
+///
+/// 返回值：Result<Vec<u8>, EcError> - DER 编码的签名
+pub fn signature_to_der(curve: EcCurve, signature: &[u8]) -> Result<Vec<u8>, EcError> {
+    let len = curve.field_len();
+    if signature.len() != len * 2 {
+        return Err(EcError::InvalidSignature(format!("{} 签名应为{}字节，实际为{}字节", curve, len * 2, signature.len())));
+    }
+    let (r, s) = signature.split_at(len);
+    Ok(EcdsaSig::from_private_components(BigNum::from_slice(r)?, BigNum::from_slice(s)?)?.to_der()?)
+}
+
+/// 将 ASN.1 DER 格式的 ECDSA 签名转换为 `r || s` 格式。
+///
+/// 返回值：Result<Vec<u8>, EcError> - 长度为 `2 * curve.field_len()` 的签名
+pub fn signature_from_der(curve: EcCurve, der: &[u8]) -> Result<Vec<u8>, EcError> {
+    let len = curve.field_len() as i32;
+    let signature = EcdsaSig::from_der(der).map_err(|e| EcError::InvalidSignature(e.to_string()))?;
+    let mut raw = signature.r().to_vec_padded(len).map_err(|e| EcError::InvalidSignature(e.to_string()))?;
+    raw.extend(signature.s().to_vec_padded(len).map_err(|e| EcError::InvalidSignature(e.to_string()))?);
+    Ok(raw)
+}
+
+/// Ed25519 私钥（RFC 8032）。
+#[derive(Clone)]
+pub struct Ed25519PrivateKey {
+    key: PKey<Private>,
+}
+
+/// Ed25519 公钥。
+#[derive(Clone)]
+pub struct Ed25519PublicKey {
+    key: PKey<Public>,
+}
+
+impl Ed25519PrivateKey {
+    /// 生成新的密钥对。
+    pub fn generate() -> Result<Self, EcError> {
+        Ok(Ed25519PrivateKey { key: PKey::generate_ed25519()? })
+    }
+
+    /// 从32字节的原始私钥（种子）导入。
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EcError> {
+        Ok(Ed25519PrivateKey { key: raw_private(bytes, Id::ED25519)? })
+    }
+
+    /// 从 PKCS#8 PEM（`BEGIN PRIVATE KEY`）导入私钥。
+    pub fn from_pem(pem: &[u8]) -> Result<Self, EcError> {
+        let key = PKey::private_key_from_pem(pem).map_err(invalid_key)?;
+        Ok(Ed25519PrivateKey { key: expect_id(key, Id::ED25519)? })
+    }
+
+    /// 从 PKCS#8 DER 导入私钥。
+    pub fn from_der(der: &[u8]) -> Result<Self, EcError> {
+        let key = PKey::private_key_from_der(der).map_err(invalid_key)?;
+        Ok(Ed25519PrivateKey { key: expect_id(key, Id::ED25519)? })
+    }
+
+    /// 导出为32字节的原始私钥。
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EcError> {
+        Ok(self.key.raw_private_key()?)
+    }
+
+    /// 导出为 PKCS#8 PEM（`BEGIN PRIVATE KEY`）。
+    pub fn to_pem(&self) -> Result<String, EcError> {
+        pem_string(self.key.private_key_to_pem_pkcs8()?)
+    }
+
+    /// 导出为 PKCS#8 DER。
+    pub fn to_der(&self) -> Result<Vec<u8>, EcError> {
+        Ok(self.key.private_key_to_pkcs8()?)
+    }
+
+    /// 返回对应的公钥。
+    pub fn public_key(&self) -> Result<Ed25519PublicKey, EcError> {
+        Ok(Ed25519PublicKey { key: raw_public(&self.key.raw_public_key()?, Id::ED25519)? })
+    }
+
+    /// 对数据签名，Ed25519 内部使用 SHA-512，不需要指定摘要算法。
+    ///
+    /// 返回值：Result<Vec<u8>, EcError> - 64字节的签名
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, EcError> {
+        Ok(Signer::new_without_digest(&self.key)?.sign_oneshot_to_vec(data)?)
+    }
+
+    pub(crate) fn pkey(&self) -> &PKey<Private> {
+        &self.key
+    }
+}
+
+impl fmt::Debug for Ed25519PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 不输出密钥内容
+        write!(f, "Ed25519PrivateKey")
+    }
+}
+
+impl Ed25519PublicKey {
+    /// 从32字节的原始公钥导入。
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EcError> {
+        Ok(Ed25519PublicKey { key: raw_public(bytes, Id::ED25519)? })
+    }
+
+    /// 从 X.509 SubjectPublicKeyInfo PEM（`BEGIN PUBLIC KEY`）导入公钥。
+    pub fn from_pem(pem: &[u8]) -> Result<Self, EcError> {
+        let key = PKey::public_key_from_pem(pem).map_err(invalid_key)?;
+        Ok(Ed25519PublicKey { key: expect_id(key, Id::ED25519)? })
+    }
+
+    /// 从 X.509 SubjectPublicKeyInfo DER 导入公钥。
+    pub fn from_der(der: &[u8]) -> Result<Self, EcError> {
+        let key = PKey::public_key_from_der(der).map_err(invalid_key)?;
+        Ok(Ed25519PublicKey { key: expect_id(key, Id::ED25519)? })
+    }
+
+    /// 导出为32字节的原始公钥。
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EcError> {
+        Ok(self.key.raw_public_key()?)
+    }
+
+    /// 导出为 X.509 SubjectPublicKeyInfo PEM（`BEGIN PUBLIC KEY`）。
+    pub fn to_pem(&self) -> Result<String, EcError> {
+        pem_string(self.key.public_key_to_pem()?)
+    }
+
+    /// 导出为 X.509 SubjectPublicKeyInfo DER。
+    pub fn to_der(&self) -> Result<Vec<u8>, EcError> {
+        Ok(self.key.public_key_to_der()?)
+    }
+
+    /// 验证签名。
+    ///
+    /// 返回值：Result<bool, EcError> - 签名正确时返回true
+    pub fn verify(&self, data: &[u8], signature: &[u8]) -> Result<bool, EcError> {
+        // 长度错误等格式问题同样视为签名无效
+        Ok(Verifier::new_without_digest(&self.key)?.verify_oneshot(signature, data).unwrap_or(false))
+    }
+
+    pub(crate) fn pkey(&self) -> &PKey<Public> {
+        &self.key
+    }
+}
+
+impl fmt::Debug for Ed25519PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ed25519PublicKey")
+    }
+}
+
+/// X25519 私钥（RFC 7748），用于 ECDH 密钥协商。
+#[derive(Clone)]
+pub struct X25519PrivateKey {
+    key: PKey<Private>,
+}
+
+/// X25519 公钥。
+#[derive(Clone)]
+pub struct X25519PublicKey {
+    key: PKey<Public>,
+}
+
+impl X25519PrivateKey {
+    /// 生成新的密钥对，每次会话使用新的密钥对可以获得前向安全性。
+    pub fn generate() -> Result<Self, EcError> {
+        Ok(X25519PrivateKey { key: PKey::generate_x25519()? })
+    }
+
+    /// 从32字节的原始私钥导入。
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EcError> {
+        Ok(X25519PrivateKey { key: raw_private(bytes, Id::X25519)? })
+    }
+
+    /// 从 PKCS#8 PEM（`BEGIN PRIVATE KEY`）导入私钥。
+    pub fn from_pem(pem: &[u8]) -> Result<Self, EcError> {
+        let key = PKey::private_key_from_pem(pem).map_err(invalid_key)?;
+        Ok(X25519PrivateKey { key: expect_id(key, Id::X25519)? })
+    }
+
+    /// 从 PKCS#8 DER 导入私钥。
+    pub fn from_der(der: &[u8]) -> Result<Self, EcError> {
+        let key = PKey::private_key_from_der(der).map_err(invalid_key)?;
+        Ok(X25519PrivateKey { key: expect_id(key, Id::X25519)? })
+    }
+
+    /// 导出为32字节的原始私钥。
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EcError> {
+        Ok(self.key.raw_private_key()?)
+    }
+
+    /// 导出为 PKCS#8 PEM（`BEGIN PRIVATE KEY`）。
+    pub fn to_pem(&self) -> Result<String, EcError> {
+        pem_string(self.key.private_key_to_pem_pkcs8()?)
+    }
+
+    /// 导出为 PKCS#8 DER。
+    pub fn to_der(&self) -> Result<Vec<u8>, EcError> {
+        Ok(self.key.private_key_to_pkcs8()?)
+    }
+
+    /// 返回对应的公钥，发送给对方用于密钥协商。
+    pub fn public_key(&self) -> Result<X25519PublicKey, EcError> {
+        Ok(X25519PublicKey { key: raw_public(&self.key.raw_public_key()?, Id::X25519)? })
+    }
+
+    /// 与对方的公钥协商出32字节的共享密钥。
+    ///
+    /// 共享密钥不是均匀分布的，不能直接用作对称密钥，应通过 `derive_key` 派生。
+    ///
+    /// 返回值：Result<Vec<u8>, EcError> - 共享密钥，对方公钥是小阶点时返回 `KeyAgreementFailed`
+    pub fn diffie_hellman(&self, peer: &X25519PublicKey) -> Result<Vec<u8>, EcError> {
+        let mut deriver = Deriver::new(&self.key)?;
+        deriver.set_peer(&peer.key)?;
+        let shared = deriver.derive_to_vec().map_err(|_| EcError::KeyAgreementFailed)?;
+        if shared.iter().all(|byte| *byte == 0) {
+            return Err(EcError::KeyAgreementFailed);
+        }
+        Ok(shared)
+    }
+
+    /// 协商共享密钥并用 HKDF-SHA256 派生对称密钥，结果可以直接用于 `SecureUtil::aes_encrypt` 或 `Sm4::new`。
+    ///
+    /// # 参数
+    ///
+    /// * `peer`: &X25519PublicKey - 对方的公钥
+    /// * `salt`: &[u8] - HKDF 的盐，可以为空
+    /// * `info`: &[u8] - 上下文信息，双方必须一致，例如协议名称和版本
+    /// * `len`: usize - 密钥字节数，AES-256 为32
+    ///
+    /// 返回值：Result<Vec<u8>, EcError> - 对称密钥，双方得到的结果相同
+    pub fn derive_key(&self, peer: &X25519PublicKey, salt: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>, EcError> {
+        if len == 0 {
+            return Err(EcError::InvalidKeyLength(len));
+        }
+        let shared = self.diffie_hellman(peer)?;
+        hkdf(DigestAlgorithm::Sha256, &shared, salt, info, len).ok_or(EcError::InvalidKeyLength(len))
+    }
+}
+
+impl fmt::Debug for X25519PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 不输出密钥内容
+        write!(f, "X25519PrivateKey")
+    }
+}
+
+impl X25519PublicKey {
+    /// 从32字节的原始公钥导入。
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EcError> {
+        Ok(X25519PublicKey { key: raw_public(bytes, Id::X25519)? })
+    }
+
+    /// 从 X.509 SubjectPublicKeyInfo PEM（`BEGIN PUBLIC KEY`）导入公钥。
+    pub fn from_pem(pem: &[u8]) -> Result<Self, EcError> {
+        let key = PKey::public_key_from_pem(pem).map_err(invalid_key)?;
+        Ok(X25519PublicKey { key: expect_id(key, Id::X25519)? })
+    }
+
+    /// 从 X.509 SubjectPublicKeyInfo DER 导入公钥。
+    pub fn from_der(der: &[u8]) -> Result<Self, EcError> {
+        let key = PKey::public_key_from_der(der).map_err(invalid_key)?;
+        Ok(X25519PublicKey { key: expect_id(key, Id::X25519)? })
+    }
+
+    /// 导出为32字节的原始公钥。
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EcError> {
+        Ok(self.key.raw_public_key()?)
+    }
+
+    /// 导出为 X.509 SubjectPublicKeyInfo PEM（`BEGIN PUBLIC KEY`）。
+    pub fn to_pem(&self) -> Result<String, EcError> {
+        pem_string(self.key.public_key_to_pem()?)
+    }
+
+    /// 导出为 X.509 SubjectPublicKeyInfo DER。
+    pub fn to_der(&self) -> Result<Vec<u8>, EcError> {
+        Ok(self.key.public_key_to_der()?)
+    }
+}
+
+impl fmt::Debug for X25519PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "X25519PublicKey")
+    }
+}
+
+fn raw_private(bytes: &[u8], id: Id) -> Result<PKey<Private>, EcError> {
+    if bytes.len() != CURVE25519_KEY_LEN {
+        return Err(EcError::InvalidKey(format!("私钥应为{}字节，实际为{}字节", CURVE25519_KEY_LEN, bytes.len())));
+    }
+    PKey::private_key_from_raw_bytes(bytes, id).map_err(invalid_key)
+}
+
+fn raw_public(bytes: &[u8], id: Id) -> Result<PKey<Public>, EcError> {
+    if bytes.len() != CURVE25519_KEY_LEN {
+        return Err(EcError::InvalidKey(format!("公钥应为{}字节，实际为{}字节", CURVE25519_KEY_LEN, bytes.len())));
+    }
+    PKey::public_key_from_raw_bytes(bytes, id).map_err(invalid_key)
+}
+
+fn expect_id<T>(key: PKey<T>, id: Id) -> Result<PKey<T>, EcError> {
+    if key.id() == id {
+        Ok(key)
+    } else {
+        Err(EcError::InvalidKey(format!("密钥类型应为{:?}，实际为{:?}", id, key.id())))
+    }
+}
+
+fn invalid_key(e: ErrorStack) -> EcError {
+    EcError::InvalidKey(e.to_string())
+}
+
+fn pem_string(pem: Vec<u8>) -> Result<String, EcError> {
+    String::from_utf8(pem).map_err(|e| EcError::InvalidKey(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure::aes::AesMode;
+    use crate::secure::secure::SecureUtil;
+
+    fn hex(input: &str) -> Vec<u8> {
+        SecureUtil::from_hex(input).unwrap()
+    }
+
+    #[test]
+    fn test_ecdsa_known_signature() {
+        // RFC 6979 A.2.5，P-256 + SHA-256，消息 "sample"
+        let key = EcdsaPrivateKey::from_bytes(
+            EcCurve::P256,
+            &hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721"),
+        )
+        .unwrap();
+        let public = key.public_key().unwrap();
+        assert_eq!(
+            SecureUtil::to_hex(&public.to_bytes(false).unwrap()),
+            concat!(
+                "0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6",
+                "7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"
+            )
+        );
+        let signature = hex(concat!(
+            "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716",
+            "f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8"
+        ));
+        assert!(public.verify(DigestAlgorithm::Sha256, b"sample", &signature).unwrap());
+        assert!(!public.verify(DigestAlgorithm::Sha256, b"sample!", &signature).unwrap());
+        assert!(!public.verify(DigestAlgorithm::Sha256, b"sample", &signature[..63]).unwrap());
+
+        let der = signature_to_der(EcCurve::P256, &signature).unwrap();
+        assert_eq!(signature_from_der(EcCurve::P256, &der).unwrap(), signature);
+        assert!(matches!(signature_from_der(EcCurve::P256, b"junk"), Err(EcError::InvalidSignature(_))));
+
+        assert!(matches!(EcdsaPrivateKey::from_bytes(EcCurve::P256, &[0; 32]), Err(EcError::InvalidKey(_))));
+        assert!(matches!(EcdsaPrivateKey::from_bytes(EcCurve::P256, &[0xff; 32]), Err(EcError::InvalidKey(_))));
+        assert!(matches!(EcdsaPrivateKey::from_bytes(EcCurve::P384, &[1; 32]), Err(EcError::InvalidKey(_))));
+    }
+
+    #[test]
+    fn test_ecdsa_sign_and_key_formats() {
+        for curve in [EcCurve::P256, EcCurve::P384] {
+            let key = EcdsaPrivateKey::generate(curve).unwrap();
+            let public = key.public_key().unwrap();
+            let signature = key.sign(curve.digest(), b"firmware").unwrap();
+            assert_eq!(signature.len(), curve.field_len() * 2);
+            assert!(public.verify(curve.digest(), b"firmware", &signature).unwrap());
+            assert!(!public.verify(DigestAlgorithm::Sha512, b"firmware", &signature).unwrap());
+            assert!(matches!(key.sign(DigestAlgorithm::Blake3, b"firmware"), Err(EcError::UnsupportedDigest(_))));
+
+            for restored in [
+                EcdsaPrivateKey::from_pem(key.to_pem().unwrap().as_bytes()).unwrap(),
+                EcdsaPrivateKey::from_pem(key.to_sec1_pem().unwrap().as_bytes()).unwrap(),
+                EcdsaPrivateKey::from_der(&key.to_der().unwrap()).unwrap(),
+                EcdsaPrivateKey::from_bytes(curve, &key.to_bytes().unwrap()).unwrap(),
+            ] {
+                assert_eq!(restored.curve(), curve);
+                assert!(public.verify(curve.digest(), b"data", &restored.sign(curve.digest(), b"data").unwrap()).unwrap());
+            }
+
+            let uncompressed = public.to_bytes(false).unwrap();
+            let compressed = public.to_bytes(true).unwrap();
+            assert_eq!((uncompressed.len(), compressed.len()), (1 + 2 * curve.field_len(), 1 + curve.field_len()));
+            for restored in [
+                EcdsaPublicKey::from_pem(public.to_pem().unwrap().as_bytes()).unwrap(),
+                EcdsaPublicKey::from_der(&public.to_der().unwrap()).unwrap(),
+                EcdsaPublicKey::from_bytes(curve, &uncompressed).unwrap(),
+                EcdsaPublicKey::from_bytes(curve, &compressed).unwrap(),
+            ] {
+                assert_eq!(restored.to_bytes(false).unwrap(), uncompressed);
+            }
+
+            let mut off_curve = uncompressed.clone();
+            off_curve[5] ^= 1;
+            assert!(matches!(EcdsaPublicKey::from_bytes(curve, &off_curve), Err(EcError::InvalidKey(_))));
+        }
+
+        let ed25519 = Ed25519PrivateKey::generate().unwrap();
+        assert!(matches!(EcdsaPrivateKey::from_pem(ed25519.to_pem().unwrap().as_bytes()), Err(EcError::InvalidKey(_))));
+    }
+
+    #[test]
+    fn test_ed25519() {
+        // RFC 8032 7.1 测试用例1和测试用例2
+        let key = Ed25519PrivateKey::from_bytes(&hex("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60")).unwrap();
+        let public = key.public_key().unwrap();
+        assert_eq!(
+            SecureUtil::to_hex(&public.to_bytes().unwrap()),
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+        );
+        let signature = key.sign(b"").unwrap();
+        assert_eq!(signature.len(), ED25519_SIGNATURE_LEN);
+        assert_eq!(
+            SecureUtil::to_hex(&signature),
+            concat!(
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555",
+                "fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+            )
+        );
+        assert!(public.verify(b"", &signature).unwrap());
+        assert!(!public.verify(b"x", &signature).unwrap());
+        assert!(!public.verify(b"", &signature[1..]).unwrap());
+
+        let public = Ed25519PublicKey::from_bytes(&hex("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c")).unwrap();
+        let signature = hex(concat!(
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da",
+            "085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"
+        ));
+        assert!(public.verify(&[0x72], &signature).unwrap());
+
+        let restored = Ed25519PrivateKey::from_pem(key.to_pem().unwrap().as_bytes()).unwrap();
+        assert_eq!(restored.to_bytes().unwrap(), key.to_bytes().unwrap());
+        assert_eq!(Ed25519PrivateKey::from_der(&key.to_der().unwrap()).unwrap().to_bytes().unwrap(), key.to_bytes().unwrap());
+        let public = key.public_key().unwrap();
+        assert_eq!(Ed25519PublicKey::from_pem(public.to_pem().unwrap().as_bytes()).unwrap().to_bytes().unwrap(), public.to_bytes().unwrap());
+        assert_eq!(Ed25519PublicKey::from_der(&public.to_der().unwrap()).unwrap().to_bytes().unwrap(), public.to_bytes().unwrap());
+        assert!(matches!(Ed25519PrivateKey::from_bytes(&[1; 31]), Err(EcError::InvalidKey(_))));
+        let x25519 = X25519PrivateKey::generate().unwrap();
+        assert!(matches!(Ed25519PrivateKey::from_pem(x25519.to_pem().unwrap().as_bytes()), Err(EcError::InvalidKey(_))));
+    }
+
+    #[test]
+    fn test_x25519_key_agreement() {
+        // RFC 7748 6.1
+        let alice = X25519PrivateKey::from_bytes(&hex("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a")).unwrap();
+        let bob = X25519PrivateKey::from_bytes(&hex("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb")).unwrap();
+        let alice_public = alice.public_key().unwrap();
+        let bob_public = bob.public_key().unwrap();
+        assert_eq!(
+            SecureUtil::to_hex(&alice_public.to_bytes().unwrap()),
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+        );
+        assert_eq!(
+            SecureUtil::to_hex(&bob_public.to_bytes().unwrap()),
+            "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"
+        );
+        let shared = "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742";
+        assert_eq!(SecureUtil::to_hex(&alice.diffie_hellman(&bob_public).unwrap()), shared);
+        assert_eq!(SecureUtil::to_hex(&bob.diffie_hellman(&alice_public).unwrap()), shared);
+
+        // 双方派生出相同的 AES 密钥
+        let alice_key = alice.derive_key(&bob_public, b"salt", b"r-tool v1", 32).unwrap();
+        let bob_key = bob.derive_key(&alice_public, b"salt", b"r-tool v1", 32).unwrap();
+        assert_eq!(alice_key, bob_key);
+        assert_ne!(alice_key, alice.derive_key(&bob_public, b"salt", b"r-tool v2", 32).unwrap());
+        let envelope = SecureUtil::aes_encrypt(AesMode::Gcm, &alice_key, b"telemetry", b"").unwrap();
+        assert_eq!(SecureUtil::aes_decrypt(AesMode::Gcm, &bob_key, &envelope, b"").unwrap(), b"telemetry");
+        assert!(matches!(alice.derive_key(&bob_public, b"", b"", 0), Err(EcError::InvalidKeyLength(0))));
+        assert!(matches!(alice.derive_key(&bob_public, b"", b"", 8161), Err(EcError::InvalidKeyLength(8161))));
+
+        // 小阶点
+        let zero = X25519PublicKey::from_bytes(&[0; 32]).unwrap();
+        assert!(matches!(alice.diffie_hellman(&zero), Err(EcError::KeyAgreementFailed)));
+
+        let restored = X25519PrivateKey::from_pem(alice.to_pem().unwrap().as_bytes()).unwrap();
+        assert_eq!(restored.to_bytes().unwrap(), alice.to_bytes().unwrap());
+        assert_eq!(X25519PrivateKey::from_der(&alice.to_der().unwrap()).unwrap().to_bytes().unwrap(), alice.to_bytes().unwrap());
+        let restored = X25519PublicKey::from_pem(alice_public.to_pem().unwrap().as_bytes()).unwrap();
+        assert_eq!(restored.to_bytes().unwrap(), alice_public.to_bytes().unwrap());
+        assert_eq!(X25519PublicKey::from_der(&alice_public.to_der().unwrap()).unwrap().to_bytes().unwrap(), alice_public.to_bytes().unwrap());
+    }
+}
@@ -0,0 +1,673 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crypto::util::fixed_time_eq;
+use openssl::error::ErrorStack;
+use openssl::rand::rand_bytes;
+use openssl::symm::{self, Cipher};
+
+use crate::secure::digest::DigestAlgorithm;
+use crate::secure::hmac::{hkdf, Hmac};
+use crate::secure::password::{self, PasswordAlgorithm, PasswordError};
+
+/// 文件头的魔数。
+const MAGIC: &[u8; 4] = b"RTFE";
+
+/// 当前的格式版本。
+const VERSION: u8 = 1;
+
+/// 随机盐的字节数。
+const SALT_LEN: usize = 16;
+
+/// 每个文件随机生成的随机数前缀的字节数，后面依次是4字节的分块序号和1字节的结束标记。
+const NONCE_PREFIX_LEN: usize = 7;
+
+/// GCM 认证标签的字节数。
+const TAG_LEN: usize = 16;
+
+/// 文件头认证码的字节数。
+const HEADER_MAC_LEN: usize = 32;
+
+/// 默认的明文分块大小。
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// 允许的最大分块大小，解密时按分块大小分配缓冲区。
+pub const MAX_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+/// 文件格式允许的最大密钥派生内存（1GiB），Argon2id 和 scrypt 共用。
+const MAX_KDF_MEMORY_KIB: u32 = 1 << 20;
+
+/// 文件格式允许的最大 Argon2id 迭代次数和 scrypt 并行度。
+const MAX_KDF_TIME_COST: u32 = 16;
+
+/// 文件格式允许的最大 scrypt log_n。
+const MAX_SCRYPT_LOG_N: u8 = 20;
+
+/// 文件格式允许的最大 PBKDF2 迭代次数。
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+
+/// 派生数据加密密钥和文件头认证密钥时使用的 HKDF 上下文。
+const ENCRYPTION_KEY_INFO: &[u8] = b"r-tool file encryption key";
+const HEADER_KEY_INFO: &[u8] = b"r-tool file header key";
+
+/// 文件加密错误。
+#[derive(Debug)]
+pub enum FileCipherError {
+    /// 读写失败
+    Io(io::Error),
+    /// 密钥派生参数或分块大小无效
+    InvalidParams(String),
+    /// 不是加密文件，或文件头格式错误，包括密钥派生参数超过格式允许的上限
+    InvalidFormat(String),
+    /// 文件的密钥派生参数超过调用方通过 `KdfLimits` 设置的上限
+    KdfLimitExceeded(String),
+    /// 不支持的格式版本
+    UnsupportedVersion(u8),
+    /// 密码错误，或文件头被篡改
+    WrongPassword,
+    /// 分块认证失败：数据被篡改、截断在分块中间、分块顺序被调整或末尾追加了数据
+    DecryptFailed,
+    /// 文件在分块边界处被截断，缺少结束分块
+    Truncated,
+    /// OpenSSL 返回的其他错误
+    OpenSsl(ErrorStack),
+}
+
+impl fmt::Display for FileCipherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileCipherError::Io(e) => write!(f, "读写失败: {}", e),
+            FileCipherError::InvalidParams(msg) => write!(f, "无效的加密参数: {}", msg),
+            FileCipherError::InvalidFormat(msg) => write!(f, "无效的加密文件: {}", msg),
+            FileCipherError::KdfLimitExceeded(msg) => write!(f, "密钥派生参数超过允许的上限: {}", msg),
+            FileCipherError::UnsupportedVersion(version) => write!(f, "不支持的加密文件版本: {}", version),
+            FileCipherError::WrongPassword => write!(f, "密码错误或文件头已被篡改"),
+            FileCipherError::DecryptFailed => write!(f, "解密失败，文件已被篡改"),
+            FileCipherError::Truncated => write!(f, "加密文件不完整"),
+            FileCipherError::OpenSsl(e) => write!(f, "OpenSSL 错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FileCipherError {}
+
+impl From<io::Error> for FileCipherError {
+    fn from(e: io::Error) -> Self {
+        FileCipherError::Io(e)
+    }
+}
+
+impl From<ErrorStack> for FileCipherError {
+    fn from(e: ErrorStack) -> Self {
+        FileCipherError::OpenSsl(e)
+    }
+}
+
+impl From<PasswordError> for FileCipherError {
+    fn from(e: PasswordError) -> Self {
+        match e {
+            PasswordError::InvalidParams(msg) => FileCipherError::InvalidParams(msg),
+            PasswordError::InvalidHash(msg) => FileCipherError::InvalidFormat(msg),
+            PasswordError::OpenSsl(e) => FileCipherError::OpenSsl(e),
+        }
+    }
+}
+
+/// 使用密码流式加密文件。
+///
+/// 密码经过 Argon2id、scrypt 或 PBKDF2 派生出密钥，数据按分块使用 AES-256-GCM 加密，每个分块单独认证，
+/// 内存占用只与分块大小有关。分块的随机数包含序号和结束标记，调换、删除分块或截断文件都会在解密时被发现。
+///
+/// 格式：`RTFE`、版本、密钥派生算法和参数、盐、随机数前缀、分块大小、文件头认证码，然后是各个分块的密文和认证标签。
+pub struct FileEncryptor {
+    password: Vec<u8>,
+    algorithm: PasswordAlgorithm,
+    chunk_size: usize,
+}
+
+impl FileEncryptor {
+    /// 使用默认参数的 Argon2id 和 64KiB 分块。
+    pub fn new(password: &[u8]) -> Self {
+        FileEncryptor { password: password.to_vec(), algorithm: PasswordAlgorithm::default(), chunk_size: DEFAULT_CHUNK_SIZE }
+    }
+
+    /// 设置密钥派生算法，支持 Argon2id、scrypt 和 PBKDF2，不支持 bcrypt。
+    pub fn with_algorithm(mut self, algorithm: PasswordAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// 设置明文分块大小，取值1 ~ `MAX_CHUNK_SIZE`。
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// 从读取器读取明文，将加密结果写入写入器。
+    ///
+    /// # 参数
+    ///
+    /// * `reader`: R - 明文
+    /// * `writer`: W - 密文的输出
+    ///
+    /// 返回值：Result<u64, FileCipherError> - 加密的明文字节数
+    pub fn encrypt<R: Read, W: Write>(&self, mut reader: R, mut writer: W) -> Result<u64, FileCipherError> {
+        if self.chunk_size == 0 || self.chunk_size > MAX_CHUNK_SIZE {
+            return Err(FileCipherError::InvalidParams(format!("分块大小必须在1~{}之间，实际为{}", MAX_CHUNK_SIZE, self.chunk_size)));
+        }
+        // 超过上限的参数生成的文件无法解密
+        KdfLimits::default().check(&self.algorithm).map_err(FileCipherError::InvalidParams)?;
+        let salt = password::generate_salt(SALT_LEN)?;
+        let mut nonce_prefix = [0; NONCE_PREFIX_LEN];
+        rand_bytes(&mut nonce_prefix)?;
+        let header = Header { algorithm: self.algorithm, salt, nonce_prefix, chunk_size: self.chunk_size };
+        let keys = Keys::derive(&self.password, &header)?;
+        let header_bytes = header.to_bytes();
+        writer.write_all(&header_bytes)?;
+        writer.write_all(&keys.header_mac(&header_bytes))?;
+
+        let mut buffer = vec![0; self.chunk_size];
+        let mut total = 0u64;
+        for counter in 0..=u32::MAX {
+            let len = read_full(&mut reader, &mut buffer)?;
+            total += len as u64;
+            // 最后一个分块总是比分块大小短，明文长度正好是分块大小的整数倍时追加一个空分块
+            let last = len < buffer.len();
+            let mut tag = [0; TAG_LEN];
+            let nonce = header.nonce(counter, last);
+            let ciphertext = symm::encrypt_aead(Cipher::aes_256_gcm(), &keys.encryption, Some(&nonce), &[], &buffer[..len], &mut tag)?;
+            writer.write_all(&ciphertext)?;
+            writer.write_all(&tag)?;
+            if last {
+                writer.flush()?;
+                return Ok(total);
+            }
+        }
+        Err(FileCipherError::InvalidParams("文件过大，分块数超过上限".to_string()))
+    }
+
+    /// 加密文件，失败时删除不完整的输出文件。
+    ///
+    /// # 参数
+    ///
+    /// * `source`: P - 明文文件
+    /// * `target`: Q - 密文文件，已存在时覆盖
+    ///
+    /// 返回值：Result<u64, FileCipherError> - 加密的明文字节数
+    pub fn encrypt_file<P: AsRef<Path>, Q: AsRef<Path>>(&self, source: P, target: Q) -> Result<u64, FileCipherError> {
+        let reader = BufReader::new(File::open(source)?);
+        write_file(target.as_ref(), |writer| self.encrypt(reader, writer))
+    }
+}
+
+impl fmt::Debug for FileEncryptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 不输出密码
+        f.debug_struct("FileEncryptor").field("algorithm", &self.algorithm).field("chunk_size", &self.chunk_size).finish()
+    }
+}
+
+/// 解密时允许的密钥派生成本上限。
+///
+/// 密钥派生参数来自文件头，在验证文件头认证码之前就要使用，伪造或损坏的文件可能要求极大的内存或计算量。
+/// 默认值即文件格式允许的上限：内存1GiB、Argon2id 迭代次数和 scrypt 并行度16、scrypt log_n 20、PBKDF2 迭代一千万次，
+/// 超过这些上限的文件头视为格式错误；`with_*` 只能进一步收紧。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfLimits {
+    max_memory_kib: u32,
+    max_time_cost: u32,
+    max_pbkdf2_iterations: u32,
+}
+
+impl Default for KdfLimits {
+    fn default() -> Self {
+        KdfLimits { max_memory_kib: MAX_KDF_MEMORY_KIB, max_time_cost: MAX_KDF_TIME_COST, max_pbkdf2_iterations: MAX_PBKDF2_ITERATIONS }
+    }
+}
+
+impl KdfLimits {
+    /// 设置 Argon2id 和 scrypt 允许使用的最大内存，单位KiB。
+    pub fn with_max_memory_kib(mut self, memory_kib: u32) -> Self {
+        self.max_memory_kib = memory_kib.min(MAX_KDF_MEMORY_KIB);
+        self
+    }
+
+    /// 设置 Argon2id 的最大迭代次数和 scrypt 的最大并行度。
+    pub fn with_max_time_cost(mut self, time_cost: u32) -> Self {
+        self.max_time_cost = time_cost.min(MAX_KDF_TIME_COST);
+        self
+    }
+
+    /// 设置 PBKDF2 的最大迭代次数。
+    pub fn with_max_pbkdf2_iterations(mut self, iterations: u32) -> Self {
+        self.max_pbkdf2_iterations = iterations.min(MAX_PBKDF2_ITERATIONS);
+        self
+    }
+
+    fn check(&self, algorithm: &PasswordAlgorithm) -> Result<(), String> {
+        let (memory_kib, time_cost) = match *algorithm {
+            PasswordAlgorithm::Argon2id { memory_kib, iterations, .. } => (memory_kib as u64, iterations),
+            PasswordAlgorithm::Scrypt { log_n, r, p } => {
+                if log_n > MAX_SCRYPT_LOG_N {
+                    return Err(format!("scrypt log_n 为{}，最大为{}", log_n, MAX_SCRYPT_LOG_N));
+                }
+                // 128 * r * N 字节
+                (((r as u64) << log_n) / 8, p)
+            }
+            PasswordAlgorithm::Pbkdf2Sha256 { iterations } => {
+                if iterations > self.max_pbkdf2_iterations {
+                    return Err(format!("PBKDF2 迭代次数为{}，最大为{}", iterations, self.max_pbkdf2_iterations));
+                }
+                return Ok(());
+            }
+            PasswordAlgorithm::Bcrypt { .. } => return Err("bcrypt 不能用于派生密钥".to_string()),
+        };
+        if memory_kib > self.max_memory_kib as u64 {
+            return Err(format!("需要{}KiB内存，最大为{}KiB", memory_kib, self.max_memory_kib));
+        }
+        if time_cost > self.max_time_cost {
+            return Err(format!("{} 的迭代次数或并行度为{}，最大为{}", algorithm.name(), time_cost, self.max_time_cost));
+        }
+        Ok(())
+    }
+}
+
+/// 解密 `FileEncryptor` 生成的数据，逐个分块认证后写入写入器。
+///
+/// 分块在认证通过后立即写出，如果之后的分块认证失败或文件被截断，已经写出的数据不完整，
+/// 调用方应在返回错误时丢弃输出；`decrypt_file` 会自动删除输出文件。
+///
+/// # 参数
+///
+/// * `reader`: R - 密文
+/// * `writer`: W - 明文的输出
+/// * `password`: &[u8] - 加密时使用的密码
+/// * `limits`: KdfLimits - 允许的密钥派生成本，一般使用 `KdfLimits::default()`
+///
+/// 返回值：Result<u64, FileCipherError> - 解密的明文字节数，密码错误时返回 `WrongPassword`，
+/// 密钥派生参数超过 `limits` 时返回 `KdfLimitExceeded`
+pub fn decrypt<R: Read, W: Write>(mut reader: R, mut writer: W, password: &[u8], limits: KdfLimits) -> Result<u64, FileCipherError> {
+    let (header, header_bytes) = Header::read(&mut reader)?;
+    limits.check(&header.algorithm).map_err(FileCipherError::KdfLimitExceeded)?;
+    let keys = Keys::derive(password, &header)?;
+    let mut mac = [0; HEADER_MAC_LEN];
+    if read_full(&mut reader, &mut mac)? != HEADER_MAC_LEN {
+        return Err(FileCipherError::InvalidFormat("文件头不完整".to_string()));
+    }
+    if !fixed_time_eq(&keys.header_mac(&header_bytes), &mac) {
+        return Err(FileCipherError::WrongPassword);
+    }
+
+    let mut buffer = vec![0; header.chunk_size + TAG_LEN];
+    let mut total = 0u64;
+    for counter in 0..=u32::MAX {
+        let len = read_full(&mut reader, &mut buffer)?;
+        if len < TAG_LEN {
+            return Err(if len == 0 { FileCipherError::Truncated } else { FileCipherError::DecryptFailed });
+        }
+        let last = len < buffer.len();
+        let (ciphertext, tag) = buffer[..len].split_at(len - TAG_LEN);
+        let nonce = header.nonce(counter, last);
+        let plaintext = symm::decrypt_aead(Cipher::aes_256_gcm(), &keys.encryption, Some(&nonce), &[], ciphertext, tag)
+            .map_err(|_| FileCipherError::DecryptFailed)?;
+        writer.write_all(&plaintext)?;
+        total += plaintext.len() as u64;
+        // 读到不满的分块时已经到达末尾，末尾追加的数据会使结束分块认证失败
+        if last {
+            writer.flush()?;
+            return Ok(total);
+        }
+    }
+    Err(FileCipherError::InvalidFormat("分块数超过上限".to_string()))
+}
+
+/// 解密文件，失败时删除不完整的输出文件。
+///
+/// # 参数
+///
+/// * `source`: P - 密文文件
+/// * `target`: Q - 明文文件，已存在时覆盖
+/// * `password`: &[u8] - 加密时使用的密码
+/// * `limits`: KdfLimits - 允许的密钥派生成本，一般使用 `KdfLimits::default()`
+///
+/// 返回值：Result<u64, FileCipherError> - 解密的明文字节数
+pub fn decrypt_file<P: AsRef<Path>, Q: AsRef<Path>>(source: P, target: Q, password: &[u8], limits: KdfLimits) -> Result<u64, FileCipherError> {
+    let reader = BufReader::new(File::open(source)?);
+    write_file(target.as_ref(), |writer| decrypt(reader, writer, password, limits))
+}
+
+fn write_file<F>(target: &Path, write: F) -> Result<u64, FileCipherError>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<u64, FileCipherError>,
+{
+    let mut writer = BufWriter::new(File::create(target)?);
+    let result = write(&mut writer).and_then(|total| {
+        writer.flush()?;
+        Ok(total)
+    });
+    if result.is_err() {
+        drop(writer);
+        let _ = fs::remove_file(target);
+    }
+    result
+}
+
+struct Header {
+    algorithm: PasswordAlgorithm,
+    salt: Vec<u8>,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    chunk_size: usize,
+}
+
+impl Header {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        match self.algorithm {
+            PasswordAlgorithm::Argon2id { memory_kib, iterations, parallelism } => {
+                bytes.push(1);
+                for value in [memory_kib, iterations, parallelism] {
+                    bytes.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+            PasswordAlgorithm::Scrypt { log_n, r, p } => {
+                bytes.push(2);
+                bytes.push(log_n);
+                bytes.extend_from_slice(&r.to_be_bytes());
+                bytes.extend_from_slice(&p.to_be_bytes());
+            }
+            PasswordAlgorithm::Pbkdf2Sha256 { iterations } => {
+                bytes.push(3);
+                bytes.extend_from_slice(&iterations.to_be_bytes());
+            }
+            // Keys::derive 已经拒绝了 bcrypt
+            PasswordAlgorithm::Bcrypt { .. } => unreachable!("bcrypt 不能用于派生密钥"),
+        }
+        bytes.push(self.salt.len() as u8);
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&self.nonce_prefix);
+        bytes.extend_from_slice(&(self.chunk_size as u32).to_be_bytes());
+        bytes
+    }
+
+    /// 读取文件头，同时返回原始字节用于验证认证码。
+    fn read<R: Read>(reader: &mut R) -> Result<(Self, Vec<u8>), FileCipherError> {
+        let mut recorder = Recorder { reader, bytes: Vec::new() };
+        let mut magic = [0; 6];
+        recorder.take(&mut magic)?;
+        if &magic[..4] != MAGIC {
+            return Err(FileCipherError::InvalidFormat("不是加密文件".to_string()));
+        }
+        if magic[4] != VERSION {
+            return Err(FileCipherError::UnsupportedVersion(magic[4]));
+        }
+        let algorithm = match magic[5] {
+            1 => PasswordAlgorithm::Argon2id {
+                memory_kib: recorder.u32()?,
+                iterations: recorder.u32()?,
+                parallelism: recorder.u32()?,
+            },
+            2 => PasswordAlgorithm::Scrypt { log_n: recorder.u8()?, r: recorder.u32()?, p: recorder.u32()? },
+            3 => PasswordAlgorithm::Pbkdf2Sha256 { iterations: recorder.u32()? },
+            other => return Err(FileCipherError::InvalidFormat(format!("未知的密钥派生算法: {}", other))),
+        };
+        // 在派生密钥之前拒绝超出格式上限的参数，避免伪造的文件头耗尽内存或计算资源
+        KdfLimits::default().check(&algorithm).map_err(FileCipherError::InvalidFormat)?;
+        let mut salt = vec![0; recorder.u8()? as usize];
+        if salt.len() < 8 {
+            return Err(FileCipherError::InvalidFormat(format!("盐的长度过短: {}", salt.len())));
+        }
+        recorder.take(&mut salt)?;
+        let mut nonce_prefix = [0; NONCE_PREFIX_LEN];
+        recorder.take(&mut nonce_prefix)?;
+        let chunk_size = recorder.u32()? as usize;
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(FileCipherError::InvalidFormat(format!("无效的分块大小: {}", chunk_size)));
+        }
+        let bytes = recorder.bytes;
+        Ok((Header { algorithm, salt, nonce_prefix, chunk_size }, bytes))
+    }
+
+    fn nonce(&self, counter: u32, last: bool) -> Vec<u8> {
+        let mut nonce = self.nonce_prefix.to_vec();
+        nonce.extend_from_slice(&counter.to_be_bytes());
+        nonce.push(u8::from(last));
+        nonce
+    }
+}
+
+/// 读取文件头字段并记录读到的原始字节。
+struct Recorder<'a, R> {
+    reader: &'a mut R,
+    bytes: Vec<u8>,
+}
+
+impl<R: Read> Recorder<'_, R> {
+    fn take(&mut self, buf: &mut [u8]) -> Result<(), FileCipherError> {
+        if read_full(self.reader, buf)? != buf.len() {
+            return Err(FileCipherError::InvalidFormat("文件头不完整".to_string()));
+        }
+        self.bytes.extend_from_slice(buf);
+        Ok(())
+    }
+
+    fn u8(&mut self) -> Result<u8, FileCipherError> {
+        let mut buf = [0; 1];
+        self.take(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, FileCipherError> {
+        let mut buf = [0; 4];
+        self.take(&mut buf)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+}
+
+struct Keys {
+    encryption: Vec<u8>,
+    header: Vec<u8>,
+}
+
+impl Keys {
+    fn derive(password: &[u8], header: &Header) -> Result<Self, FileCipherError> {
+        let master = header.algorithm.derive_key(password, &header.salt, 32)?;
+        let expand = |info| hkdf(DigestAlgorithm::Sha256, &master, &[], info, 32).expect("32字节不超过 HKDF 上限");
+        Ok(Keys { encryption: expand(ENCRYPTION_KEY_INFO), header: expand(HEADER_KEY_INFO) })
+    }
+
+    fn header_mac(&self, header: &[u8]) -> Vec<u8> {
+        let mut mac = Hmac::new(DigestAlgorithm::Sha256, &self.header);
+        mac.update(header);
+        mac.finalize()
+    }
+}
+
+/// 读满缓冲区，遇到文件末尾时返回实际读到的字节数。
+fn read_full<R: Read + ?Sized>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHUNK: usize = 16;
+
+    /// PBKDF2 文件头（38字节）加上认证码的长度。
+    const HEADER_LEN: usize = 70;
+
+    fn encryptor() -> FileEncryptor {
+        FileEncryptor::new(b"correct horse")
+            .with_algorithm(PasswordAlgorithm::Pbkdf2Sha256 { iterations: 10 })
+            .with_chunk_size(CHUNK)
+    }
+
+    fn encrypt(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        assert_eq!(encryptor().encrypt(data, &mut out).unwrap(), data.len() as u64);
+        out
+    }
+
+    fn decrypt_bytes(data: &[u8]) -> Result<Vec<u8>, FileCipherError> {
+        let mut out = Vec::new();
+        decrypt(data, &mut out, b"correct horse", KdfLimits::default())?;
+        Ok(out)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data: Vec<u8> = (0..200u8).collect();
+        for len in [0, 1, CHUNK - 1, CHUNK, CHUNK + 1, 3 * CHUNK, 200] {
+            let encrypted = encrypt(&data[..len]);
+            // 每个分块增加16字节的标签，最后一个分块总是不满
+            assert_eq!(encrypted.len(), HEADER_LEN + len + (len / CHUNK + 1) * TAG_LEN, "{}", len);
+            assert_eq!(decrypt_bytes(&encrypted).unwrap(), &data[..len]);
+        }
+
+        // 相同的输入每次得到不同的密文
+        assert_ne!(encrypt(b"same"), encrypt(b"same"));
+
+        for algorithm in [
+            PasswordAlgorithm::Argon2id { memory_kib: 64, iterations: 1, parallelism: 1 },
+            PasswordAlgorithm::Scrypt { log_n: 4, r: 8, p: 1 },
+        ] {
+            let mut encrypted = Vec::new();
+            FileEncryptor::new(b"pw").with_algorithm(algorithm).encrypt(&data[..], &mut encrypted).unwrap();
+            let mut decrypted = Vec::new();
+            assert_eq!(decrypt(&encrypted[..], &mut decrypted, b"pw", KdfLimits::default()).unwrap(), 200);
+            assert_eq!(decrypted, data);
+        }
+    }
+
+    #[test]
+    fn test_detects_tampering() {
+        let data = [7u8; 3 * CHUNK + 5];
+        let encrypted = encrypt(&data);
+        let block = CHUNK + TAG_LEN;
+        let chunk = |i: usize| HEADER_LEN + i * block;
+
+        let mut out = Vec::new();
+        assert!(matches!(decrypt(&encrypted[..], &mut out, b"wrong", KdfLimits::default()), Err(FileCipherError::WrongPassword)));
+
+        let mut tampered = encrypted.clone();
+        tampered[chunk(1) + 3] ^= 1;
+        assert!(matches!(decrypt_bytes(&tampered), Err(FileCipherError::DecryptFailed)));
+
+        // 修改文件头中的分块大小
+        let mut tampered = encrypted.clone();
+        tampered[HEADER_LEN - 33] ^= 1;
+        assert!(matches!(decrypt_bytes(&tampered), Err(FileCipherError::WrongPassword)));
+
+        // 调换前两个分块
+        let mut reordered = encrypted[..chunk(0)].to_vec();
+        reordered.extend_from_slice(&encrypted[chunk(1)..chunk(2)]);
+        reordered.extend_from_slice(&encrypted[chunk(0)..chunk(1)]);
+        reordered.extend_from_slice(&encrypted[chunk(2)..]);
+        assert!(matches!(decrypt_bytes(&reordered), Err(FileCipherError::DecryptFailed)));
+
+        // 在分块边界截断、在分块中间截断、删除中间的分块
+        assert!(matches!(decrypt_bytes(&encrypted[..chunk(3)]), Err(FileCipherError::Truncated)));
+        assert!(matches!(decrypt_bytes(&encrypted[..chunk(2) + 20]), Err(FileCipherError::DecryptFailed)));
+        let mut removed = encrypted[..chunk(1)].to_vec();
+        removed.extend_from_slice(&encrypted[chunk(2)..]);
+        assert!(matches!(decrypt_bytes(&removed), Err(FileCipherError::DecryptFailed)));
+
+        for extra in [1, CHUNK + TAG_LEN] {
+            let mut appended = encrypted.clone();
+            appended.extend(vec![0; extra]);
+            assert!(matches!(decrypt_bytes(&appended), Err(FileCipherError::DecryptFailed)));
+        }
+
+        assert!(matches!(decrypt_bytes(b"plain text file"), Err(FileCipherError::InvalidFormat(_))));
+        assert!(matches!(decrypt_bytes(&encrypted[..HEADER_LEN - 1]), Err(FileCipherError::InvalidFormat(_))));
+        let mut version = encrypted.clone();
+        version[4] = 9;
+        assert!(matches!(decrypt_bytes(&version), Err(FileCipherError::UnsupportedVersion(9))));
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        let mut out = Vec::new();
+        let bcrypt = FileEncryptor::new(b"pw").with_algorithm(PasswordAlgorithm::Bcrypt { cost: 4 });
+        assert!(matches!(bcrypt.encrypt(&b"data"[..], &mut out), Err(FileCipherError::InvalidParams(_))));
+        assert!(matches!(encryptor().with_chunk_size(0).encrypt(&b"data"[..], &mut out), Err(FileCipherError::InvalidParams(_))));
+        assert!(matches!(
+            encryptor().with_chunk_size(MAX_CHUNK_SIZE + 1).encrypt(&b"data"[..], &mut out),
+            Err(FileCipherError::InvalidParams(_))
+        ));
+        assert!(!format!("{:?}", encryptor()).contains("horse"));
+
+        for algorithm in [
+            PasswordAlgorithm::Argon2id { memory_kib: MAX_KDF_MEMORY_KIB + 1, iterations: 1, parallelism: 1 },
+            PasswordAlgorithm::Scrypt { log_n: MAX_SCRYPT_LOG_N + 1, r: 1, p: 1 },
+            PasswordAlgorithm::Pbkdf2Sha256 { iterations: MAX_PBKDF2_ITERATIONS + 1 },
+        ] {
+            let encryptor = FileEncryptor::new(b"pw").with_algorithm(algorithm);
+            assert!(matches!(encryptor.encrypt(&b"data"[..], &mut out), Err(FileCipherError::InvalidParams(_))));
+        }
+    }
+
+    #[test]
+    fn test_kdf_limits() {
+        let mut encrypted = Vec::new();
+        let argon2 = PasswordAlgorithm::Argon2id { memory_kib: 64, iterations: 1, parallelism: 1 };
+        FileEncryptor::new(b"pw").with_algorithm(argon2).encrypt(&b"data"[..], &mut encrypted).unwrap();
+
+        // 伪造的文件头要求 4TiB 内存，应在派生密钥之前失败
+        let mut forged = encrypted.clone();
+        forged[6..10].copy_from_slice(&u32::MAX.to_be_bytes());
+        let mut out = Vec::new();
+        let error = decrypt(&forged[..], &mut out, b"pw", KdfLimits::default()).unwrap_err();
+        let memory = format!("需要{}KiB内存，最大为{}KiB", u32::MAX, MAX_KDF_MEMORY_KIB);
+        assert!(matches!(&error, FileCipherError::InvalidFormat(msg) if *msg == memory), "{}", error);
+        // 迭代次数
+        let mut forged = encrypted.clone();
+        forged[10..14].copy_from_slice(&u32::MAX.to_be_bytes());
+        let error = decrypt(&forged[..], &mut out, b"pw", KdfLimits::default()).unwrap_err();
+        assert!(matches!(&error, FileCipherError::InvalidFormat(msg) if msg.contains("迭代次数或并行度")), "{}", error);
+
+        let strict = KdfLimits::default().with_max_memory_kib(32);
+        assert!(matches!(decrypt(&encrypted[..], &mut out, b"pw", strict), Err(FileCipherError::KdfLimitExceeded(_))));
+        let pbkdf2 = encrypt(b"data");
+        let strict = KdfLimits::default().with_max_pbkdf2_iterations(5);
+        assert!(matches!(decrypt(&pbkdf2[..], &mut out, b"correct horse", strict), Err(FileCipherError::KdfLimitExceeded(_))));
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_encrypt_file() {
+        let dir = std::env::temp_dir();
+        let source = dir.join(format!("r-tool-file-cipher-{}.txt", std::process::id()));
+        let encrypted = dir.join(format!("r-tool-file-cipher-{}.enc", std::process::id()));
+        let decrypted = dir.join(format!("r-tool-file-cipher-{}.out", std::process::id()));
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&source, &data).unwrap();
+
+        let encryptor = FileEncryptor::new(b"pw").with_algorithm(PasswordAlgorithm::Pbkdf2Sha256 { iterations: 10 });
+        assert_eq!(encryptor.encrypt_file(&source, &encrypted).unwrap(), data.len() as u64);
+        assert_eq!(decrypt_file(&encrypted, &decrypted, b"pw", KdfLimits::default()).unwrap(), data.len() as u64);
+        assert_eq!(fs::read(&decrypted).unwrap(), data);
+
+        // 解密失败时删除输出文件
+        let bytes = fs::read(&encrypted).unwrap();
+        fs::write(&encrypted, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(matches!(decrypt_file(&encrypted, &decrypted, b"pw", KdfLimits::default()), Err(FileCipherError::DecryptFailed)));
+        assert!(!decrypted.exists());
+
+        for path in [source, encrypted] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+}
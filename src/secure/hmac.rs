@@ -0,0 +1,215 @@
+use std::io::{self, Write};
+
+use crypto::util::fixed_time_eq;
+
+use crate::secure::digest::{DigestAlgorithm, Hasher};
+
+/// 内层填充字节。
+const IPAD: u8 = 0x36;
+
+/// 外层填充字节。
+const OPAD: u8 = 0x5c;
+
+/// 增量计算 HMAC（RFC 2104）的对象，适合消息体分多次到达或很大的场景。
+///
+/// 实现了 `Write`，可以配合 `io::copy` 从任意读取器中流式计算。
+pub struct Hmac {
+    inner: Hasher,
+    // 与外层填充异或后的密钥
+    outer_key: Vec<u8>,
+}
+
+impl Hmac {
+    /// 使用指定的摘要算法和密钥创建 HMAC 对象。
+    ///
+    /// # 参数
+    ///
+    /// * `algorithm`: DigestAlgorithm - 摘要算法，例如 `DigestAlgorithm::Sha256`
+    /// * `key`: &[u8] - 密钥，长度超过算法的分组大小时先对其计算摘要
+    ///
+    /// 返回值：Hmac - 新创建的 HMAC 对象
+    pub fn new(algorithm: DigestAlgorithm, key: &[u8]) -> Self {
+        let mut block = if key.len() > algorithm.block_size() {
+            let mut hasher = Hasher::new(algorithm);
+            hasher.update(key);
+            hasher.finalize()
+        } else {
+            key.to_vec()
+        };
+        block.resize(algorithm.block_size(), 0);
+
+        let inner_key: Vec<u8> = block.iter().map(|byte| byte ^ IPAD).collect();
+        let outer_key = block.iter().map(|byte| byte ^ OPAD).collect();
+        let mut inner = Hasher::new(algorithm);
+        inner.update(&inner_key);
+        Hmac { inner, outer_key }
+    }
+
+    /// 返回使用的摘要算法。
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.inner.algorithm()
+    }
+
+    /// 追加消息数据。
+    ///
+    /// # 参数
+    ///
+    /// * `data`: &[u8] - 追加的数据
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    /// 返回此前追加的所有数据的 HMAC。
+    ///
+    /// 返回值：Vec<u8> - HMAC，长度与算法的摘要长度相同
+    pub fn finalize(self) -> Vec<u8> {
+        let algorithm = self.inner.algorithm();
+        let inner_digest = self.inner.finalize();
+        let mut outer = Hasher::new(algorithm);
+        outer.update(&self.outer_key);
+        outer.update(&inner_digest);
+        outer.finalize()
+    }
+
+    /// 以固定时间比较此前追加的所有数据的 HMAC 与给定的签名，比较耗时不会泄露签名中匹配的前缀长度。
+    ///
+    /// # 参数
+    ///
+    /// * `signature`: &[u8] - 待验证的签名
+    ///
+    /// 返回值：bool - 签名正确时返回true
+    pub fn verify(self, signature: &[u8]) -> bool {
+        fixed_time_eq(&self.finalize(), signature)
+    }
+}
+
+impl Write for Hmac {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// HKDF 提取步骤（RFC 5869）：从输入密钥材料中提取固定长度的伪随机密钥。
+///
+/// # 参数
+///
+/// * `algorithm`: DigestAlgorithm - 摘要算法
+/// * `salt`: &[u8] - 盐，可以为空，此时使用全0
+/// * `ikm`: &[u8] - 输入密钥材料，例如 ECDH 共享密钥
+///
+/// 返回值：Vec<u8> - 伪随机密钥，长度与摘要长度相同
+pub fn hkdf_extract(algorithm: DigestAlgorithm, salt: &[u8], ikm: &[u8]) -> Vec<u8> {
+    let mut hmac = Hmac::new(algorithm, salt);
+    hmac.update(ikm);
+    hmac.finalize()
+}
+
+/// HKDF 扩展步骤（RFC 5869）：将伪随机密钥扩展为指定长度的输出。
+///
+/// # 参数
+///
+/// * `algorithm`: DigestAlgorithm - 摘要算法
+/// * `prk`: &[u8] - `hkdf_extract` 的结果
+/// * `info`: &[u8] - 上下文信息，不同用途使用不同的值可以派生出互不相关的密钥
+/// * `len`: usize - 输出字节数
+///
+/// 返回值：Option<Vec<u8>> - 输出，`len` 超过摘要长度的255倍时返回None
+pub fn hkdf_expand(algorithm: DigestAlgorithm, prk: &[u8], info: &[u8], len: usize) -> Option<Vec<u8>> {
+    if len > 255 * algorithm.output_len() {
+        return None;
+    }
+    let mut okm = Vec::with_capacity(len);
+    let mut previous = Vec::new();
+    let mut counter = 1u8;
+    while okm.len() < len {
+        let mut hmac = Hmac::new(algorithm, prk);
+        hmac.update(&previous);
+        hmac.update(info);
+        hmac.update(&[counter]);
+        previous = hmac.finalize();
+        okm.extend_from_slice(&previous[..previous.len().min(len - okm.len())]);
+        counter = counter.wrapping_add(1);
+    }
+    Some(okm)
+}
+
+/// HKDF（RFC 5869），依次执行提取和扩展步骤。
+///
+/// 返回值：Option<Vec<u8>> - 派生的密钥，`len` 超过摘要长度的255倍时返回None
+pub fn hkdf(algorithm: DigestAlgorithm, ikm: &[u8], salt: &[u8], info: &[u8], len: usize) -> Option<Vec<u8>> {
+    hkdf_expand(algorithm, &hkdf_extract(algorithm, salt, ikm), info, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure::secure::SecureUtil;
+
+    #[test]
+    fn test_hmac_rfc_vectors() {
+        // RFC 2202 与 RFC 4231 的测试用例2
+        let cases = [
+            (DigestAlgorithm::Md5, "750c783e6ab0b503eaa86e310a5db738"),
+            (DigestAlgorithm::Sha1, "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"),
+            (DigestAlgorithm::Sha256, "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"),
+            (
+                DigestAlgorithm::Sha512,
+                "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            ),
+        ];
+        for (algorithm, expected) in cases {
+            let mut hmac = Hmac::new(algorithm, b"Jefe");
+            hmac.update(b"what do ya want ");
+            hmac.update(b"for nothing?");
+            assert_eq!(SecureUtil::to_hex(&hmac.finalize()), expected, "{}", algorithm);
+        }
+
+        // RFC 4231 的测试用例6：密钥比分组大小更长
+        let mut hmac = Hmac::new(DigestAlgorithm::Sha256, &[0xaa; 131]);
+        io::copy(&mut b"Test Using Larger Than Block-Size Key - Hash Key First".as_slice(), &mut hmac).unwrap();
+        assert_eq!(
+            SecureUtil::to_hex(&hmac.finalize()),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_hmac_verify() {
+        let signature = SecureUtil::hmac(DigestAlgorithm::Sha256, b"secret", b"payload");
+        let mut hmac = Hmac::new(DigestAlgorithm::Sha256, b"secret");
+        hmac.update(b"payload");
+        assert!(hmac.verify(&signature));
+
+        let mut tampered = signature.clone();
+        tampered[0] ^= 1;
+        assert!(!SecureUtil::verify_hmac(DigestAlgorithm::Sha256, b"secret", b"payload", &tampered));
+        assert!(!SecureUtil::verify_hmac(DigestAlgorithm::Sha256, b"secret", b"payload", &signature[..16]));
+        assert!(!SecureUtil::verify_hmac(DigestAlgorithm::Sha256, b"other", b"payload", &signature));
+    }
+
+    #[test]
+    fn test_hkdf_rfc_vectors() {
+        // RFC 5869 测试用例1和测试用例3
+        let ikm = [0x0b; 22];
+        let salt = SecureUtil::from_hex("000102030405060708090a0b0c").unwrap();
+        let info = SecureUtil::from_hex("f0f1f2f3f4f5f6f7f8f9").unwrap();
+        let prk = hkdf_extract(DigestAlgorithm::Sha256, &salt, &ikm);
+        assert_eq!(SecureUtil::to_hex(&prk), "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5");
+        assert_eq!(
+            SecureUtil::to_hex(&hkdf_expand(DigestAlgorithm::Sha256, &prk, &info, 42).unwrap()),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+        assert_eq!(
+            SecureUtil::to_hex(&hkdf(DigestAlgorithm::Sha256, &ikm, b"", b"", 42).unwrap()),
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"
+        );
+
+        assert_eq!(hkdf(DigestAlgorithm::Sha256, &ikm, b"", b"", 255 * 32).unwrap().len(), 255 * 32);
+        assert_eq!(hkdf(DigestAlgorithm::Sha256, &ikm, b"", b"", 255 * 32 + 1), None);
+    }
+}
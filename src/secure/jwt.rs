@@ -0,0 +1,874 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crypto::util::fixed_time_eq;
+use openssl::bn::BigNum;
+use openssl::ecdsa::EcdsaSig;
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{HasPublic, Id, PKey, PKeyRef, Private, Public};
+use openssl::sign::{Signer, Verifier};
+use serde_json::{Map, Value};
+
+use crate::date::clock::{Clock, SystemClock};
+use crate::secure::digest::DigestAlgorithm;
+use crate::secure::ec::{EcdsaPrivateKey, EcdsaPublicKey, Ed25519PrivateKey, Ed25519PublicKey};
+use crate::secure::hmac::Hmac;
+use crate::secure::rsa::{RsaPrivateKey, RsaPublicKey};
+
+/// RS256 要求的最小 RSA 密钥位数（RFC 7518 3.3）。
+const MIN_RSA_BITS: u32 = 2048;
+
+/// ES256 签名中 r 和 s 的字节数。
+const ES256_COMPONENT_LEN: usize = 32;
+
+/// 默认的时间容差。
+const DEFAULT_LEEWAY: Duration = Duration::from_secs(60);
+
+/// JWT 错误。
+#[derive(Debug)]
+pub enum JwtError {
+    /// 令牌结构、Base64 或 JSON 格式错误
+    Malformed(String),
+    /// 不支持的 `alg`
+    UnsupportedAlgorithm(String),
+    /// 令牌的算法不在允许的列表中
+    AlgorithmNotAllowed(JwtAlgorithm),
+    /// 密钥类型与算法不匹配
+    InvalidKey(String),
+    /// 签名错误
+    InvalidSignature,
+    /// 令牌已过期（`exp`）
+    Expired,
+    /// 令牌尚未生效（`nbf`）
+    NotYetValid,
+    /// 签发时间在未来，或早于允许的最大有效期（`iat`）
+    InvalidIssuedAt,
+    /// 签发者不匹配（`iss`）
+    InvalidIssuer,
+    /// 受众不匹配（`aud`）
+    InvalidAudience,
+    /// 缺少要求的声明
+    MissingClaim(String),
+    OpenSsl(ErrorStack),
+}
+
+impl fmt::Display for JwtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JwtError::Malformed(msg) => write!(f, "JWT 格式错误: {}", msg),
+            JwtError::UnsupportedAlgorithm(alg) => write!(f, "不支持的 JWT 算法: {}", alg),
+            JwtError::AlgorithmNotAllowed(alg) => write!(f, "不允许的 JWT 算法: {}", alg),
+            JwtError::InvalidKey(msg) => write!(f, "无效的 JWT 密钥: {}", msg),
+            JwtError::InvalidSignature => write!(f, "JWT 签名错误"),
+            JwtError::Expired => write!(f, "JWT 已过期"),
+            JwtError::NotYetValid => write!(f, "JWT 尚未生效"),
+            JwtError::InvalidIssuedAt => write!(f, "JWT 签发时间无效"),
+            JwtError::InvalidIssuer => write!(f, "JWT 签发者不匹配"),
+            JwtError::InvalidAudience => write!(f, "JWT 受众不匹配"),
+            JwtError::MissingClaim(name) => write!(f, "JWT 缺少声明: {}", name),
+            JwtError::OpenSsl(e) => write!(f, "OpenSSL 错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for JwtError {}
+
+impl From<ErrorStack> for JwtError {
+    fn from(e: ErrorStack) -> Self {
+        JwtError::OpenSsl(e)
+    }
+}
+
+/// JWT 签名算法（RFC 7518、RFC 8037）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JwtAlgorithm {
+    /// HMAC-SHA256
+    HS256,
+    /// HMAC-SHA384
+    HS384,
+    /// HMAC-SHA512
+    HS512,
+    /// RSASSA-PKCS1-v1_5 + SHA-256
+    RS256,
+    /// ECDSA P-256 + SHA-256
+    ES256,
+    /// Ed25519
+    EdDSA,
+}
+
+impl JwtAlgorithm {
+    /// 返回 `alg` 头部中的名称。
+    pub fn name(&self) -> &'static str {
+        match self {
+            JwtAlgorithm::HS256 => "HS256",
+            JwtAlgorithm::HS384 => "HS384",
+            JwtAlgorithm::HS512 => "HS512",
+            JwtAlgorithm::RS256 => "RS256",
+            JwtAlgorithm::ES256 => "ES256",
+            JwtAlgorithm::EdDSA => "EdDSA",
+        }
+    }
+
+    fn hmac_digest(&self) -> Option<DigestAlgorithm> {
+        match self {
+            JwtAlgorithm::HS256 => Some(DigestAlgorithm::Sha256),
+            JwtAlgorithm::HS384 => Some(DigestAlgorithm::Sha384),
+            JwtAlgorithm::HS512 => Some(DigestAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    /// 检查非对称密钥的类型是否与算法匹配。
+    fn check_key<T: HasPublic>(&self, key: &PKeyRef<T>) -> Result<(), JwtError> {
+        let ok = match self {
+            JwtAlgorithm::RS256 => key.id() == Id::RSA && key.bits() >= MIN_RSA_BITS,
+            JwtAlgorithm::ES256 => {
+                key.id() == Id::EC && key.ec_key()?.group().curve_name() == Some(Nid::X9_62_PRIME256V1)
+            }
+            JwtAlgorithm::EdDSA => key.id() == Id::ED25519,
+            _ => false,
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(JwtError::InvalidKey(format!("{} 不能使用该密钥", self)))
+        }
+    }
+}
+
+impl fmt::Display for JwtAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for JwtAlgorithm {
+    type Err = JwtError;
+
+    /// 解析 `alg` 头部，区分大小写。
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "HS256" => Ok(JwtAlgorithm::HS256),
+            "HS384" => Ok(JwtAlgorithm::HS384),
+            "HS512" => Ok(JwtAlgorithm::HS512),
+            "RS256" => Ok(JwtAlgorithm::RS256),
+            "ES256" => Ok(JwtAlgorithm::ES256),
+            "EdDSA" => Ok(JwtAlgorithm::EdDSA),
+            other => Err(JwtError::UnsupportedAlgorithm(other.to_string())),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum KeyKind {
+    Secret(Vec<u8>),
+    Private(PKey<Private>),
+    Public(PKey<Public>),
+}
+
+/// 签名或验证 JWT 使用的密钥。
+///
+/// HS 系列算法使用共享密钥；RS256、ES256 和 EdDSA 使用私钥签名、公钥验证，私钥也可以用于验证。
+#[derive(Clone)]
+pub struct JwtKey {
+    kind: KeyKind,
+}
+
+impl JwtKey {
+    /// HS256、HS384、HS512 使用的共享密钥，长度应不小于摘要长度。
+    pub fn secret(secret: &[u8]) -> Self {
+        JwtKey { kind: KeyKind::Secret(secret.to_vec()) }
+    }
+
+    /// 从 PEM 导入 RSA、P-256 或 Ed25519 私钥，支持 PKCS#8 和传统格式。
+    ///
+    /// 返回值：Result<JwtKey, JwtError> - 私钥
+    pub fn private_pem(pem: &[u8]) -> Result<Self, JwtError> {
+        Ok(JwtKey { kind: KeyKind::Private(PKey::private_key_from_pem(pem)?) })
+    }
+
+    /// 从 PEM 导入 RSA、P-256 或 Ed25519 公钥（`BEGIN PUBLIC KEY`）。
+    ///
+    /// 返回值：Result<JwtKey, JwtError> - 公钥
+    pub fn public_pem(pem: &[u8]) -> Result<Self, JwtError> {
+        Ok(JwtKey { kind: KeyKind::Public(PKey::public_key_from_pem(pem)?) })
+    }
+
+    /// 返回私钥对应的公钥，共享密钥和公钥原样返回。
+    ///
+    /// 返回值：Result<JwtKey, JwtError> - 可以公开分发的验证密钥
+    pub fn to_public(&self) -> Result<Self, JwtError> {
+        match &self.kind {
+            KeyKind::Private(key) => {
+                Ok(JwtKey { kind: KeyKind::Public(PKey::public_key_from_der(&key.public_key_to_der()?)?) })
+            }
+            _ => Ok(self.clone()),
+        }
+    }
+
+    fn from_private(key: PKey<Private>) -> Self {
+        JwtKey { kind: KeyKind::Private(key) }
+    }
+
+    fn from_public(key: PKey<Public>) -> Self {
+        JwtKey { kind: KeyKind::Public(key) }
+    }
+
+    fn sign(&self, algorithm: JwtAlgorithm, data: &[u8]) -> Result<Vec<u8>, JwtError> {
+        match (&self.kind, algorithm.hmac_digest()) {
+            (KeyKind::Secret(secret), Some(digest)) => {
+                let mut mac = Hmac::new(digest, secret);
+                mac.update(data);
+                Ok(mac.finalize())
+            }
+            (KeyKind::Private(key), None) => {
+                algorithm.check_key(key)?;
+                match algorithm {
+                    JwtAlgorithm::EdDSA => Ok(Signer::new_without_digest(key)?.sign_oneshot_to_vec(data)?),
+                    JwtAlgorithm::ES256 => {
+                        let der = Signer::new(MessageDigest::sha256(), key)?.sign_oneshot_to_vec(data)?;
+                        let signature = EcdsaSig::from_der(&der)?;
+                        let mut raw = signature.r().to_vec_padded(ES256_COMPONENT_LEN as i32)?;
+                        raw.extend(signature.s().to_vec_padded(ES256_COMPONENT_LEN as i32)?);
+                        Ok(raw)
+                    }
+                    _ => Ok(Signer::new(MessageDigest::sha256(), key)?.sign_oneshot_to_vec(data)?),
+                }
+            }
+            _ => Err(JwtError::InvalidKey(format!("{} 不能使用该密钥签名", algorithm))),
+        }
+    }
+
+    fn verify(&self, algorithm: JwtAlgorithm, data: &[u8], signature: &[u8]) -> Result<bool, JwtError> {
+        match (&self.kind, algorithm.hmac_digest()) {
+            (KeyKind::Secret(secret), Some(digest)) => {
+                let mut mac = Hmac::new(digest, secret);
+                mac.update(data);
+                Ok(fixed_time_eq(&mac.finalize(), signature))
+            }
+            (KeyKind::Private(key), None) => verify_with(key, algorithm, data, signature),
+            (KeyKind::Public(key), None) => verify_with(key, algorithm, data, signature),
+            _ => Err(JwtError::InvalidKey(format!("{} 不能使用该密钥验证", algorithm))),
+        }
+    }
+}
+
+impl From<&RsaPrivateKey> for JwtKey {
+    fn from(key: &RsaPrivateKey) -> Self {
+        JwtKey::from_private(key.pkey().clone())
+    }
+}
+
+impl From<&RsaPublicKey> for JwtKey {
+    fn from(key: &RsaPublicKey) -> Self {
+        JwtKey::from_public(key.pkey().clone())
+    }
+}
+
+impl From<&EcdsaPrivateKey> for JwtKey {
+    fn from(key: &EcdsaPrivateKey) -> Self {
+        JwtKey::from_private(key.pkey().clone())
+    }
+}
+
+impl From<&EcdsaPublicKey> for JwtKey {
+    fn from(key: &EcdsaPublicKey) -> Self {
+        JwtKey::from_public(key.pkey().clone())
+    }
+}
+
+impl From<&Ed25519PrivateKey> for JwtKey {
+    fn from(key: &Ed25519PrivateKey) -> Self {
+        JwtKey::from_private(key.pkey().clone())
+    }
+}
+
+impl From<&Ed25519PublicKey> for JwtKey {
+    fn from(key: &Ed25519PublicKey) -> Self {
+        JwtKey::from_public(key.pkey().clone())
+    }
+}
+
+impl fmt::Debug for JwtKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 不输出密钥内容
+        match &self.kind {
+            KeyKind::Secret(secret) => write!(f, "JwtKey::Secret({} bytes)", secret.len()),
+            KeyKind::Private(key) => write!(f, "JwtKey::Private({:?})", key.id()),
+            KeyKind::Public(key) => write!(f, "JwtKey::Public({:?})", key.id()),
+        }
+    }
+}
+
+fn verify_with<T: HasPublic>(key: &PKeyRef<T>, algorithm: JwtAlgorithm, data: &[u8], signature: &[u8]) -> Result<bool, JwtError> {
+    algorithm.check_key(key)?;
+    match algorithm {
+        JwtAlgorithm::EdDSA => Ok(Verifier::new_without_digest(key)?.verify_oneshot(signature, data)?),
+        JwtAlgorithm::ES256 => {
+            if signature.len() != ES256_COMPONENT_LEN * 2 {
+                return Ok(false);
+            }
+            let (r, s) = signature.split_at(ES256_COMPONENT_LEN);
+            let der = EcdsaSig::from_private_components(BigNum::from_slice(r)?, BigNum::from_slice(s)?)?.to_der()?;
+            let mut verifier = Verifier::new(MessageDigest::sha256(), key)?;
+            verifier.update(data)?;
+            // 签名数值超出范围时 OpenSSL 返回错误而不是false
+            Ok(verifier.verify(&der).unwrap_or(false))
+        }
+        _ => {
+            let mut verifier = Verifier::new(MessageDigest::sha256(), key)?;
+            verifier.update(data)?;
+            Ok(verifier.verify(signature).unwrap_or(false))
+        }
+    }
+}
+
+/// JWT 头部。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    algorithm: JwtAlgorithm,
+    token_type: Option<String>,
+    key_id: Option<String>,
+}
+
+impl Header {
+    /// 创建 `typ` 为 `JWT` 的头部。
+    pub fn new(algorithm: JwtAlgorithm) -> Self {
+        Header { algorithm, token_type: Some("JWT".to_string()), key_id: None }
+    }
+
+    /// 设置密钥标识 `kid`，验证方据此选择公钥。
+    pub fn with_key_id(mut self, key_id: &str) -> Self {
+        self.key_id = Some(key_id.to_string());
+        self
+    }
+
+    /// 返回签名算法 `alg`。
+    pub fn algorithm(&self) -> JwtAlgorithm {
+        self.algorithm
+    }
+
+    /// 返回令牌类型 `typ`。
+    pub fn token_type(&self) -> Option<&str> {
+        self.token_type.as_deref()
+    }
+
+    /// 返回密钥标识 `kid`。
+    pub fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    fn to_json(&self) -> Map<String, Value> {
+        let mut map = Map::new();
+        map.insert("alg".to_string(), self.algorithm.name().into());
+        if let Some(typ) = &self.token_type {
+            map.insert("typ".to_string(), typ.as_str().into());
+        }
+        if let Some(kid) = &self.key_id {
+            map.insert("kid".to_string(), kid.as_str().into());
+        }
+        map
+    }
+
+    fn from_json(map: &Map<String, Value>) -> Result<Self, JwtError> {
+        let string = |name: &str| match map.get(name) {
+            None => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value.clone())),
+            Some(_) => Err(JwtError::Malformed(format!("头部 {} 必须是字符串", name))),
+        };
+        let algorithm = string("alg")?.ok_or_else(|| JwtError::Malformed("头部缺少 alg".to_string()))?;
+        if map.contains_key("crit") {
+            return Err(JwtError::Malformed("不支持 crit 头部".to_string()));
+        }
+        Ok(Header { algorithm: algorithm.parse()?, token_type: string("typ")?, key_id: string("kid")? })
+    }
+}
+
+/// JWT 声明集。
+///
+/// 标准声明通过 `with_issuer` 等方法设置，其他声明通过 `with_claim` 设置，时间均为 Unix 时间戳（秒）。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Claims {
+    map: Map<String, Value>,
+}
+
+impl Claims {
+    /// 创建空的声明集。
+    pub fn new() -> Self {
+        Claims::default()
+    }
+
+    /// 设置签发者 `iss`。
+    pub fn with_issuer(self, issuer: &str) -> Self {
+        self.with_claim("iss", issuer)
+    }
+
+    /// 设置主题 `sub`。
+    pub fn with_subject(self, subject: &str) -> Self {
+        self.with_claim("sub", subject)
+    }
+
+    /// 设置受众 `aud`，只有一个受众时写为字符串，多个时写为数组。
+    pub fn with_audience(self, audience: &[&str]) -> Self {
+        match audience {
+            [single] => self.with_claim("aud", *single),
+            _ => self.with_claim("aud", audience.to_vec()),
+        }
+    }
+
+    /// 设置过期时间 `exp`。
+    pub fn with_expiration(self, timestamp: u64) -> Self {
+        self.with_claim("exp", timestamp)
+    }
+
+    /// 设置生效时间 `nbf`。
+    pub fn with_not_before(self, timestamp: u64) -> Self {
+        self.with_claim("nbf", timestamp)
+    }
+
+    /// 设置签发时间 `iat`。
+    pub fn with_issued_at(self, timestamp: u64) -> Self {
+        self.with_claim("iat", timestamp)
+    }
+
+    /// 设置令牌标识 `jti`。
+    pub fn with_jwt_id(self, id: &str) -> Self {
+        self.with_claim("jti", id)
+    }
+
+    /// 将 `iat` 设为当前时间，`exp` 设为当前时间加上 `ttl`。
+    pub fn expires_in(self, ttl: Duration) -> Self {
+        let now = SystemClock.unix_timestamp();
+        self.with_issued_at(now).with_expiration(now + ttl.as_secs())
+    }
+
+    /// 设置任意声明，同名声明会被覆盖。
+    pub fn with_claim<V: Into<Value>>(mut self, name: &str, value: V) -> Self {
+        self.map.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// 返回签发者 `iss`。
+    pub fn issuer(&self) -> Option<&str> {
+        self.map.get("iss").and_then(Value::as_str)
+    }
+
+    /// 返回主题 `sub`。
+    pub fn subject(&self) -> Option<&str> {
+        self.map.get("sub").and_then(Value::as_str)
+    }
+
+    /// 返回受众 `aud`，字符串和数组两种写法都转换为列表。
+    pub fn audience(&self) -> Vec<&str> {
+        match self.map.get("aud") {
+            Some(Value::String(aud)) => vec![aud.as_str()],
+            Some(Value::Array(auds)) => auds.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// 返回过期时间 `exp`。
+    pub fn expiration(&self) -> Option<u64> {
+        self.timestamp("exp").ok().flatten()
+    }
+
+    /// 返回生效时间 `nbf`。
+    pub fn not_before(&self) -> Option<u64> {
+        self.timestamp("nbf").ok().flatten()
+    }
+
+    /// 返回签发时间 `iat`。
+    pub fn issued_at(&self) -> Option<u64> {
+        self.timestamp("iat").ok().flatten()
+    }
+
+    /// 返回令牌标识 `jti`。
+    pub fn jwt_id(&self) -> Option<&str> {
+        self.map.get("jti").and_then(Value::as_str)
+    }
+
+    /// 返回任意声明。
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.map.get(name)
+    }
+
+    /// 返回全部声明。
+    pub fn as_map(&self) -> &Map<String, Value> {
+        &self.map
+    }
+
+    /// 读取时间声明，允许带小数部分的数字（RFC 7519 NumericDate）。
+    fn timestamp(&self, name: &str) -> Result<Option<u64>, JwtError> {
+        match self.map.get(name) {
+            None => Ok(None),
+            Some(Value::Number(n)) => match n.as_u64() {
+                Some(value) => Ok(Some(value)),
+                None => match n.as_f64() {
+                    Some(value) if value >= 0.0 => Ok(Some(value as u64)),
+                    _ => Err(JwtError::Malformed(format!("声明 {} 不是有效的时间", name))),
+                },
+            },
+            Some(_) => Err(JwtError::Malformed(format!("声明 {} 必须是数字", name))),
+        }
+    }
+}
+
+/// 验证通过的令牌。
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub header: Header,
+    pub claims: Claims,
+}
+
+/// 验证规则。
+///
+/// 默认要求令牌包含 `exp`，并检查 `exp`、`nbf` 和 `iat`，时间容差60秒；设置签发者或受众后还会检查 `iss`、`aud`。
+#[derive(Debug, Clone)]
+pub struct Validation {
+    algorithms: Vec<JwtAlgorithm>,
+    leeway: u64,
+    require_expiration: bool,
+    max_age: Option<u64>,
+    issuers: Vec<String>,
+    audiences: Vec<String>,
+    required: Vec<String>,
+    now: Option<u64>,
+}
+
+impl Validation {
+    /// 只接受 `algorithm` 签名的令牌，防止攻击者通过修改 `alg` 绕过验证。
+    pub fn new(algorithm: JwtAlgorithm) -> Self {
+        Validation {
+            algorithms: vec![algorithm],
+            leeway: DEFAULT_LEEWAY.as_secs(),
+            require_expiration: true,
+            max_age: None,
+            issuers: Vec::new(),
+            audiences: Vec::new(),
+            required: Vec::new(),
+            now: None,
+        }
+    }
+
+    /// 额外允许的算法，例如密钥轮换期间同时接受新旧算法。
+    pub fn with_algorithm(mut self, algorithm: JwtAlgorithm) -> Self {
+        if !self.algorithms.contains(&algorithm) {
+            self.algorithms.push(algorithm);
+        }
+        self
+    }
+
+    /// 设置检查 `exp`、`nbf` 和 `iat` 时允许的时钟偏差。
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway.as_secs();
+        self
+    }
+
+    /// 允许不包含 `exp` 的令牌，包含时仍然检查。
+    pub fn without_expiration(mut self) -> Self {
+        self.require_expiration = false;
+        self
+    }
+
+    /// 要求令牌包含 `iat`，并拒绝签发时间早于 `max_age` 之前的令牌。
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age.as_secs());
+        self
+    }
+
+    /// 允许的签发者，可以多次调用，令牌的 `iss` 必须是其中之一。
+    pub fn with_issuer(mut self, issuer: &str) -> Self {
+        self.issuers.push(issuer.to_string());
+        self
+    }
+
+    /// 允许的受众，可以多次调用，令牌的 `aud` 至少包含其中之一。
+    pub fn with_audience(mut self, audience: &str) -> Self {
+        self.audiences.push(audience.to_string());
+        self
+    }
+
+    /// 要求令牌包含指定声明。
+    pub fn with_required_claim(mut self, name: &str) -> Self {
+        self.required.push(name.to_string());
+        self
+    }
+
+    /// 使用固定的当前时间（Unix 时间戳，秒），用于验证历史令牌或测试。
+    pub fn with_current_time(mut self, timestamp: u64) -> Self {
+        self.now = Some(timestamp);
+        self
+    }
+
+    fn validate(&self, claims: &Claims) -> Result<(), JwtError> {
+        let now = self.now.unwrap_or_else(|| SystemClock.unix_timestamp());
+        for name in &self.required {
+            if !claims.map.contains_key(name) {
+                return Err(JwtError::MissingClaim(name.clone()));
+            }
+        }
+
+        match claims.timestamp("exp")? {
+            Some(exp) if now >= exp.saturating_add(self.leeway) => return Err(JwtError::Expired),
+            None if self.require_expiration => return Err(JwtError::MissingClaim("exp".to_string())),
+            _ => {}
+        }
+        if claims.timestamp("nbf")?.is_some_and(|nbf| nbf > now.saturating_add(self.leeway)) {
+            return Err(JwtError::NotYetValid);
+        }
+        match claims.timestamp("iat")? {
+            Some(iat) if iat > now.saturating_add(self.leeway) => return Err(JwtError::InvalidIssuedAt),
+            Some(iat) if self.max_age.is_some_and(|age| iat.saturating_add(age).saturating_add(self.leeway) < now) => {
+                return Err(JwtError::InvalidIssuedAt)
+            }
+            None if self.max_age.is_some() => return Err(JwtError::MissingClaim("iat".to_string())),
+            _ => {}
+        }
+
+        if !self.issuers.is_empty() {
+            match claims.map.get("iss") {
+                None => return Err(JwtError::MissingClaim("iss".to_string())),
+                Some(Value::String(iss)) if self.issuers.contains(iss) => {}
+                Some(_) => return Err(JwtError::InvalidIssuer),
+            }
+        }
+        if !self.audiences.is_empty() {
+            if !claims.map.contains_key("aud") {
+                return Err(JwtError::MissingClaim("aud".to_string()));
+            }
+            if !claims.audience().iter().any(|aud| self.audiences.iter().any(|expected| expected == aud)) {
+                return Err(JwtError::InvalidAudience);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 生成签名的 JWT（JWS 紧凑序列化）。
+///
+/// # 参数
+///
+/// * `header`: &Header - 头部，决定签名算法
+/// * `claims`: &Claims - 声明集
+/// * `key`: &JwtKey - HS 系列为共享密钥，其他算法为私钥
+///
+/// 返回值：Result<String, JwtError> - `头部.声明.签名` 格式的令牌
+pub fn encode(header: &Header, claims: &Claims, key: &JwtKey) -> Result<String, JwtError> {
+    let signing_input = format!(
+        "{}.{}",
+        encode_part(&Value::Object(header.to_json()))?,
+        encode_part(&Value::Object(claims.map.clone()))?
+    );
+    let signature = key.sign(header.algorithm, signing_input.as_bytes())?;
+    Ok(format!("{}.{}", signing_input, base64::encode_config(signature, base64::URL_SAFE_NO_PAD)))
+}
+
+/// 验证 JWT 的签名和声明。
+///
+/// 先检查算法是否允许并验证签名，签名正确后才检查声明，因此声明相关的错误只会对可信的令牌返回。
+///
+/// # 参数
+///
+/// * `token`: &str - 令牌
+/// * `key`: &JwtKey - HS 系列为共享密钥，其他算法为公钥或私钥
+/// * `validation`: &Validation - 验证规则
+///
+/// 返回值：Result<Token, JwtError> - 头部和声明
+pub fn decode(token: &str, key: &JwtKey, validation: &Validation) -> Result<Token, JwtError> {
+    let (header, claims) = split(token)?;
+    let algorithm = header.algorithm;
+    if !validation.algorithms.contains(&algorithm) {
+        return Err(JwtError::AlgorithmNotAllowed(algorithm));
+    }
+    let (signing_input, signature) = token.rsplit_once('.').expect("split 已经检查过分段");
+    let signature = decode_base64url(signature, "签名")?;
+    if !key.verify(algorithm, signing_input.as_bytes(), &signature)? {
+        return Err(JwtError::InvalidSignature);
+    }
+    validation.validate(&claims)?;
+    Ok(Token { header, claims })
+}
+
+/// 不验证签名，只解析头部，用于根据 `kid` 选择验证密钥。
+///
+/// 返回值：Result<Header, JwtError> - 头部，内容不可信
+pub fn decode_header(token: &str) -> Result<Header, JwtError> {
+    Ok(split(token)?.0)
+}
+
+fn split(token: &str) -> Result<(Header, Claims), JwtError> {
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err(JwtError::Malformed(format!("应为3段，实际为{}段", parts.len())));
+    }
+    let header = Header::from_json(&decode_part(parts[0], "头部")?)?;
+    let claims = Claims { map: decode_part(parts[1], "声明")? };
+    Ok((header, claims))
+}
+
+fn encode_part(value: &Value) -> Result<String, JwtError> {
+    let json = serde_json::to_vec(value).map_err(|e| JwtError::Malformed(e.to_string()))?;
+    Ok(base64::encode_config(json, base64::URL_SAFE_NO_PAD))
+}
+
+fn decode_part(part: &str, name: &str) -> Result<Map<String, Value>, JwtError> {
+    let json = decode_base64url(part, name)?;
+    match serde_json::from_slice(&json) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(JwtError::Malformed(format!("{}不是 JSON 对象", name))),
+        Err(e) => Err(JwtError::Malformed(format!("{}: {}", name, e))),
+    }
+}
+
+/// JWS 要求不带填充的 Base64url。
+fn decode_base64url(part: &str, name: &str) -> Result<Vec<u8>, JwtError> {
+    if part.contains('=') {
+        return Err(JwtError::Malformed(format!("{}包含填充字符", name)));
+    }
+    base64::decode_config(part, base64::URL_SAFE_NO_PAD).map_err(|e| JwtError::Malformed(format!("{}: {}", name, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure::ec::EcCurve;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn hs256_token(claims: &Claims) -> String {
+        encode(&Header::new(JwtAlgorithm::HS256), claims, &JwtKey::secret(b"0123456789abcdef0123456789abcdef")).unwrap()
+    }
+
+    fn check(token: &str, validation: Validation) -> Result<Token, JwtError> {
+        decode(token, &JwtKey::secret(b"0123456789abcdef0123456789abcdef"), &validation.with_current_time(NOW))
+    }
+
+    #[test]
+    fn test_rfc7515_hs256() {
+        // RFC 7515 附录 A.1
+        let token = concat!(
+            "eyJ0eXAiOiJKV1QiLA0KICJhbGciOiJIUzI1NiJ9.",
+            "eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ.",
+            "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"
+        );
+        let secret = base64::decode_config(
+            "AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow",
+            base64::URL_SAFE_NO_PAD,
+        )
+        .unwrap();
+        let key = JwtKey::secret(&secret);
+        let validation = Validation::new(JwtAlgorithm::HS256).with_issuer("joe");
+
+        let decoded = decode(token, &key, &validation.clone().with_current_time(1_300_819_000)).unwrap();
+        assert_eq!(decoded.header.algorithm(), JwtAlgorithm::HS256);
+        assert_eq!(decoded.claims.issuer(), Some("joe"));
+        assert_eq!(decoded.claims.expiration(), Some(1_300_819_380));
+        assert_eq!(decoded.claims.get("http://example.com/is_root"), Some(&Value::Bool(true)));
+
+        assert!(matches!(decode(token, &key, &validation.clone()), Err(JwtError::Expired)));
+        assert!(matches!(decode(token, &JwtKey::secret(b"other"), &validation), Err(JwtError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_sign_and_verify_all_algorithms() {
+        let keys = [
+            (JwtAlgorithm::HS256, JwtKey::secret(&[7; 32])),
+            (JwtAlgorithm::HS384, JwtKey::secret(&[7; 48])),
+            (JwtAlgorithm::HS512, JwtKey::secret(&[7; 64])),
+            (JwtAlgorithm::RS256, JwtKey::from(&RsaPrivateKey::generate(2048).unwrap())),
+            (JwtAlgorithm::ES256, JwtKey::from(&EcdsaPrivateKey::generate(EcCurve::P256).unwrap())),
+            (JwtAlgorithm::EdDSA, JwtKey::from(&Ed25519PrivateKey::generate().unwrap())),
+        ];
+        let claims = Claims::new().with_subject("42").with_claim("admin", true).expires_in(Duration::from_secs(300));
+
+        for (algorithm, key) in &keys {
+            let header = Header::new(*algorithm).with_key_id("k1");
+            let token = encode(&header, &claims, key).unwrap();
+            let public = key.to_public().unwrap();
+            let decoded = decode(&token, &public, &Validation::new(*algorithm)).unwrap();
+            assert_eq!(decoded.header, header);
+            assert_eq!(decoded.claims, claims);
+            assert_eq!(decode_header(&token).unwrap().key_id(), Some("k1"));
+
+            // 修改声明或签名
+            let (_, signature) = token.rsplit_once('.').unwrap();
+            let other = encode(&header, &claims.clone().with_subject("0"), key).unwrap();
+            let forged = format!("{}.{}", other.rsplit_once('.').unwrap().0, signature);
+            assert!(matches!(decode(&forged, &public, &Validation::new(*algorithm)), Err(JwtError::InvalidSignature)));
+            let mut tampered = token.clone();
+            let first = token.rfind('.').unwrap() + 1;
+            let replacement = if token[first..].starts_with('A') { "B" } else { "A" };
+            tampered.replace_range(first..first + 1, replacement);
+            assert!(matches!(decode(&tampered, &public, &Validation::new(*algorithm)), Err(JwtError::InvalidSignature)));
+        }
+
+        // 密钥与算法不匹配
+        let rsa = &keys[3].1;
+        assert!(matches!(encode(&Header::new(JwtAlgorithm::ES256), &claims, rsa), Err(JwtError::InvalidKey(_))));
+        assert!(matches!(encode(&Header::new(JwtAlgorithm::HS256), &claims, rsa), Err(JwtError::InvalidKey(_))));
+        let p384 = JwtKey::from(&EcdsaPrivateKey::generate(EcCurve::P384).unwrap());
+        assert!(matches!(encode(&Header::new(JwtAlgorithm::ES256), &claims, &p384), Err(JwtError::InvalidKey(_))));
+        assert!(matches!(
+            encode(&Header::new(JwtAlgorithm::RS256), &claims, &rsa.to_public().unwrap()),
+            Err(JwtError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_algorithm_checks() {
+        let token = hs256_token(&Claims::new().with_expiration(NOW + 10));
+        assert!(matches!(check(&token, Validation::new(JwtAlgorithm::RS256)), Err(JwtError::AlgorithmNotAllowed(JwtAlgorithm::HS256))));
+        assert!(check(&token, Validation::new(JwtAlgorithm::RS256).with_algorithm(JwtAlgorithm::HS256)).is_ok());
+
+        // alg 为 none 的未签名令牌
+        let payload = base64::encode_config(format!("{{\"exp\":{}}}", NOW + 10), base64::URL_SAFE_NO_PAD);
+        let unsigned = format!("eyJhbGciOiJub25lIn0.{}.", payload);
+        assert!(matches!(check(&unsigned, Validation::new(JwtAlgorithm::HS256)), Err(JwtError::UnsupportedAlgorithm(_))));
+
+        for malformed in ["", "a.b", "a.b.c.d", "!!.e30.", "e30.e30.", "W10.e30.", &format!("{}=", token)] {
+            assert!(matches!(check(malformed, Validation::new(JwtAlgorithm::HS256)), Err(JwtError::Malformed(_))), "{}", malformed);
+        }
+    }
+
+    #[test]
+    fn test_claim_validation() {
+        let hs256 = || Validation::new(JwtAlgorithm::HS256);
+
+        // exp 和时间容差
+        let token = hs256_token(&Claims::new().with_expiration(NOW - 30));
+        assert!(check(&token, hs256()).is_ok());
+        assert!(matches!(check(&token, hs256().with_leeway(Duration::ZERO)), Err(JwtError::Expired)));
+        let token = hs256_token(&Claims::new().with_subject("1"));
+        assert!(matches!(check(&token, hs256()), Err(JwtError::MissingClaim(name)) if name == "exp"));
+        assert!(check(&token, hs256().without_expiration()).is_ok());
+        let token = hs256_token(&Claims::new().with_claim("exp", "tomorrow"));
+        assert!(matches!(check(&token, hs256()), Err(JwtError::Malformed(_))));
+        let token = hs256_token(&Claims::new().with_claim("exp", NOW as f64 + 10.5));
+        assert!(check(&token, hs256()).is_ok());
+
+        // nbf 和 iat
+        let token = hs256_token(&Claims::new().with_expiration(NOW + 600).with_not_before(NOW + 120));
+        assert!(matches!(check(&token, hs256()), Err(JwtError::NotYetValid)));
+        assert!(check(&token, hs256().with_leeway(Duration::from_secs(120))).is_ok());
+        let token = hs256_token(&Claims::new().with_expiration(NOW + 600).with_issued_at(NOW + 120));
+        assert!(matches!(check(&token, hs256()), Err(JwtError::InvalidIssuedAt)));
+        let token = hs256_token(&Claims::new().with_expiration(NOW + 600).with_issued_at(NOW - 3600));
+        assert!(check(&token, hs256().with_max_age(Duration::from_secs(7200))).is_ok());
+        assert!(matches!(check(&token, hs256().with_max_age(Duration::from_secs(1800))), Err(JwtError::InvalidIssuedAt)));
+
+        // iss 和 aud
+        let claims = Claims::new().with_expiration(NOW + 600).with_issuer("auth").with_audience(&["api", "web"]);
+        let token = hs256_token(&claims);
+        assert_eq!(claims.audience(), vec!["api", "web"]);
+        assert!(check(&token, hs256().with_issuer("other").with_issuer("auth").with_audience("web")).is_ok());
+        assert!(matches!(check(&token, hs256().with_issuer("other")), Err(JwtError::InvalidIssuer)));
+        assert!(matches!(check(&token, hs256().with_audience("admin")), Err(JwtError::InvalidAudience)));
+        assert!(matches!(check(&token, hs256().with_required_claim("jti")), Err(JwtError::MissingClaim(_))));
+        let token = hs256_token(&Claims::new().with_expiration(NOW + 600).with_audience(&["api"]));
+        assert!(check(&token, hs256().with_audience("api")).is_ok());
+        assert!(matches!(check(&token, hs256().with_issuer("auth")), Err(JwtError::MissingClaim(name)) if name == "iss"));
+    }
+}
@@ -1 +1,14 @@
-pub mod secure;
\ No newline at end of file
+pub mod aes;
+mod argon2;
+pub mod digest;
+pub mod ec;
+pub mod file_cipher;
+pub mod hmac;
+pub mod jwt;
+pub mod otp;
+pub mod password;
+pub mod rsa;
+pub mod secure;
+pub mod sm2;
+mod sm3;
+pub mod sm4;
@@ -0,0 +1,559 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crypto::util::fixed_time_eq;
+use openssl::error::ErrorStack;
+use openssl::rand::rand_bytes;
+
+use crate::date::clock::{Clock, SystemClock};
+use crate::secure::digest::DigestAlgorithm;
+use crate::secure::hmac::Hmac;
+
+/// 默认的验证码位数。
+pub const DEFAULT_DIGITS: u32 = 6;
+
+/// TOTP 默认的时间步长（秒）。
+pub const DEFAULT_PERIOD: u64 = 30;
+
+/// `generate_secret` 生成的密钥字节数，与 HMAC-SHA1 的输出长度相同。
+pub const SECRET_LEN: usize = 20;
+
+/// RFC 4226 要求密钥至少128位。
+pub const MIN_SECRET_LEN: usize = 16;
+
+/// 验证时允许向前或前后查找的最大步数。
+pub const MAX_WINDOW: u64 = 100;
+
+/// RFC 4648 Base32 字母表。
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// 一次性密码错误。
+#[derive(Debug)]
+pub enum OtpError {
+    /// 密钥为空、过短或不是有效的 Base32
+    InvalidSecret(String),
+    /// 位数、摘要算法、时间步长、验证窗口或账户信息无效
+    InvalidParams(String),
+    /// OpenSSL 返回的错误
+    OpenSsl(ErrorStack),
+}
+
+impl fmt::Display for OtpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OtpError::InvalidSecret(msg) => write!(f, "无效的密钥: {}", msg),
+            OtpError::InvalidParams(msg) => write!(f, "无效的参数: {}", msg),
+            OtpError::OpenSsl(e) => write!(f, "OpenSSL 错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for OtpError {}
+
+impl From<ErrorStack> for OtpError {
+    fn from(e: ErrorStack) -> Self {
+        OtpError::OpenSsl(e)
+    }
+}
+
+/// 基于计数器的一次性密码（RFC 4226 HOTP）。
+///
+/// 默认6位、HMAC-SHA1，与常见的身份验证器应用兼容。
+#[derive(Clone)]
+pub struct Hotp {
+    secret: Vec<u8>,
+    digits: u32,
+    algorithm: DigestAlgorithm,
+}
+
+impl Hotp {
+    /// 使用原始密钥创建，密钥至少 `MIN_SECRET_LEN` 字节，在生成或验证时检查。
+    pub fn new(secret: &[u8]) -> Self {
+        Hotp { secret: secret.to_vec(), digits: DEFAULT_DIGITS, algorithm: DigestAlgorithm::Sha1 }
+    }
+
+    /// 使用 Base32 编码的密钥创建，忽略大小写、空格和末尾的填充。
+    ///
+    /// # 参数
+    ///
+    /// * `secret`: &str - Base32 编码的密钥，例如 `generate_secret` 的结果
+    ///
+    /// 返回值：Result<Hotp, OtpError> - 密钥不是有效的 Base32 时返回 `InvalidSecret`
+    pub fn from_base32(secret: &str) -> Result<Self, OtpError> {
+        let secret = base32_decode(secret).ok_or_else(|| OtpError::InvalidSecret("不是有效的 Base32".to_string()))?;
+        Ok(Hotp::new(&secret))
+    }
+
+    /// 设置验证码位数，取值6 ~ 8。
+    pub fn with_digits(mut self, digits: u32) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    /// 设置 HMAC 使用的摘要算法，支持 SHA-1、SHA-256 和 SHA-512。
+    pub fn with_algorithm(mut self, algorithm: DigestAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// 返回 Base32 编码的密钥。
+    pub fn secret_base32(&self) -> String {
+        base32_encode(&self.secret)
+    }
+
+    /// 生成指定计数器的验证码。
+    ///
+    /// # 参数
+    ///
+    /// * `counter`: u64 - 计数器
+    ///
+    /// 返回值：Result<String, OtpError> - 左侧补0的验证码
+    pub fn generate(&self, counter: u64) -> Result<String, OtpError> {
+        self.validate()?;
+        Ok(self.code(counter))
+    }
+
+    /// 在 `counter` ~ `counter + look_ahead` 范围内验证验证码。
+    ///
+    /// 验证成功后调用方应保存返回的计数器加1，下次从该值开始验证，使用过的验证码不能再次通过。
+    ///
+    /// # 参数
+    ///
+    /// * `code`: &str - 用户输入的验证码
+    /// * `counter`: u64 - 服务端保存的计数器
+    /// * `look_ahead`: u64 - 允许客户端领先的步数，不超过 `MAX_WINDOW`
+    ///
+    /// 返回值：Result<Option<u64>, OtpError> - 匹配的计数器，不匹配时返回 None
+    pub fn verify(&self, code: &str, counter: u64, look_ahead: u64) -> Result<Option<u64>, OtpError> {
+        self.validate()?;
+        check_window(look_ahead)?;
+        let last = counter.saturating_add(look_ahead);
+        Ok(self.find(code, counter..=last))
+    }
+
+    /// 生成身份验证器应用扫码使用的 `otpauth://hotp/` URI。
+    ///
+    /// # 参数
+    ///
+    /// * `issuer`: &str - 服务名称，不能包含 `:`，为空时省略
+    /// * `account`: &str - 账户名称，例如邮箱
+    /// * `counter`: u64 - 初始计数器
+    ///
+    /// 返回值：Result<String, OtpError> - URI，通常生成二维码展示给用户
+    pub fn provisioning_uri(&self, issuer: &str, account: &str, counter: u64) -> Result<String, OtpError> {
+        self.validate()?;
+        let uri = provisioning_uri("hotp", self, issuer, account)?;
+        Ok(format!("{}&counter={}", uri, counter))
+    }
+
+    fn validate(&self) -> Result<(), OtpError> {
+        if self.secret.len() < MIN_SECRET_LEN {
+            return Err(OtpError::InvalidSecret(format!("密钥长度为{}字节，至少需要{}字节", self.secret.len(), MIN_SECRET_LEN)));
+        }
+        if !(6..=8).contains(&self.digits) {
+            return Err(OtpError::InvalidParams(format!("验证码位数必须是6 ~ 8，实际为{}", self.digits)));
+        }
+        match self.algorithm {
+            DigestAlgorithm::Sha1 | DigestAlgorithm::Sha256 | DigestAlgorithm::Sha512 => Ok(()),
+            algorithm => Err(OtpError::InvalidParams(format!("不支持的摘要算法: {}", algorithm))),
+        }
+    }
+
+    /// RFC 4226 第5.3节的动态截断。
+    fn code(&self, counter: u64) -> String {
+        let mut hmac = Hmac::new(self.algorithm, &self.secret);
+        hmac.update(&counter.to_be_bytes());
+        let mac = hmac.finalize();
+        let offset = (mac[mac.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([mac[offset] & 0x7f, mac[offset + 1], mac[offset + 2], mac[offset + 3]]);
+        format!("{:0width$}", binary % 10u32.pow(self.digits), width = self.digits as usize)
+    }
+
+    /// 依次比较范围内的所有验证码，不在匹配后提前返回。
+    fn find<I: Iterator<Item = u64>>(&self, code: &str, counters: I) -> Option<u64> {
+        if code.len() != self.digits as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let mut matched = None;
+        for counter in counters {
+            if fixed_time_eq(self.code(counter).as_bytes(), code.as_bytes()) && matched.is_none() {
+                matched = Some(counter);
+            }
+        }
+        matched
+    }
+}
+
+impl fmt::Debug for Hotp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 不输出密钥
+        f.debug_struct("Hotp").field("digits", &self.digits).field("algorithm", &self.algorithm).finish()
+    }
+}
+
+/// 基于时间的一次性密码（RFC 6238 TOTP）。
+///
+/// 默认6位、HMAC-SHA1、30秒步长，验证时允许前后各1个时间步的时钟偏差。
+/// `generate` 和 `verify` 从 `with_clock` 设置的时钟读取当前时间，测试时可以传入 `ManualClock`；
+/// 带 `_at` 后缀的方法直接使用传入的 Unix 时间戳，用于验证历史记录。
+#[derive(Clone)]
+pub struct Totp {
+    hotp: Hotp,
+    period: u64,
+    window: u64,
+    clock: Arc<dyn Clock>,
+}
+
+impl Totp {
+    /// 使用原始密钥创建，密钥至少 `MIN_SECRET_LEN` 字节，在生成或验证时检查。
+    pub fn new(secret: &[u8]) -> Self {
+        Totp::from_hotp(Hotp::new(secret))
+    }
+
+    /// 使用 Base32 编码的密钥创建，忽略大小写、空格和末尾的填充。
+    ///
+    /// # 参数
+    ///
+    /// * `secret`: &str - Base32 编码的密钥，例如 `generate_secret` 的结果
+    ///
+    /// 返回值：Result<Totp, OtpError> - 密钥不是有效的 Base32 时返回 `InvalidSecret`
+    pub fn from_base32(secret: &str) -> Result<Self, OtpError> {
+        Ok(Totp::from_hotp(Hotp::from_base32(secret)?))
+    }
+
+    fn from_hotp(hotp: Hotp) -> Self {
+        Totp { hotp, period: DEFAULT_PERIOD, window: 1, clock: Arc::new(SystemClock) }
+    }
+
+    /// 设置验证码位数，取值6 ~ 8。
+    pub fn with_digits(mut self, digits: u32) -> Self {
+        self.hotp.digits = digits;
+        self
+    }
+
+    /// 设置 HMAC 使用的摘要算法，支持 SHA-1、SHA-256 和 SHA-512。
+    pub fn with_algorithm(mut self, algorithm: DigestAlgorithm) -> Self {
+        self.hotp.algorithm = algorithm;
+        self
+    }
+
+    /// 设置时间步长（秒），不能为0。
+    pub fn with_period(mut self, period: u64) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// 设置验证时允许的时钟偏差（前后各多少个时间步），0表示只接受当前时间步，不超过 `MAX_WINDOW`。
+    pub fn with_window(mut self, window: u64) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// 设置读取当前时间的时钟，默认使用系统时钟。
+    ///
+    /// # 参数
+    ///
+    /// * `clock`: Arc<dyn Clock> - 时钟
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 返回 Base32 编码的密钥。
+    pub fn secret_base32(&self) -> String {
+        self.hotp.secret_base32()
+    }
+
+    /// 返回时间戳所在的时间步。
+    pub fn time_step(&self, timestamp: u64) -> u64 {
+        timestamp / self.period.max(1)
+    }
+
+    /// 生成当前时间的验证码。
+    pub fn generate(&self) -> Result<String, OtpError> {
+        self.generate_at(self.clock.unix_timestamp())
+    }
+
+    /// 生成指定时间的验证码。
+    ///
+    /// # 参数
+    ///
+    /// * `timestamp`: u64 - Unix 时间戳（秒）
+    ///
+    /// 返回值：Result<String, OtpError> - 左侧补0的验证码
+    pub fn generate_at(&self, timestamp: u64) -> Result<String, OtpError> {
+        self.validate()?;
+        Ok(self.hotp.code(self.time_step(timestamp)))
+    }
+
+    /// 按当前时间验证验证码。
+    pub fn verify(&self, code: &str) -> Result<Option<u64>, OtpError> {
+        self.verify_at(code, self.clock.unix_timestamp())
+    }
+
+    /// 按指定时间验证验证码，接受前后 `window` 个时间步内的验证码。
+    ///
+    /// 同一个验证码在有效期内可以多次通过，调用方应保存返回的时间步，拒绝不大于上次成功时间步的验证码。
+    ///
+    /// # 参数
+    ///
+    /// * `code`: &str - 用户输入的验证码
+    /// * `timestamp`: u64 - Unix 时间戳（秒）
+    ///
+    /// 返回值：Result<Option<u64>, OtpError> - 匹配的时间步，不匹配时返回 None
+    pub fn verify_at(&self, code: &str, timestamp: u64) -> Result<Option<u64>, OtpError> {
+        self.validate()?;
+        let step = self.time_step(timestamp);
+        Ok(self.hotp.find(code, step.saturating_sub(self.window)..=step.saturating_add(self.window)))
+    }
+
+    /// 生成身份验证器应用扫码使用的 `otpauth://totp/` URI。
+    ///
+    /// # 参数
+    ///
+    /// * `issuer`: &str - 服务名称，不能包含 `:`，为空时省略
+    /// * `account`: &str - 账户名称，例如邮箱
+    ///
+    /// 返回值：Result<String, OtpError> - URI，通常生成二维码展示给用户
+    pub fn provisioning_uri(&self, issuer: &str, account: &str) -> Result<String, OtpError> {
+        self.validate()?;
+        let uri = provisioning_uri("totp", &self.hotp, issuer, account)?;
+        Ok(format!("{}&period={}", uri, self.period))
+    }
+
+    fn validate(&self) -> Result<(), OtpError> {
+        self.hotp.validate()?;
+        if self.period == 0 {
+            return Err(OtpError::InvalidParams("时间步长不能为0".to_string()));
+        }
+        check_window(self.window)
+    }
+}
+
+impl fmt::Debug for Totp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Totp").field("hotp", &self.hotp).field("period", &self.period).field("window", &self.window).finish()
+    }
+}
+
+/// 生成 `SECRET_LEN` 字节的随机密钥，返回不带填充的 Base32 字符串。
+///
+/// 返回值：Result<String, OtpError> - 32个字符的 Base32 密钥
+pub fn generate_secret() -> Result<String, OtpError> {
+    let mut secret = [0; SECRET_LEN];
+    rand_bytes(&mut secret)?;
+    Ok(base32_encode(&secret))
+}
+
+/// 使用 RFC 4648 字母表进行 Base32 编码，不带填充。
+///
+/// 返回值：String - 大写的 Base32 字符串
+pub fn base32_encode(data: &[u8]) -> String {
+    let mut output = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[(buffer >> bits) as usize & 0x1f] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 0x1f] as char);
+    }
+    output
+}
+
+/// 解码 Base32 字符串，忽略大小写、空格和末尾的 `=` 填充。
+///
+/// 返回值：Option<Vec<u8>> - 包含无效字符或长度无效时返回 None
+pub fn base32_decode(input: &str) -> Option<Vec<u8>> {
+    let input = input.trim_end_matches(|c: char| c == '=' || c.is_whitespace());
+    let mut output = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    let mut count = 0;
+    for c in input.chars().filter(|c| !c.is_whitespace()) {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u32 - 'A' as u32,
+            c @ '2'..='7' => c as u32 - '2' as u32 + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        count += 1;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    // 每8个字符编码5个字节，剩余1、3、6个字符无法组成完整的字节
+    match count % 8 {
+        1 | 3 | 6 => None,
+        _ => Some(output),
+    }
+}
+
+/// 生成 `otpauth://` URI 的公共部分，格式见 Google Authenticator 的 Key Uri Format。
+fn provisioning_uri(kind: &str, hotp: &Hotp, issuer: &str, account: &str) -> Result<String, OtpError> {
+    if account.is_empty() {
+        return Err(OtpError::InvalidParams("账户名称不能为空".to_string()));
+    }
+    if issuer.contains(':') {
+        return Err(OtpError::InvalidParams("服务名称不能包含冒号".to_string()));
+    }
+    let algorithm = match hotp.algorithm {
+        DigestAlgorithm::Sha256 => "SHA256",
+        DigestAlgorithm::Sha512 => "SHA512",
+        _ => "SHA1",
+    };
+    let mut uri = format!("otpauth://{}/", kind);
+    if !issuer.is_empty() {
+        uri.push_str(&percent_encode(issuer));
+        uri.push(':');
+    }
+    uri.push_str(&percent_encode(account));
+    uri.push_str(&format!("?secret={}", hotp.secret_base32()));
+    if !issuer.is_empty() {
+        uri.push_str(&format!("&issuer={}", percent_encode(issuer)));
+    }
+    uri.push_str(&format!("&algorithm={}&digits={}", algorithm, hotp.digits));
+    Ok(uri)
+}
+
+/// 保留 RFC 3986 的非保留字符，其余字节编码为 `%XX`。
+fn percent_encode(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => output.push(byte as char),
+            _ => output.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    output
+}
+
+fn check_window(window: u64) -> Result<(), OtpError> {
+    if window > MAX_WINDOW {
+        return Err(OtpError::InvalidParams(format!("验证窗口不能超过{}", MAX_WINDOW)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+    use crate::date::clock::ManualClock;
+
+    const SHA1_SECRET: &[u8] = b"12345678901234567890";
+    const SHA256_SECRET: &[u8] = b"12345678901234567890123456789012";
+    const SHA512_SECRET: &[u8] = b"1234567890123456789012345678901234567890123456789012345678901234";
+
+    #[test]
+    fn test_hotp_rfc4226() {
+        // RFC 4226 附录D
+        let expected = ["755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871", "520489"];
+        let hotp = Hotp::new(SHA1_SECRET);
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(hotp.generate(counter as u64).unwrap(), *code);
+        }
+
+        assert_eq!(hotp.verify("359152", 0, 5).unwrap(), Some(2));
+        assert_eq!(hotp.verify("359152", 3, 5).unwrap(), None);
+        assert_eq!(hotp.verify("520489", 0, 5).unwrap(), None);
+        assert_eq!(hotp.verify("35915", 0, 5).unwrap(), None);
+        assert_eq!(hotp.verify("+59152", 0, 5).unwrap(), None);
+    }
+
+    #[test]
+    fn test_totp_rfc6238() {
+        // RFC 6238 附录B
+        let vectors: [(u64, &str, &str, &str); 6] = [
+            (59, "94287082", "46119246", "90693936"),
+            (1111111109, "07081804", "68084774", "25091201"),
+            (1111111111, "14050471", "67062674", "99943326"),
+            (1234567890, "89005924", "91819424", "93441116"),
+            (2000000000, "69279037", "90698825", "38618901"),
+            (20000000000, "65353130", "77737706", "47863826"),
+        ];
+        let sha1 = Totp::new(SHA1_SECRET).with_digits(8);
+        let sha256 = Totp::new(SHA256_SECRET).with_digits(8).with_algorithm(DigestAlgorithm::Sha256);
+        let sha512 = Totp::new(SHA512_SECRET).with_digits(8).with_algorithm(DigestAlgorithm::Sha512);
+        for (time, code1, code256, code512) in vectors {
+            assert_eq!(sha1.generate_at(time).unwrap(), code1);
+            assert_eq!(sha256.generate_at(time).unwrap(), code256);
+            assert_eq!(sha512.generate_at(time).unwrap(), code512);
+        }
+    }
+
+    #[test]
+    fn test_totp_window() {
+        let totp = Totp::new(SHA1_SECRET).with_digits(8);
+        // 1111111109 位于时间步 37037036
+        assert_eq!(totp.verify_at("07081804", 1111111109).unwrap(), Some(37037036));
+        assert_eq!(totp.verify_at("07081804", 1111111109 + 30).unwrap(), Some(37037036));
+        assert_eq!(totp.verify_at("07081804", 1111111109 - 30).unwrap(), Some(37037036));
+        assert_eq!(totp.verify_at("07081804", 1111111109 + 60).unwrap(), None);
+        assert_eq!(totp.clone().with_window(0).verify_at("07081804", 1111111109 + 30).unwrap(), None);
+        assert_eq!(totp.clone().with_window(2).verify_at("07081804", 1111111109 + 60).unwrap(), Some(37037036));
+
+        let current = Totp::new(SHA1_SECRET);
+        assert!(current.verify(&current.generate().unwrap()).unwrap().is_some());
+
+        // 注入的时钟
+        let clock = ManualClock::starting_at(UNIX_EPOCH + Duration::from_secs(59));
+        let totp = Totp::new(SHA1_SECRET).with_digits(8).with_clock(Arc::new(clock.clone()));
+        assert_eq!(totp.generate().unwrap(), "94287082");
+        assert_eq!(totp.verify("94287082").unwrap(), Some(1));
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(totp.verify("94287082").unwrap(), None);
+        assert_eq!(Totp::new(SHA1_SECRET).with_period(60).time_step(119), 1);
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        assert!(matches!(Hotp::new(b"short").generate(0), Err(OtpError::InvalidSecret(_))));
+        assert!(matches!(Hotp::from_base32("not base32!"), Err(OtpError::InvalidSecret(_))));
+        assert!(matches!(Hotp::new(SHA1_SECRET).with_digits(5).generate(0), Err(OtpError::InvalidParams(_))));
+        assert!(matches!(Hotp::new(SHA1_SECRET).with_digits(9).generate(0), Err(OtpError::InvalidParams(_))));
+        assert!(matches!(Hotp::new(SHA1_SECRET).with_algorithm(DigestAlgorithm::Md5).generate(0), Err(OtpError::InvalidParams(_))));
+        assert!(matches!(Hotp::new(SHA1_SECRET).verify("755224", 0, MAX_WINDOW + 1), Err(OtpError::InvalidParams(_))));
+        assert!(matches!(Totp::new(SHA1_SECRET).with_period(0).generate_at(0), Err(OtpError::InvalidParams(_))));
+        assert!(matches!(Totp::new(SHA1_SECRET).provisioning_uri("a:b", "alice"), Err(OtpError::InvalidParams(_))));
+    }
+
+    #[test]
+    fn test_base32() {
+        // RFC 4648 第10节，不带填充
+        let vectors = [("", ""), ("f", "MY"), ("fo", "MZXQ"), ("foo", "MZXW6"), ("foob", "MZXW6YQ"), ("fooba", "MZXW6YTB"), ("foobar", "MZXW6YTBOI")];
+        for (plain, encoded) in vectors {
+            assert_eq!(base32_encode(plain.as_bytes()), encoded);
+            assert_eq!(base32_decode(encoded).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(base32_decode("mzxw 6ytb oi======").unwrap(), b"foobar");
+        assert!(base32_decode("MZXW6YTB1").is_none());
+        assert!(base32_decode("MZX").is_none());
+
+        let secret = generate_secret().unwrap();
+        assert_eq!(secret.len(), 32);
+        assert_eq!(Totp::from_base32(&secret).unwrap().secret_base32(), secret);
+    }
+
+    #[test]
+    fn test_provisioning_uri() {
+        let totp = Totp::new(SHA1_SECRET);
+        assert_eq!(
+            totp.provisioning_uri("Example Co", "alice@example.com").unwrap(),
+            "otpauth://totp/Example%20Co:alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=Example%20Co&algorithm=SHA1&digits=6&period=30"
+        );
+        let hotp = Hotp::new(SHA256_SECRET).with_algorithm(DigestAlgorithm::Sha256).with_digits(8);
+        assert_eq!(
+            hotp.provisioning_uri("", "bob", 7).unwrap(),
+            "otpauth://hotp/bob?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZA&algorithm=SHA256&digits=8&counter=7"
+        );
+    }
+}
@@ -0,0 +1,550 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crypto::bcrypt::bcrypt;
+use crypto::hmac::Hmac;
+use crypto::pbkdf2::pbkdf2;
+use crypto::scrypt::{scrypt, ScryptParams};
+use crypto::sha2::Sha256;
+use crypto::util::fixed_time_eq;
+use openssl::error::ErrorStack;
+use openssl::rand::rand_bytes;
+
+use crate::secure::argon2::{self, argon2id, Params};
+
+/// 随机盐的字节数。
+pub const SALT_LEN: usize = 16;
+
+/// Argon2id、scrypt 和 PBKDF2 输出的哈希字节数。
+const HASH_LEN: usize = 32;
+
+/// bcrypt 输出的哈希字节数。
+const BCRYPT_HASH_LEN: usize = 23;
+
+/// bcrypt 只使用密码的前72字节（包括末尾的0）。
+const BCRYPT_MAX_PASSWORD_LEN: usize = 72;
+
+/// Argon2id 和 scrypt 允许使用的最大内存（4GiB），哈希字符串中的参数来自外部，不能无限制地分配内存。
+const MAX_MEMORY_KIB: u64 = 4 << 20;
+
+/// Argon2id 和 scrypt 允许的最大并行度。
+const MAX_PARALLELISM: u32 = 64;
+
+/// 密码哈希错误。
+#[derive(Debug)]
+pub enum PasswordError {
+    /// 成本参数超出算法允许的范围
+    InvalidParams(String),
+    /// 哈希字符串格式错误或使用了不支持的算法
+    InvalidHash(String),
+    /// 生成随机盐失败
+    OpenSsl(ErrorStack),
+}
+
+impl fmt::Display for PasswordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasswordError::InvalidParams(msg) => write!(f, "无效的密码哈希参数: {}", msg),
+            PasswordError::InvalidHash(msg) => write!(f, "无效的密码哈希: {}", msg),
+            PasswordError::OpenSsl(e) => write!(f, "OpenSSL 错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PasswordError {}
+
+impl From<ErrorStack> for PasswordError {
+    fn from(e: ErrorStack) -> Self {
+        PasswordError::OpenSsl(e)
+    }
+}
+
+/// 密码哈希算法及其成本参数。
+///
+/// 成本参数越大，计算一次哈希越慢，暴力破解的代价也越高。`argon2id()` 等构造函数返回 OWASP 推荐的参数，
+/// 应根据服务器性能调整到单次哈希耗时几百毫秒。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PasswordAlgorithm {
+    /// Argon2id v1.3，首选算法
+    Argon2id {
+        /// 内存大小，单位KiB，不小于 8 * parallelism
+        memory_kib: u32,
+        /// 迭代次数
+        iterations: u32,
+        /// 并行度（通道数），所有通道在当前线程中依次计算
+        parallelism: u32,
+    },
+    /// bcrypt，只使用密码的前72字节
+    Bcrypt {
+        /// 成本因子，取值4~31，每加1耗时翻倍
+        cost: u32,
+    },
+    /// scrypt，内存占用为 128 * r * 2^log_n 字节
+    Scrypt {
+        /// N 的以2为底的对数
+        log_n: u8,
+        /// 块大小
+        r: u32,
+        /// 并行度
+        p: u32,
+    },
+    /// PBKDF2-HMAC-SHA256，仅在必须符合 FIPS 时使用
+    Pbkdf2Sha256 {
+        /// 迭代次数
+        iterations: u32,
+    },
+}
+
+impl PasswordAlgorithm {
+    /// 推荐参数的 Argon2id：19MiB 内存，2次迭代，并行度1。
+    pub const fn argon2id() -> Self {
+        PasswordAlgorithm::Argon2id { memory_kib: 19 * 1024, iterations: 2, parallelism: 1 }
+    }
+
+    /// 推荐参数的 bcrypt：成本因子12。
+    pub const fn bcrypt() -> Self {
+        PasswordAlgorithm::Bcrypt { cost: 12 }
+    }
+
+    /// 推荐参数的 scrypt：N = 2^17，r = 8，p = 1。
+    pub const fn scrypt() -> Self {
+        PasswordAlgorithm::Scrypt { log_n: 17, r: 8, p: 1 }
+    }
+
+    /// 推荐参数的 PBKDF2-HMAC-SHA256：600000次迭代。
+    pub const fn pbkdf2_sha256() -> Self {
+        PasswordAlgorithm::Pbkdf2Sha256 { iterations: 600_000 }
+    }
+
+    /// 从哈希字符串中解析算法和参数。
+    ///
+    /// # 参数
+    ///
+    /// * `hash`: &str - `hash_password` 生成的哈希字符串
+    ///
+    /// 返回值：Result<PasswordAlgorithm, PasswordError> - 算法和参数
+    pub fn from_hash(hash: &str) -> Result<Self, PasswordError> {
+        Ok(PasswordHash::parse(hash)?.algorithm)
+    }
+
+    /// 返回哈希字符串中的算法标识，例如 `argon2id`。
+    pub fn name(&self) -> &'static str {
+        match self {
+            PasswordAlgorithm::Argon2id { .. } => "argon2id",
+            PasswordAlgorithm::Bcrypt { .. } => "2b",
+            PasswordAlgorithm::Scrypt { .. } => "scrypt",
+            PasswordAlgorithm::Pbkdf2Sha256 { .. } => "pbkdf2-sha256",
+        }
+    }
+
+    fn validate(&self) -> Result<(), PasswordError> {
+        let invalid = |msg: String| Err(PasswordError::InvalidParams(msg));
+        match *self {
+            PasswordAlgorithm::Argon2id { memory_kib, iterations, parallelism } => {
+                if !(1..=MAX_PARALLELISM).contains(&parallelism) {
+                    return invalid(format!("Argon2id 并行度必须在1~{}之间，实际为{}", MAX_PARALLELISM, parallelism));
+                }
+                if iterations == 0 {
+                    return invalid("Argon2id 迭代次数不能为0".to_string());
+                }
+                if (memory_kib as u64) < 8 * parallelism as u64 {
+                    return invalid(format!("Argon2id 内存不能小于 8 * 并行度，实际为{}KiB", memory_kib));
+                }
+                if memory_kib as u64 > MAX_MEMORY_KIB {
+                    return invalid(format!("Argon2id 需要{}KiB内存，最大为{}KiB", memory_kib, MAX_MEMORY_KIB));
+                }
+            }
+            PasswordAlgorithm::Bcrypt { cost } => {
+                if !(4..=31).contains(&cost) {
+                    return invalid(format!("bcrypt 成本因子必须在4~31之间，实际为{}", cost));
+                }
+            }
+            PasswordAlgorithm::Scrypt { log_n, r, p } => {
+                // 与 ScryptParams::new 中的断言一致，避免构造参数时 panic
+                let r128 = (r as u64) * 128;
+                let fits = (log_n as u32) < usize::BITS
+                    && r128.checked_mul(1u64 << log_n.min(63)).is_some_and(|bytes| bytes <= usize::MAX as u64)
+                    && r128.checked_mul(p as u64).is_some_and(|bytes| bytes <= usize::MAX as u64);
+                if r == 0 || p == 0 || log_n == 0 || !fits || log_n as u64 >= r as u64 * 16 {
+                    return invalid(format!("scrypt 参数无效: log_n={}, r={}, p={}", log_n, r, p));
+                }
+                if p > MAX_PARALLELISM || p as u64 > (u32::MAX as u64 * 32) / r128 {
+                    return invalid(format!("scrypt 并行度过大: p={}", p));
+                }
+                // 128 * r * N 字节，上面已经检查过不会溢出
+                let memory_kib = (r128 << log_n) / 1024;
+                if memory_kib > MAX_MEMORY_KIB {
+                    return invalid(format!("scrypt 需要{}KiB内存，最大为{}KiB", memory_kib, MAX_MEMORY_KIB));
+                }
+            }
+            PasswordAlgorithm::Pbkdf2Sha256 { iterations } => {
+                if iterations == 0 {
+                    return invalid("PBKDF2 迭代次数不能为0".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 从密码派生指定长度的密钥，bcrypt 的输出长度固定，不能用于派生密钥。
+    pub(crate) fn derive_key(&self, password: &[u8], salt: &[u8], len: usize) -> Result<Vec<u8>, PasswordError> {
+        if let PasswordAlgorithm::Bcrypt { .. } = self {
+            return Err(PasswordError::InvalidParams("bcrypt 不能用于派生密钥".to_string()));
+        }
+        self.validate()?;
+        Ok(self.compute(password, salt, len))
+    }
+
+    /// 计算原始哈希，调用前需要先验证参数。
+    fn compute(&self, password: &[u8], salt: &[u8], len: usize) -> Vec<u8> {
+        let mut out = vec![0; len];
+        match *self {
+            PasswordAlgorithm::Argon2id { memory_kib, iterations, parallelism } => {
+                let params = Params {
+                    memory: memory_kib,
+                    passes: iterations,
+                    lanes: parallelism,
+                    tag_len: len,
+                    secret: &[],
+                    associated: &[],
+                };
+                out = argon2id(password, salt, &params);
+            }
+            PasswordAlgorithm::Bcrypt { cost } => {
+                let mut input: Vec<u8> = password.iter().copied().take_while(|byte| *byte != 0).collect();
+                input.push(0);
+                input.truncate(BCRYPT_MAX_PASSWORD_LEN);
+                let mut output = [0; BCRYPT_HASH_LEN + 1];
+                bcrypt(cost, salt, &input, &mut output);
+                out.copy_from_slice(&output[..len]);
+            }
+            PasswordAlgorithm::Scrypt { log_n, r, p } => {
+                scrypt(password, salt, &ScryptParams::new(log_n, r, p), &mut out);
+            }
+            PasswordAlgorithm::Pbkdf2Sha256 { iterations } => {
+                let mut mac = Hmac::new(Sha256::new(), password);
+                pbkdf2(&mut mac, salt, iterations, &mut out);
+            }
+        }
+        out
+    }
+}
+
+impl Default for PasswordAlgorithm {
+    fn default() -> Self {
+        PasswordAlgorithm::argon2id()
+    }
+}
+
+/// 解析后的哈希字符串。
+struct PasswordHash {
+    algorithm: PasswordAlgorithm,
+    salt: Vec<u8>,
+    hash: Vec<u8>,
+}
+
+impl PasswordHash {
+    fn parse(input: &str) -> Result<Self, PasswordError> {
+        let invalid = |msg: &str| PasswordError::InvalidHash(msg.to_string());
+        let fields: Vec<&str> = input.split('$').collect();
+        if fields.len() < 4 || !fields[0].is_empty() {
+            return Err(invalid("不是 PHC 或 bcrypt 格式"));
+        }
+
+        let parsed = match fields[1] {
+            "2a" | "2b" | "2y" => {
+                if fields.len() != 4 || fields[2].len() != 2 || fields[3].len() != 53 || !fields[3].is_ascii() {
+                    return Err(invalid("bcrypt 哈希格式错误"));
+                }
+                let cost = fields[2].parse().map_err(|_| invalid("bcrypt 成本因子格式错误"))?;
+                let (salt, hash) = fields[3].split_at(22);
+                PasswordHash {
+                    algorithm: PasswordAlgorithm::Bcrypt { cost },
+                    salt: base64::decode_config(salt, base64::BCRYPT).map_err(|_| invalid("bcrypt 盐格式错误"))?,
+                    hash: base64::decode_config(hash, base64::BCRYPT).map_err(|_| invalid("bcrypt 哈希格式错误"))?,
+                }
+            }
+            "argon2id" => {
+                if fields.len() != 6 || fields[2] != format!("v={}", argon2::VERSION) {
+                    return Err(invalid("只支持 v=19 的 Argon2id 哈希"));
+                }
+                let params = parse_params(fields[3])?;
+                PasswordHash {
+                    algorithm: PasswordAlgorithm::Argon2id {
+                        memory_kib: param(&params, "m")?,
+                        iterations: param(&params, "t")?,
+                        parallelism: param(&params, "p")?,
+                    },
+                    salt: decode_b64(fields[4])?,
+                    hash: decode_b64(fields[5])?,
+                }
+            }
+            "scrypt" => {
+                if fields.len() != 5 {
+                    return Err(invalid("scrypt 哈希格式错误"));
+                }
+                let params = parse_params(fields[2])?;
+                PasswordHash {
+                    algorithm: PasswordAlgorithm::Scrypt {
+                        log_n: param(&params, "ln")?,
+                        r: param(&params, "r")?,
+                        p: param(&params, "p")?,
+                    },
+                    salt: decode_b64(fields[3])?,
+                    hash: decode_b64(fields[4])?,
+                }
+            }
+            "pbkdf2-sha256" => {
+                if fields.len() != 5 {
+                    return Err(invalid("PBKDF2 哈希格式错误"));
+                }
+                let params = parse_params(fields[2])?;
+                let hash = decode_b64(fields[4])?;
+                if params.get("l").is_some_and(|len| *len != hash.len().to_string()) {
+                    return Err(invalid("PBKDF2 哈希长度与参数 l 不一致"));
+                }
+                PasswordHash {
+                    algorithm: PasswordAlgorithm::Pbkdf2Sha256 { iterations: param(&params, "i")? },
+                    salt: decode_b64(fields[3])?,
+                    hash,
+                }
+            }
+            other => return Err(PasswordError::InvalidHash(format!("不支持的算法: {}", other))),
+        };
+
+        let bcrypt = matches!(parsed.algorithm, PasswordAlgorithm::Bcrypt { .. });
+        let salt_ok = if bcrypt { parsed.salt.len() == SALT_LEN } else { (8..=64).contains(&parsed.salt.len()) };
+        let hash_ok = if bcrypt { parsed.hash.len() == BCRYPT_HASH_LEN } else { (16..=64).contains(&parsed.hash.len()) };
+        if !salt_ok || !hash_ok {
+            return Err(invalid("盐或哈希的长度不正确"));
+        }
+        parsed.algorithm.validate().map_err(|e| PasswordError::InvalidHash(e.to_string()))?;
+        Ok(parsed)
+    }
+
+    fn format(&self) -> String {
+        let salt = base64::encode_config(&self.salt, base64::STANDARD_NO_PAD);
+        let hash = base64::encode_config(&self.hash, base64::STANDARD_NO_PAD);
+        match self.algorithm {
+            PasswordAlgorithm::Argon2id { memory_kib, iterations, parallelism } => format!(
+                "$argon2id$v={}$m={},t={},p={}${}${}",
+                argon2::VERSION,
+                memory_kib,
+                iterations,
+                parallelism,
+                salt,
+                hash
+            ),
+            PasswordAlgorithm::Bcrypt { cost } => format!(
+                "$2b${:02}${}{}",
+                cost,
+                base64::encode_config(&self.salt, base64::BCRYPT),
+                base64::encode_config(&self.hash, base64::BCRYPT)
+            ),
+            PasswordAlgorithm::Scrypt { log_n, r, p } => format!("$scrypt$ln={},r={},p={}${}${}", log_n, r, p, salt, hash),
+            PasswordAlgorithm::Pbkdf2Sha256 { iterations } => {
+                format!("$pbkdf2-sha256$i={},l={}${}${}", iterations, self.hash.len(), salt, hash)
+            }
+        }
+    }
+}
+
+fn parse_params(input: &str) -> Result<HashMap<&str, &str>, PasswordError> {
+    input
+        .split(',')
+        .map(|pair| pair.split_once('=').ok_or_else(|| PasswordError::InvalidHash(format!("参数格式错误: {}", pair))))
+        .collect()
+}
+
+fn param<T: std::str::FromStr>(params: &HashMap<&str, &str>, key: &str) -> Result<T, PasswordError> {
+    params
+        .get(key)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| PasswordError::InvalidHash(format!("缺少参数 {} 或格式错误", key)))
+}
+
+fn decode_b64(input: &str) -> Result<Vec<u8>, PasswordError> {
+    base64::decode_config(input, base64::STANDARD_NO_PAD).map_err(|e| PasswordError::InvalidHash(e.to_string()))
+}
+
+/// 使用密码学安全的随机数生成器生成盐。
+///
+/// # 参数
+///
+/// * `len`: usize - 盐的字节数，通常为 `SALT_LEN`
+///
+/// 返回值：Result<Vec<u8>, PasswordError> - 随机盐
+pub fn generate_salt(len: usize) -> Result<Vec<u8>, PasswordError> {
+    let mut salt = vec![0; len];
+    rand_bytes(&mut salt)?;
+    Ok(salt)
+}
+
+/// 使用随机盐计算密码哈希。
+///
+/// bcrypt 生成 `$2b$` 格式的字符串，其他算法生成 PHC 格式的字符串，例如
+/// `$argon2id$v=19$m=19456,t=2,p=1$<盐>$<哈希>`，可以直接保存到数据库中。
+///
+/// # 参数
+///
+/// * `password`: &[u8] - 密码
+/// * `algorithm`: PasswordAlgorithm - 算法和成本参数
+///
+/// 返回值：Result<String, PasswordError> - 哈希字符串，参数无效时返回 `InvalidParams`
+pub fn hash_password(password: &[u8], algorithm: PasswordAlgorithm) -> Result<String, PasswordError> {
+    algorithm.validate()?;
+    let salt = generate_salt(SALT_LEN)?;
+    let len = if matches!(algorithm, PasswordAlgorithm::Bcrypt { .. }) { BCRYPT_HASH_LEN } else { HASH_LEN };
+    let hash = algorithm.compute(password, &salt, len);
+    Ok(PasswordHash { algorithm, salt, hash }.format())
+}
+
+/// 验证密码是否与哈希字符串匹配，比较以固定时间进行。
+///
+/// # 参数
+///
+/// * `password`: &[u8] - 用户输入的密码
+/// * `hash`: &str - 保存的哈希字符串，算法和参数从中解析
+///
+/// 返回值：Result<bool, PasswordError> - 密码正确时返回true，哈希字符串格式错误时返回 `InvalidHash`
+pub fn verify_password(password: &[u8], hash: &str) -> Result<bool, PasswordError> {
+    let parsed = PasswordHash::parse(hash)?;
+    let actual = parsed.algorithm.compute(password, &parsed.salt, parsed.hash.len());
+    Ok(fixed_time_eq(&actual, &parsed.hash))
+}
+
+/// 判断哈希是否需要使用新的算法或参数重新计算。
+///
+/// 通常在用户登录、`verify_password` 验证通过后调用，此时可以用明文密码计算新的哈希并替换旧值。
+///
+/// # 参数
+///
+/// * `hash`: &str - 保存的哈希字符串
+/// * `algorithm`: PasswordAlgorithm - 当前要求的算法和参数
+///
+/// 返回值：Result<bool, PasswordError> - 算法或任一参数与要求不同时返回true
+pub fn needs_rehash(hash: &str, algorithm: PasswordAlgorithm) -> Result<bool, PasswordError> {
+    let parsed = PasswordHash::parse(hash)?;
+    let expected_len = if matches!(algorithm, PasswordAlgorithm::Bcrypt { .. }) { BCRYPT_HASH_LEN } else { HASH_LEN };
+    Ok(parsed.algorithm != algorithm || parsed.hash.len() != expected_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure::secure::SecureUtil;
+
+    /// 测试使用的低成本参数。
+    const FAST: [PasswordAlgorithm; 4] = [
+        PasswordAlgorithm::Argon2id { memory_kib: 64, iterations: 1, parallelism: 2 },
+        PasswordAlgorithm::Bcrypt { cost: 4 },
+        PasswordAlgorithm::Scrypt { log_n: 4, r: 8, p: 1 },
+        PasswordAlgorithm::Pbkdf2Sha256 { iterations: 10 },
+    ];
+
+    #[test]
+    fn test_known_hashes() {
+        // crypt_blowfish 的测试向量
+        assert!(verify_password(b"U*U", "$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW").unwrap());
+        assert!(verify_password(b"U*U*", "$2a$05$CCCCCCCCCCCCCCCCCCCCC.VGOzA784oUp/Z0DY336zx7pLYAy0lwK").unwrap());
+        assert!(!verify_password(b"U*U", "$2a$05$CCCCCCCCCCCCCCCCCCCCC.VGOzA784oUp/Z0DY336zx7pLYAy0lwK").unwrap());
+
+        // RFC 7914 第11节和第12节
+        let pbkdf2 = PasswordAlgorithm::Pbkdf2Sha256 { iterations: 1 }.compute(b"passwd", b"salt", 64);
+        assert_eq!(
+            SecureUtil::to_hex(&pbkdf2),
+            concat!(
+                "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc",
+                "49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+            )
+        );
+        let scrypt = PasswordAlgorithm::Scrypt { log_n: 4, r: 1, p: 1 }.compute(b"", b"", 64);
+        assert_eq!(
+            SecureUtil::to_hex(&scrypt),
+            concat!(
+                "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442",
+                "fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"
+            )
+        );
+
+        // 其他实现生成的 PHC 字符串
+        for hash in [
+            "$argon2id$v=19$m=64,t=2,p=1$c29tZXNhbHRzb21lc2FsdA$Gpj7qOY5RCXJvcMzqcdQqvgR3wcPX7SleI4c9NtXk6E",
+            "$scrypt$ln=10,r=8,p=1$c29tZXNhbHRzb21lc2FsdA$dj05BT7oUTq35qmxXqG/pksYG8IJr8uxtvAzbfGjoic",
+            "$pbkdf2-sha256$i=1000,l=32$c29tZXNhbHRzb21lc2FsdA$s5LQUeAEZUMuFVrnmF3OMNPXs3QWnF8SO/5BXmCj6QQ",
+        ] {
+            assert!(verify_password(b"password", hash).unwrap(), "{}", hash);
+            assert!(!verify_password(b"Password", hash).unwrap(), "{}", hash);
+        }
+    }
+
+    #[test]
+    fn test_hash_and_verify() {
+        for algorithm in FAST {
+            let hash = hash_password("pässwörd".as_bytes(), algorithm).unwrap();
+            assert!(hash.starts_with(&format!("${}$", algorithm.name())), "{}", hash);
+            assert!(verify_password("pässwörd".as_bytes(), &hash).unwrap(), "{}", hash);
+            assert!(!verify_password(b"password", &hash).unwrap(), "{}", hash);
+            assert_eq!(PasswordAlgorithm::from_hash(&hash).unwrap(), algorithm);
+
+            // 每次使用不同的随机盐
+            assert_ne!(hash_password(b"same", algorithm).unwrap(), hash_password(b"same", algorithm).unwrap());
+        }
+
+        // bcrypt 只使用前72字节
+        let bcrypt = PasswordAlgorithm::Bcrypt { cost: 4 };
+        let hash = hash_password(&[b'a'; 80], bcrypt).unwrap();
+        assert!(verify_password(&[b'a'; 72], &hash).unwrap());
+        assert_eq!(hash.len(), 60);
+    }
+
+    #[test]
+    fn test_needs_rehash() {
+        let [argon2, bcrypt, ..] = FAST;
+        let hash = hash_password(b"password", bcrypt).unwrap();
+        assert!(!needs_rehash(&hash, bcrypt).unwrap());
+        assert!(needs_rehash(&hash, PasswordAlgorithm::Bcrypt { cost: 5 }).unwrap());
+        assert!(needs_rehash(&hash, argon2).unwrap());
+
+        let hash = hash_password(b"password", argon2).unwrap();
+        assert!(!needs_rehash(&hash, argon2).unwrap());
+        assert!(needs_rehash(&hash, PasswordAlgorithm::argon2id()).unwrap());
+        assert_eq!(PasswordAlgorithm::default(), PasswordAlgorithm::argon2id());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        for algorithm in [
+            PasswordAlgorithm::Argon2id { memory_kib: 8, iterations: 1, parallelism: 2 },
+            PasswordAlgorithm::Argon2id { memory_kib: 64, iterations: 0, parallelism: 1 },
+            PasswordAlgorithm::Bcrypt { cost: 3 },
+            PasswordAlgorithm::Scrypt { log_n: 16, r: 1, p: 1 },
+            PasswordAlgorithm::Scrypt { log_n: 0, r: 8, p: 1 },
+            PasswordAlgorithm::Pbkdf2Sha256 { iterations: 0 },
+            PasswordAlgorithm::Argon2id { memory_kib: u32::MAX, iterations: 1, parallelism: 1 },
+            PasswordAlgorithm::Argon2id { memory_kib: 1024, iterations: 1, parallelism: 65 },
+            PasswordAlgorithm::Scrypt { log_n: 40, r: 8, p: 1 },
+            PasswordAlgorithm::Scrypt { log_n: 10, r: 8, p: 65 },
+        ] {
+            assert!(matches!(hash_password(b"password", algorithm), Err(PasswordError::InvalidParams(_))), "{:?}", algorithm);
+        }
+
+        for hash in [
+            "",
+            "5f4dcc3b5aa765d61d8327deb882cf99",
+            "$md5$abc$def",
+            "$argon2id$v=16$m=64,t=1,p=1$c29tZXNhbHQ$aGFzaGhhc2hoYXNoaGFzaA",
+            "$argon2id$v=19$m=64,t=1$c29tZXNhbHRzb21lc2FsdA$Gpj7qOY5RCXJvcMzqcdQqvgR3wcPX7SleI4c9NtXk6E",
+            "$scrypt$ln=10,r=8,p=1$c29tZXNhbHRzb21lc2FsdA$!!!",
+            "$2b$04$tooshort",
+            "$2b$99$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW",
+            // 参数过大的哈希在计算之前就被拒绝，不会尝试分配内存
+            "$argon2id$v=19$m=4294967295,t=1,p=1$c29tZXNhbHRzb21lc2FsdA$Gpj7qOY5RCXJvcMzqcdQqvgR3wcPX7SleI4c9NtXk6E",
+            "$argon2id$v=19$m=65536,t=1,p=4096$c29tZXNhbHRzb21lc2FsdA$Gpj7qOY5RCXJvcMzqcdQqvgR3wcPX7SleI4c9NtXk6E",
+            "$scrypt$ln=40,r=8,p=1$c29tZXNhbHRzb21lc2FsdA$Gpj7qOY5RCXJvcMzqcdQqvgR3wcPX7SleI4c9NtXk6E",
+            "$scrypt$ln=14,r=1048576,p=1$c29tZXNhbHRzb21lc2FsdA$Gpj7qOY5RCXJvcMzqcdQqvgR3wcPX7SleI4c9NtXk6E",
+        ] {
+            assert!(matches!(verify_password(b"password", hash), Err(PasswordError::InvalidHash(_))), "{}", hash);
+        }
+    }
+}
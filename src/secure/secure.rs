@@ -1,7 +1,20 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
 use crypto::digest::Digest;
 use crypto::md5::Md5;
 use crypto::sha1::Sha1;
 
+use crate::secure::aes::{AesMode, CipherError, Envelope};
+use crate::secure::digest::{DigestAlgorithm, Hasher};
+use crate::secure::file_cipher::{self, FileCipherError, FileEncryptor, KdfLimits};
+use crate::secure::hmac::Hmac;
+use crate::secure::password::{self, PasswordAlgorithm, PasswordError};
+
+/// 流式计算摘要时每次读取的字节数。
+const BUFFER_SIZE: usize = 64 * 1024;
+
 pub struct SecureUtil;
 
 impl SecureUtil {
@@ -15,11 +28,30 @@ impl SecureUtil {
     ///
     /// 返回一个包含输入字符串 MD5 哈希值的 String。
     ///
+    /// MD5 不能用于保存密码，请使用 `hash_password`。
+    ///
     pub fn md5(input: &str) -> String {
         let mut md5 = Md5::new();
         md5.input(input.as_ref());
         md5.result_str()
     }
+    /// 使用 MD5 哈希算法计算字节数据的摘要。
+    ///
+    /// # 参数
+    ///
+    /// * `input`: &[u8] - 待计算摘要的数据
+    ///
+    /// # 返回值
+    ///
+    /// 返回16字节的 MD5 摘要。
+    ///
+    pub fn md5_bytes(input: &[u8]) -> [u8; 16] {
+        let mut md5 = Md5::new();
+        md5.input(input);
+        let mut digest = [0; 16];
+        md5.result(&mut digest);
+        digest
+    }
     /// 使用 SHA-1 哈希算法计算输入字符串的哈希值。
     ///
     /// # 参数
@@ -35,11 +67,357 @@ impl SecureUtil {
         sha1.input_str(input);
         sha1.result_str()
     }
+    /// 使用国密 SM3 算法计算输入字符串的摘要。
+    ///
+    /// # 参数
+    ///
+    /// * `input`: &str - 待计算摘要的字符串
+    ///
+    /// # 返回值
+    ///
+    /// 返回小写十六进制形式的 SM3 摘要。
+    ///
+    pub fn sm3(input: &str) -> String {
+        SecureUtil::to_hex(&SecureUtil::digest(DigestAlgorithm::Sm3, input.as_bytes()))
+    }
+    /// 使用 SHA-1 哈希算法计算字节数据的摘要。
+    ///
+    /// # 参数
+    ///
+    /// * `input`: &[u8] - 待计算摘要的数据
+    ///
+    /// # 返回值
+    ///
+    /// 返回20字节的 SHA-1 摘要。
+    ///
+    pub fn sha1_bytes(input: &[u8]) -> [u8; 20] {
+        let mut sha1 = Sha1::new();
+        sha1.input(input);
+        let mut digest = [0; 20];
+        sha1.result(&mut digest);
+        digest
+    }
+    /// 使用 MD5 哈希算法流式计算读取器中所有数据的摘要，按固定大小分块读取，内存占用与数据大小无关。
+    ///
+    /// # 参数
+    ///
+    /// * `reader`: R - 数据来源，例如文件或网络流
+    ///
+    /// # 返回值
+    ///
+    /// 返回16字节的 MD5 摘要，读取失败时返回错误。
+    ///
+    pub fn md5_reader<R: Read>(reader: R) -> io::Result<[u8; 16]> {
+        let digest = SecureUtil::digest_reader(DigestAlgorithm::Md5, reader)?;
+        Ok(digest.try_into().expect("MD5 摘要为16字节"))
+    }
+    /// 使用 SHA-1 哈希算法流式计算读取器中所有数据的摘要，按固定大小分块读取，内存占用与数据大小无关。
+    ///
+    /// # 参数
+    ///
+    /// * `reader`: R - 数据来源，例如文件或网络流
+    ///
+    /// # 返回值
+    ///
+    /// 返回20字节的 SHA-1 摘要，读取失败时返回错误。
+    ///
+    pub fn sha1_reader<R: Read>(reader: R) -> io::Result<[u8; 20]> {
+        let digest = SecureUtil::digest_reader(DigestAlgorithm::Sha1, reader)?;
+        Ok(digest.try_into().expect("SHA-1 摘要为20字节"))
+    }
+    /// 使用 MD5 哈希算法计算文件的摘要，适合校验大文件。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 文件路径
+    ///
+    /// # 返回值
+    ///
+    /// 返回16字节的 MD5 摘要，打开或读取文件失败时返回错误。
+    ///
+    pub fn md5_file<P: AsRef<Path>>(path: P) -> io::Result<[u8; 16]> {
+        SecureUtil::md5_reader(File::open(path)?)
+    }
+    /// 使用 SHA-1 哈希算法计算文件的摘要，适合校验大文件。
+    ///
+    /// # 参数
+    ///
+    /// * `path`: P - 文件路径
+    ///
+    /// # 返回值
+    ///
+    /// 返回20字节的 SHA-1 摘要，打开或读取文件失败时返回错误。
+    ///
+    pub fn sha1_file<P: AsRef<Path>>(path: P) -> io::Result<[u8; 20]> {
+        SecureUtil::sha1_reader(File::open(path)?)
+    }
+    /// 使用指定的摘要算法计算字节数据的摘要。
+    ///
+    /// # 参数
+    ///
+    /// * `algorithm`: DigestAlgorithm - 摘要算法，例如 `DigestAlgorithm::Sha256`
+    /// * `input`: &[u8] - 待计算摘要的数据
+    ///
+    /// # 返回值
+    ///
+    /// 返回摘要，长度为 `algorithm.output_len()`。
+    ///
+    pub fn digest(algorithm: DigestAlgorithm, input: &[u8]) -> Vec<u8> {
+        let mut hasher = Hasher::new(algorithm);
+        hasher.update(input);
+        hasher.finalize()
+    }
+    /// 使用指定的摘要算法流式计算读取器中所有数据的摘要，按固定大小分块读取，内存占用与数据大小无关。
+    ///
+    /// # 参数
+    ///
+    /// * `algorithm`: DigestAlgorithm - 摘要算法
+    /// * `reader`: R - 数据来源，例如文件或网络流
+    ///
+    /// # 返回值
+    ///
+    /// 返回摘要，读取失败时返回错误。
+    ///
+    pub fn digest_reader<R: Read>(algorithm: DigestAlgorithm, mut reader: R) -> io::Result<Vec<u8>> {
+        let mut hasher = Hasher::new(algorithm);
+        let mut buffer = vec![0; BUFFER_SIZE];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => hasher.update(&buffer[..read]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(hasher.finalize())
+    }
+    /// 使用指定的摘要算法计算文件的摘要，适合校验大文件。
+    ///
+    /// # 参数
+    ///
+    /// * `algorithm`: DigestAlgorithm - 摘要算法
+    /// * `path`: P - 文件路径
+    ///
+    /// # 返回值
+    ///
+    /// 返回摘要，打开或读取文件失败时返回错误。
+    ///
+    pub fn digest_file<P: AsRef<Path>>(algorithm: DigestAlgorithm, path: P) -> io::Result<Vec<u8>> {
+        SecureUtil::digest_reader(algorithm, File::open(path)?)
+    }
+    /// 使用指定的摘要算法和密钥计算消息的 HMAC，可用于签名 Webhook 和 API 请求。
+    ///
+    /// # 参数
+    ///
+    /// * `algorithm`: DigestAlgorithm - 摘要算法，例如 `DigestAlgorithm::Sha256`
+    /// * `key`: &[u8] - 密钥
+    /// * `data`: &[u8] - 消息
+    ///
+    /// # 返回值
+    ///
+    /// 返回 HMAC，长度与算法的摘要长度相同。消息很大时可以使用 `secure::hmac::Hmac` 增量计算。
+    ///
+    pub fn hmac(algorithm: DigestAlgorithm, key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut hmac = Hmac::new(algorithm, key);
+        hmac.update(data);
+        hmac.finalize()
+    }
+    /// 计算消息的 HMAC 并转换为小写的十六进制字符串。
+    ///
+    /// # 参数
+    ///
+    /// * `algorithm`: DigestAlgorithm - 摘要算法
+    /// * `key`: &[u8] - 密钥
+    /// * `data`: &[u8] - 消息
+    ///
+    /// # 返回值
+    ///
+    /// 返回十六进制字符串形式的 HMAC。
+    ///
+    pub fn hmac_hex(algorithm: DigestAlgorithm, key: &[u8], data: &[u8]) -> String {
+        SecureUtil::to_hex(&SecureUtil::hmac(algorithm, key, data))
+    }
+    /// 计算消息的 HMAC 并转换为标准 Base64 字符串。
+    ///
+    /// # 参数
+    ///
+    /// * `algorithm`: DigestAlgorithm - 摘要算法
+    /// * `key`: &[u8] - 密钥
+    /// * `data`: &[u8] - 消息
+    ///
+    /// # 返回值
+    ///
+    /// 返回 Base64 字符串形式的 HMAC。
+    ///
+    pub fn hmac_base64(algorithm: DigestAlgorithm, key: &[u8], data: &[u8]) -> String {
+        SecureUtil::to_base64(&SecureUtil::hmac(algorithm, key, data))
+    }
+    /// 验证消息的 HMAC 签名。比较以固定时间进行，耗时不会泄露签名中匹配的前缀长度。
+    ///
+    /// # 参数
+    ///
+    /// * `algorithm`: DigestAlgorithm - 摘要算法
+    /// * `key`: &[u8] - 密钥
+    /// * `data`: &[u8] - 消息
+    /// * `signature`: &[u8] - 待验证的签名，十六进制或 Base64 形式的签名需要先解码
+    ///
+    /// # 返回值
+    ///
+    /// 签名正确时返回true。
+    ///
+    pub fn verify_hmac(algorithm: DigestAlgorithm, key: &[u8], data: &[u8], signature: &[u8]) -> bool {
+        let mut hmac = Hmac::new(algorithm, key);
+        hmac.update(data);
+        hmac.verify(signature)
+    }
+    /// 使用 AES 加密数据，返回 Base64 编码的密文信封，可以直接写入配置文件。
+    ///
+    /// 每次加密都会生成新的随机 IV，信封中包含解密所需的模式、IV 和认证标签。
+    ///
+    /// # 参数
+    ///
+    /// * `mode`: AesMode - 工作模式，推荐使用 `AesMode::Gcm`
+    /// * `key`: &[u8] - 16、24或32字节的密钥，可以使用 `secure::aes::generate_key` 生成
+    /// * `plaintext`: &[u8] - 明文
+    /// * `aad`: &[u8] - 附加认证数据，仅 GCM 模式支持，其他模式必须为空
+    ///
+    /// # 返回值
+    ///
+    /// 返回 Base64 编码的密文信封，密钥长度或参数不正确时返回错误。
+    ///
+    pub fn aes_encrypt(mode: AesMode, key: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<String, CipherError> {
+        Ok(Envelope::seal(mode, key, plaintext, aad)?.to_base64())
+    }
+    /// 解密 `aes_encrypt` 生成的密文信封。
+    ///
+    /// # 参数
+    ///
+    /// * `mode`: AesMode - 加密时使用的模式，信封的模式不同时返回错误，防止 GCM 信封被改写为不认证的模式
+    /// * `key`: &[u8] - 加密时使用的密钥
+    /// * `envelope`: &str - Base64 编码的密文信封
+    /// * `aad`: &[u8] - 加密时使用的附加认证数据
+    ///
+    /// # 返回值
+    ///
+    /// 返回明文；信封格式错误、密钥错误或 GCM 模式下数据被篡改时返回错误。
+    ///
+    pub fn aes_decrypt(mode: AesMode, key: &[u8], envelope: &str, aad: &[u8]) -> Result<Vec<u8>, CipherError> {
+        Envelope::from_base64(envelope)?.open(mode, key, aad)
+    }
+    /// 使用随机盐计算密码哈希，结果可以直接保存。
+    ///
+    /// # 参数
+    ///
+    /// * `password`: &str - 密码
+    /// * `algorithm`: PasswordAlgorithm - 算法和成本参数，一般使用 `PasswordAlgorithm::default()`
+    ///
+    /// # 返回值
+    ///
+    /// 返回 PHC 格式（bcrypt 为 `$2b$` 格式）的哈希字符串；参数无效时返回错误。
+    ///
+    pub fn hash_password(password: &str, algorithm: PasswordAlgorithm) -> Result<String, PasswordError> {
+        password::hash_password(password.as_bytes(), algorithm)
+    }
+    /// 验证密码是否与 `hash_password` 生成的哈希匹配。
+    ///
+    /// # 参数
+    ///
+    /// * `password`: &str - 用户输入的密码
+    /// * `hash`: &str - 保存的哈希字符串
+    ///
+    /// # 返回值
+    ///
+    /// 密码正确时返回 true；哈希字符串格式错误时返回错误。
+    ///
+    pub fn verify_password(password: &str, hash: &str) -> Result<bool, PasswordError> {
+        password::verify_password(password.as_bytes(), hash)
+    }
+    /// 使用密码加密文件，密钥通过默认参数的 Argon2id 派生，数据分块流式加密。
+    ///
+    /// # 参数
+    ///
+    /// * `password`: &str - 密码
+    /// * `source`: P - 明文文件
+    /// * `target`: Q - 密文文件，已存在时覆盖
+    ///
+    /// # 返回值
+    ///
+    /// 返回加密的字节数；失败时删除不完整的输出文件并返回错误。
+    ///
+    pub fn encrypt_file<P: AsRef<Path>, Q: AsRef<Path>>(password: &str, source: P, target: Q) -> Result<u64, FileCipherError> {
+        FileEncryptor::new(password.as_bytes()).encrypt_file(source, target)
+    }
+    /// 解密 `encrypt_file` 生成的文件。
+    ///
+    /// # 参数
+    ///
+    /// * `password`: &str - 加密时使用的密码
+    /// * `source`: P - 密文文件
+    /// * `target`: Q - 明文文件，已存在时覆盖
+    ///
+    /// # 返回值
+    ///
+    /// 返回解密的字节数；密码错误、文件被篡改或不完整时删除输出文件并返回错误。
+    ///
+    pub fn decrypt_file<P: AsRef<Path>, Q: AsRef<Path>>(password: &str, source: P, target: Q) -> Result<u64, FileCipherError> {
+        file_cipher::decrypt_file(source, target, password.as_bytes(), KdfLimits::default())
+    }
+    /// 将摘要转换为小写的十六进制字符串。
+    ///
+    /// # 参数
+    ///
+    /// * `digest`: &[u8] - 摘要
+    ///
+    /// # 返回值
+    ///
+    /// 返回十六进制字符串，与 `md5`、`sha1` 的格式相同。
+    ///
+    pub fn to_hex(digest: &[u8]) -> String {
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+    /// 将十六进制字符串解码为字节，不区分大小写。
+    ///
+    /// # 参数
+    ///
+    /// * `input`: &str - 十六进制字符串，例如签名请求头中的值
+    ///
+    /// # 返回值
+    ///
+    /// 返回解码后的字节，长度为奇数或含有非十六进制字符时返回None。
+    ///
+    pub fn from_hex(input: &str) -> Option<Vec<u8>> {
+        // from_str_radix 接受前导的 `+`，需要先逐字节检查
+        if input.len() % 2 != 0 || !input.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        (0..input.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&input[i..i + 2], 16).ok())
+            .collect()
+    }
+    /// 将摘要转换为标准 Base64 字符串。
+    ///
+    /// # 参数
+    ///
+    /// * `digest`: &[u8] - 摘要
+    ///
+    /// # 返回值
+    ///
+    /// 返回 Base64 字符串，例如 HTTP 的 `Content-MD5` 头使用这种格式。
+    ///
+    pub fn to_base64(digest: &[u8]) -> String {
+        base64::encode(digest)
+    }
 }
 
-
 #[cfg(test)]
 mod tests {
+    use std::io;
+
+    use crate::secure::aes::{generate_key, AesMode, CipherError};
+    use crate::secure::password::PasswordAlgorithm;
+    use crate::secure::digest::DigestAlgorithm;
+    use crate::secure::file_cipher::FileCipherError;
     use crate::secure::secure::SecureUtil;
 
     #[test]
@@ -49,10 +427,97 @@ mod tests {
         assert_eq!(SecureUtil::md5(input), expected_output);
     }
 
+    #[test]
+    fn test_md5_bytes() {
+        let digest = SecureUtil::md5_bytes(b"Hello, World!");
+        assert_eq!(SecureUtil::to_hex(&digest), SecureUtil::md5("Hello, World!"));
+        assert_eq!(SecureUtil::to_base64(&digest), "ZajifYh5KDgxtmS9i38K1A==");
+    }
+
     #[test]
     fn test_sha1() {
         let input = "Hello, World!";
         let expected_output = "0a0a9f2a6772942557ab5355d76af442f8f65e01";
         assert_eq!(SecureUtil::sha1(input), expected_output);
+        assert_eq!(SecureUtil::to_hex(&SecureUtil::sha1_bytes(input.as_bytes())), expected_output);
+    }
+
+    #[test]
+    fn test_streaming_digest() {
+        // 跨越多个读取块的数据
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        assert_eq!(SecureUtil::md5_reader(data.as_slice()).unwrap(), SecureUtil::md5_bytes(&data));
+        assert_eq!(SecureUtil::sha1_reader(data.as_slice()).unwrap(), SecureUtil::sha1_bytes(&data));
+        assert_eq!(SecureUtil::to_hex(&SecureUtil::md5_reader(io::empty()).unwrap()), "d41d8cd98f00b204e9800998ecf8427e");
+
+        let path = std::env::temp_dir().join(format!("r-tool-digest-{}", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        assert_eq!(SecureUtil::md5_file(&path).unwrap(), SecureUtil::md5_bytes(&data));
+        assert_eq!(SecureUtil::sha1_file(&path).unwrap(), SecureUtil::sha1_bytes(&data));
+        std::fs::remove_file(&path).unwrap();
+        assert!(SecureUtil::md5_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_hmac() {
+        let signature = SecureUtil::hmac_hex(DigestAlgorithm::Sha256, b"key", b"The quick brown fox jumps over the lazy dog");
+        assert_eq!(signature, "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8");
+        assert_eq!(
+            SecureUtil::hmac_base64(DigestAlgorithm::Sha256, b"key", b"The quick brown fox jumps over the lazy dog"),
+            "97yD9DBThCSxMpjmqm+xQ+9NWaFJRhdZl0edvC0aPNg="
+        );
+
+        let decoded = SecureUtil::from_hex(&signature.to_uppercase()).unwrap();
+        assert!(SecureUtil::verify_hmac(DigestAlgorithm::Sha256, b"key", b"The quick brown fox jumps over the lazy dog", &decoded));
+        assert!(!SecureUtil::verify_hmac(DigestAlgorithm::Sha256, b"key", b"The quick brown fox", &decoded));
+        assert_eq!(SecureUtil::from_hex("abc"), None);
+        assert_eq!(SecureUtil::from_hex("zz"), None);
+        assert_eq!(SecureUtil::from_hex("+f+f"), None);
+    }
+
+    #[test]
+    fn test_aes() {
+        let key = generate_key(256).unwrap();
+        let envelope = SecureUtil::aes_encrypt(AesMode::Gcm, &key, b"s3cr3t", b"db").unwrap();
+        assert_eq!(SecureUtil::aes_decrypt(AesMode::Gcm, &key, &envelope, b"db").unwrap(), b"s3cr3t");
+        assert!(matches!(SecureUtil::aes_decrypt(AesMode::Gcm, &key, &envelope, b""), Err(CipherError::DecryptFailed)));
+
+        let envelope = SecureUtil::aes_encrypt(AesMode::Cbc, &key[..16], b"s3cr3t", b"").unwrap();
+        assert_eq!(SecureUtil::aes_decrypt(AesMode::Cbc, &key[..16], &envelope, b"").unwrap(), b"s3cr3t");
+        assert!(matches!(SecureUtil::aes_decrypt(AesMode::Gcm, &key[..16], &envelope, b""), Err(CipherError::ModeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_password() {
+        let algorithm = PasswordAlgorithm::Pbkdf2Sha256 { iterations: 10 };
+        let hash = SecureUtil::hash_password("s3cr3t", algorithm).unwrap();
+        assert!(SecureUtil::verify_password("s3cr3t", &hash).unwrap());
+        assert!(!SecureUtil::verify_password("secret", &hash).unwrap());
+        assert!(SecureUtil::verify_password("s3cr3t", &SecureUtil::md5("s3cr3t")).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_file() {
+        let source = std::env::temp_dir().join(format!("r-tool-encrypt-file-{}", std::process::id()));
+        let encrypted = source.with_extension("enc");
+        let decrypted = source.with_extension("out");
+        std::fs::write(&source, b"partner export").unwrap();
+        assert_eq!(SecureUtil::encrypt_file("s3cr3t", &source, &encrypted).unwrap(), 14);
+        assert!(matches!(SecureUtil::decrypt_file("secret", &encrypted, &decrypted), Err(FileCipherError::WrongPassword)));
+        assert_eq!(SecureUtil::decrypt_file("s3cr3t", &encrypted, &decrypted).unwrap(), 14);
+        assert_eq!(std::fs::read(&decrypted).unwrap(), b"partner export");
+        for path in [source, encrypted, decrypted] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_digest_file() {
+        let data = vec![7u8; 300_000];
+        let path = std::env::temp_dir().join(format!("r-tool-digest-file-{}", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        let digest = SecureUtil::digest_file(DigestAlgorithm::Sha512, &path).unwrap();
+        assert_eq!(digest, SecureUtil::digest(DigestAlgorithm::Sha512, &data));
+        std::fs::remove_file(&path).unwrap();
     }
 }
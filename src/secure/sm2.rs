@@ -0,0 +1,719 @@
+use std::fmt;
+
+use crypto::util::fixed_time_eq;
+use openssl::bn::{BigNum, BigNumContext, BigNumRef};
+use openssl::ec::{EcGroup, EcPoint, EcPointRef, PointConversionForm};
+use openssl::ecdsa::EcdsaSig;
+use openssl::error::ErrorStack;
+use openssl::nid::Nid;
+
+use crate::secure::digest::{DigestAlgorithm, Hasher};
+use crate::secure::secure::SecureUtil;
+
+/// 未约定用户标识时使用的默认值（GM/T 0009-2012）。
+pub const DEFAULT_USER_ID: &[u8] = b"1234567812345678";
+
+/// SM3 摘要的字节数，即密文中 C3 的长度。
+const HASH_LEN: usize = 32;
+
+/// SM2 错误。
+#[derive(Debug)]
+pub enum Sm2Error {
+    /// 私钥不在 [1, n-2] 范围内，或者公钥不是曲线上的点
+    InvalidKey(String),
+    /// 用户标识超过8191字节，长度无法用16位比特数表示
+    UserIdTooLong(usize),
+    /// 签名格式错误
+    InvalidSignature(String),
+    /// 解密失败：密文格式错误、私钥错误或者密文被篡改
+    DecryptFailed,
+    /// OpenSSL 返回的其他错误
+    OpenSsl(ErrorStack),
+}
+
+impl fmt::Display for Sm2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sm2Error::InvalidKey(msg) => write!(f, "无效的 SM2 密钥: {}", msg),
+            Sm2Error::UserIdTooLong(len) => write!(f, "用户标识长度为{}字节，不能超过8191字节", len),
+            Sm2Error::InvalidSignature(msg) => write!(f, "无效的 SM2 签名: {}", msg),
+            Sm2Error::DecryptFailed => write!(f, "解密失败，密钥错误或数据已被篡改"),
+            Sm2Error::OpenSsl(e) => write!(f, "OpenSSL 错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Sm2Error {}
+
+impl From<ErrorStack> for Sm2Error {
+    fn from(e: ErrorStack) -> Self {
+        Sm2Error::OpenSsl(e)
+    }
+}
+
+/// SM2 密文中各部分的排列顺序。
+///
+/// C1 为65字节的随机点，C2 为与明文等长的密文，C3 为32字节的 SM3 校验值。
+/// GB/T 32918.4-2016 规定为 `C1C3C2`，早期的实现（包括部分 Java 库的默认值）使用 `C1C2C3`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sm2CipherFormat {
+    C1C3C2,
+    C1C2C3,
+}
+
+/// 椭圆曲线参数，`a`、`b` 和基点坐标以定长字节保存，用于计算用户标识的杂凑值 Z。
+struct Curve {
+    group: EcGroup,
+    n: BigNum,
+    // 坐标的字节数
+    len: usize,
+    a: Vec<u8>,
+    b: Vec<u8>,
+    gx: Vec<u8>,
+    gy: Vec<u8>,
+}
+
+impl Curve {
+    /// 国密推荐曲线 sm2p256v1。
+    fn sm2() -> Result<Self, Sm2Error> {
+        Curve::from_group(EcGroup::from_curve_name(Nid::SM2)?)
+    }
+
+    fn from_group(group: EcGroup) -> Result<Self, Sm2Error> {
+        let mut ctx = BigNumContext::new()?;
+        let (mut p, mut a, mut b) = (BigNum::new()?, BigNum::new()?, BigNum::new()?);
+        group.components_gfp(&mut p, &mut a, &mut b, &mut ctx)?;
+        let mut n = BigNum::new()?;
+        group.order(&mut n, &mut ctx)?;
+        let len = p.num_bytes() as usize;
+        let (gx, gy) = coordinates(&group, group.generator(), len, &mut ctx)?;
+        let a = a.to_vec_padded(len as i32)?;
+        let b = b.to_vec_padded(len as i32)?;
+        Ok(Curve { group, n, len, a, b, gx, gy })
+    }
+
+    fn point_from_bytes(&self, bytes: &[u8], ctx: &mut BigNumContext) -> Result<EcPoint, Sm2Error> {
+        let point = EcPoint::from_bytes(&self.group, bytes, ctx).map_err(|e| Sm2Error::InvalidKey(e.to_string()))?;
+        if point.is_infinity(&self.group) || !point.is_on_curve(&self.group, ctx)? {
+            return Err(Sm2Error::InvalidKey("不是曲线上的点".to_string()));
+        }
+        Ok(point)
+    }
+
+    /// 生成 [1, n-1] 范围内的随机数。
+    fn random_scalar(&self) -> Result<BigNum, Sm2Error> {
+        let mut k = BigNum::new()?;
+        loop {
+            self.n.rand_range(&mut k)?;
+            if !is_zero(&k) {
+                return Ok(k);
+            }
+        }
+    }
+
+    /// 计算用户标识的杂凑值 Z = SM3(ENTL || ID || a || b || xG || yG || xA || yA)。
+    fn z(&self, user_id: &[u8], public: &[u8]) -> Result<Vec<u8>, Sm2Error> {
+        let bits = u16::try_from(user_id.len() * 8).map_err(|_| Sm2Error::UserIdTooLong(user_id.len()))?;
+        let mut hasher = Hasher::new(DigestAlgorithm::Sm3);
+        hasher.update(&bits.to_be_bytes());
+        hasher.update(user_id);
+        for part in [&self.a, &self.b, &self.gx, &self.gy] {
+            hasher.update(part);
+        }
+        // 跳过未压缩点的 0x04 前缀
+        hasher.update(&public[1..]);
+        Ok(hasher.finalize())
+    }
+
+    /// 签名消息的杂凑值 e = SM3(Z || M)。
+    fn e(&self, user_id: &[u8], public: &[u8], message: &[u8]) -> Result<BigNum, Sm2Error> {
+        let mut hasher = Hasher::new(DigestAlgorithm::Sm3);
+        hasher.update(&self.z(user_id, public)?);
+        hasher.update(message);
+        Ok(BigNum::from_slice(&hasher.finalize())?)
+    }
+
+    fn sign(
+        &self,
+        d: &BigNumRef,
+        public: &[u8],
+        user_id: &[u8],
+        message: &[u8],
+        mut next_k: impl FnMut() -> Result<BigNum, Sm2Error>,
+    ) -> Result<Vec<u8>, Sm2Error> {
+        let mut ctx = BigNumContext::new()?;
+        let e = self.e(user_id, public, message)?;
+        // 与私钥和随机数 k 有关的值都设置常数时间标记，OpenSSL 对它们求逆和求幂时使用不依赖数值的实现，避免通过耗时泄露私钥
+        let mut d = d.to_owned()?;
+        d.set_const_time();
+        // (1 + d)^-1
+        let mut inverse = BigNum::new()?;
+        inverse.set_const_time();
+        let mut one_plus_d = d.to_owned()?;
+        one_plus_d.set_const_time();
+        one_plus_d.add_word(1)?;
+        inverse.mod_inverse(&one_plus_d, &self.n, &mut ctx)?;
+
+        loop {
+            let mut k = next_k()?;
+            k.set_const_time();
+            let mut point = EcPoint::new(&self.group)?;
+            point.mul_generator(&self.group, &k, &ctx)?;
+            let (x1, _) = coordinates(&self.group, &point, self.len, &mut ctx)?;
+            let x1 = BigNum::from_slice(&x1)?;
+
+            // r = (e + x1) mod n，r = 0 或 r + k = n 时重新选取 k
+            let mut r = BigNum::new()?;
+            r.mod_add(&e, &x1, &self.n, &mut ctx)?;
+            let mut r_plus_k = BigNum::new()?;
+            r_plus_k.checked_add(&r, &k)?;
+            if is_zero(&r) || r_plus_k == self.n {
+                continue;
+            }
+
+            // s = ((1 + d)^-1 * (k - r * d)) mod n
+            let mut rd = BigNum::new()?;
+            rd.set_const_time();
+            rd.mod_mul(&r, &d, &self.n, &mut ctx)?;
+            let mut k_minus_rd = BigNum::new()?;
+            k_minus_rd.set_const_time();
+            k_minus_rd.mod_sub(&k, &rd, &self.n, &mut ctx)?;
+            let mut s = BigNum::new()?;
+            s.mod_mul(&inverse, &k_minus_rd, &self.n, &mut ctx)?;
+            if is_zero(&s) {
+                continue;
+            }
+
+            let len = self.n.num_bytes();
+            let mut signature = r.to_vec_padded(len)?;
+            signature.extend(s.to_vec_padded(len)?);
+            return Ok(signature);
+        }
+    }
+
+    fn verify(&self, public: &[u8], user_id: &[u8], message: &[u8], signature: &[u8]) -> Result<bool, Sm2Error> {
+        let len = self.n.num_bytes() as usize;
+        if signature.len() != len * 2 {
+            return Ok(false);
+        }
+        let r = BigNum::from_slice(&signature[..len])?;
+        let s = BigNum::from_slice(&signature[len..])?;
+        if is_zero(&r) || is_zero(&s) || r >= self.n || s >= self.n {
+            return Ok(false);
+        }
+
+        let mut ctx = BigNumContext::new()?;
+        let e = self.e(user_id, public, message)?;
+        // t = (r + s) mod n
+        let mut t = BigNum::new()?;
+        t.mod_add(&r, &s, &self.n, &mut ctx)?;
+        if is_zero(&t) {
+            return Ok(false);
+        }
+
+        // (x1, y1) = sG + tP
+        let public = self.point_from_bytes(public, &mut ctx)?;
+        let mut sg = EcPoint::new(&self.group)?;
+        sg.mul_generator(&self.group, &s, &ctx)?;
+        let mut tp = EcPoint::new(&self.group)?;
+        tp.mul(&self.group, &public, &t, &ctx)?;
+        let mut point = EcPoint::new(&self.group)?;
+        point.add(&self.group, &sg, &tp, &mut ctx)?;
+        if point.is_infinity(&self.group) {
+            return Ok(false);
+        }
+        let (x1, _) = coordinates(&self.group, &point, self.len, &mut ctx)?;
+        let x1 = BigNum::from_slice(&x1)?;
+
+        // R = (e + x1) mod n
+        let mut expected = BigNum::new()?;
+        expected.mod_add(&e, &x1, &self.n, &mut ctx)?;
+        Ok(expected == r)
+    }
+
+    fn encrypt(
+        &self,
+        public: &[u8],
+        format: Sm2CipherFormat,
+        plaintext: &[u8],
+        mut next_k: impl FnMut() -> Result<BigNum, Sm2Error>,
+    ) -> Result<Vec<u8>, Sm2Error> {
+        let mut ctx = BigNumContext::new()?;
+        let public = self.point_from_bytes(public, &mut ctx)?;
+        loop {
+            let k = next_k()?;
+            // C1 = kG
+            let mut c1 = EcPoint::new(&self.group)?;
+            c1.mul_generator(&self.group, &k, &ctx)?;
+            // (x2, y2) = kP
+            let mut shared = EcPoint::new(&self.group)?;
+            shared.mul(&self.group, &public, &k, &ctx)?;
+            let (x2, y2) = coordinates(&self.group, &shared, self.len, &mut ctx)?;
+
+            let Some(c2) = xor_kdf(&x2, &y2, plaintext) else {
+                continue;
+            };
+            let c3 = checksum(&x2, plaintext, &y2);
+            let mut out = c1.to_bytes(&self.group, PointConversionForm::UNCOMPRESSED, &mut ctx)?;
+            match format {
+                Sm2CipherFormat::C1C3C2 => {
+                    out.extend(c3);
+                    out.extend(c2);
+                }
+                Sm2CipherFormat::C1C2C3 => {
+                    out.extend(c2);
+                    out.extend(c3);
+                }
+            }
+            return Ok(out);
+        }
+    }
+
+    fn decrypt(&self, d: &BigNumRef, format: Sm2CipherFormat, ciphertext: &[u8]) -> Result<Vec<u8>, Sm2Error> {
+        let c1_len = 1 + 2 * self.len;
+        if ciphertext.len() < c1_len + HASH_LEN || ciphertext[0] != 0x04 {
+            return Err(Sm2Error::DecryptFailed);
+        }
+        let (c1, rest) = ciphertext.split_at(c1_len);
+        let (c2, c3) = match format {
+            Sm2CipherFormat::C1C3C2 => {
+                let (c3, c2) = rest.split_at(HASH_LEN);
+                (c2, c3)
+            }
+            Sm2CipherFormat::C1C2C3 => rest.split_at(rest.len() - HASH_LEN),
+        };
+
+        let mut ctx = BigNumContext::new()?;
+        let c1 = self.point_from_bytes(c1, &mut ctx).map_err(|_| Sm2Error::DecryptFailed)?;
+        // (x2, y2) = dC1
+        let mut shared = EcPoint::new(&self.group)?;
+        shared.mul(&self.group, &c1, d, &ctx)?;
+        let (x2, y2) = coordinates(&self.group, &shared, self.len, &mut ctx)?;
+        let plaintext = xor_kdf(&x2, &y2, c2).ok_or(Sm2Error::DecryptFailed)?;
+        if !fixed_time_eq(&checksum(&x2, &plaintext, &y2), c3) {
+            return Err(Sm2Error::DecryptFailed);
+        }
+        Ok(plaintext)
+    }
+}
+
+fn is_zero(n: &BigNumRef) -> bool {
+    n.num_bits() == 0
+}
+
+/// 返回点的定长仿射坐标。
+fn coordinates(
+    group: &EcGroup,
+    point: &EcPointRef,
+    len: usize,
+    ctx: &mut BigNumContext,
+) -> Result<(Vec<u8>, Vec<u8>), Sm2Error> {
+    let (mut x, mut y) = (BigNum::new()?, BigNum::new()?);
+    point.affine_coordinates(group, &mut x, &mut y, ctx)?;
+    Ok((x.to_vec_padded(len as i32)?, y.to_vec_padded(len as i32)?))
+}
+
+/// 使用密钥派生函数 KDF(x2 || y2, klen) 的输出与数据异或，输出全为0时返回None。
+fn xor_kdf(x2: &[u8], y2: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    for (counter, chunk) in (1u32..).zip(data.chunks(HASH_LEN)) {
+        let mut hasher = Hasher::new(DigestAlgorithm::Sm3);
+        hasher.update(x2);
+        hasher.update(y2);
+        hasher.update(&counter.to_be_bytes());
+        out.extend(chunk.iter().zip(hasher.finalize()).map(|(byte, key)| byte ^ key));
+    }
+    let all_zero = out.iter().zip(data).all(|(c, m)| c == m);
+    if all_zero && !data.is_empty() {
+        return None;
+    }
+    Some(out)
+}
+
+/// 校验值 C3 = SM3(x2 || M || y2)。
+fn checksum(x2: &[u8], message: &[u8], y2: &[u8]) -> Vec<u8> {
+    let mut hasher = Hasher::new(DigestAlgorithm::Sm3);
+    hasher.update(x2);
+    hasher.update(message);
+    hasher.update(y2);
+    hasher.finalize()
+}
+
+/// SM2 私钥（GB/T 32918-2016），使用国密推荐曲线 sm2p256v1。
+pub struct Sm2PrivateKey {
+    d: BigNum,
+    public: Sm2PublicKey,
+}
+
+/// SM2 公钥，以65字节的未压缩点（`04 || x || y`）保存。
+#[derive(Clone, PartialEq, Eq)]
+pub struct Sm2PublicKey {
+    point: Vec<u8>,
+}
+
+impl Sm2PrivateKey {
+    /// 生成新的密钥对。
+    ///
+    /// 返回值：Result<Sm2PrivateKey, Sm2Error> - 私钥，公钥可以通过 `public_key` 取得
+    pub fn generate() -> Result<Self, Sm2Error> {
+        let curve = Curve::sm2()?;
+        loop {
+            let d = curve.random_scalar()?;
+            // 私钥必须在 [1, n-2] 范围内
+            let mut d_plus_one = d.to_owned()?;
+            d_plus_one.add_word(1)?;
+            if d_plus_one != curve.n {
+                return Sm2PrivateKey::from_scalar(&curve, d);
+            }
+        }
+    }
+
+    /// 从32字节的私钥导入。
+    ///
+    /// # 参数
+    ///
+    /// * `bytes`: &[u8] - 大端序的私钥，必须正好32字节
+    ///
+    /// 返回值：Result<Sm2PrivateKey, Sm2Error> - 私钥，长度不是32字节或不在 [1, n-2] 范围内时返回 `InvalidKey`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Sm2Error> {
+        if bytes.len() != 32 {
+            return Err(Sm2Error::InvalidKey(format!("私钥应为32字节，实际为{}字节", bytes.len())));
+        }
+        Sm2PrivateKey::from_scalar(&Curve::sm2()?, BigNum::from_slice(bytes)?)
+    }
+
+    /// 从十六进制字符串导入私钥。
+    ///
+    /// # 参数
+    ///
+    /// * `input`: &str - 十六进制的私钥
+    ///
+    /// 返回值：Result<Sm2PrivateKey, Sm2Error> - 私钥
+    pub fn from_hex(input: &str) -> Result<Self, Sm2Error> {
+        let bytes = SecureUtil::from_hex(input).ok_or_else(|| Sm2Error::InvalidKey("不是十六进制字符串".to_string()))?;
+        Sm2PrivateKey::from_bytes(&bytes)
+    }
+
+    fn from_scalar(curve: &Curve, mut d: BigNum) -> Result<Self, Sm2Error> {
+        let mut n_minus_one = curve.n.to_owned()?;
+        n_minus_one.sub_word(1)?;
+        if is_zero(&d) || d >= n_minus_one {
+            return Err(Sm2Error::InvalidKey("私钥不在 [1, n-2] 范围内".to_string()));
+        }
+        d.set_const_time();
+        let mut ctx = BigNumContext::new()?;
+        let mut point = EcPoint::new(&curve.group)?;
+        point.mul_generator(&curve.group, &d, &ctx)?;
+        let point = point.to_bytes(&curve.group, PointConversionForm::UNCOMPRESSED, &mut ctx)?;
+        Ok(Sm2PrivateKey { d, public: Sm2PublicKey { point } })
+    }
+
+    /// 导出为32字节的私钥。
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Sm2Error> {
+        Ok(self.d.to_vec_padded(32)?)
+    }
+
+    /// 导出为小写十六进制字符串。
+    pub fn to_hex(&self) -> Result<String, Sm2Error> {
+        Ok(SecureUtil::to_hex(&self.to_bytes()?))
+    }
+
+    /// 返回对应的公钥。
+    pub fn public_key(&self) -> &Sm2PublicKey {
+        &self.public
+    }
+
+    /// 对消息签名。
+    ///
+    /// # 参数
+    ///
+    /// * `user_id`: &[u8] - 签名者的用户标识，参与杂凑值 Z 的计算，没有约定时使用 `DEFAULT_USER_ID`
+    /// * `message`: &[u8] - 待签名的消息
+    ///
+    /// 返回值：Result<Vec<u8>, Sm2Error> - 64字节的签名 `r || s`，可以使用 `signature_to_der` 转换为 ASN.1 格式
+    pub fn sign(&self, user_id: &[u8], message: &[u8]) -> Result<Vec<u8>, Sm2Error> {
+        let curve = Curve::sm2()?;
+        curve.sign(&self.d, &self.public.point, user_id, message, || curve.random_scalar())
+    }
+
+    /// 解密 `Sm2PublicKey::encrypt` 生成的密文。
+    ///
+    /// # 参数
+    ///
+    /// * `format`: Sm2CipherFormat - 加密时使用的密文格式
+    /// * `ciphertext`: &[u8] - 密文
+    ///
+    /// 返回值：Result<Vec<u8>, Sm2Error> - 明文，密钥错误或密文被篡改时返回 `DecryptFailed`
+    pub fn decrypt(&self, format: Sm2CipherFormat, ciphertext: &[u8]) -> Result<Vec<u8>, Sm2Error> {
+        Curve::sm2()?.decrypt(&self.d, format, ciphertext)
+    }
+}
+
+impl Clone for Sm2PrivateKey {
+    fn clone(&self) -> Self {
+        let mut d = self.d.to_owned().expect("复制私钥时内存分配失败");
+        d.set_const_time();
+        Sm2PrivateKey { d, public: self.public.clone() }
+    }
+}
+
+impl fmt::Debug for Sm2PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 不输出私钥内容
+        write!(f, "Sm2PrivateKey({:?})", self.public)
+    }
+}
+
+impl Sm2PublicKey {
+    /// 从字节导入公钥。
+    ///
+    /// # 参数
+    ///
+    /// * `bytes`: &[u8] - 65字节的未压缩点（`04 || x || y`）、33字节的压缩点，或者不带前缀的64字节 `x || y`
+    ///
+    /// 返回值：Result<Sm2PublicKey, Sm2Error> - 公钥，不是曲线上的点时返回 `InvalidKey`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Sm2Error> {
+        let curve = Curve::sm2()?;
+        let mut ctx = BigNumContext::new()?;
+        let prefixed;
+        let bytes = if bytes.len() == 2 * curve.len {
+            prefixed = [&[0x04], bytes].concat();
+            &prefixed
+        } else {
+            bytes
+        };
+        let point = curve.point_from_bytes(bytes, &mut ctx)?;
+        let point = point.to_bytes(&curve.group, PointConversionForm::UNCOMPRESSED, &mut ctx)?;
+        Ok(Sm2PublicKey { point })
+    }
+
+    /// 从十六进制字符串导入公钥，格式与 `from_bytes` 相同。
+    ///
+    /// # 参数
+    ///
+    /// * `input`: &str - 十六进制的公钥
+    ///
+    /// 返回值：Result<Sm2PublicKey, Sm2Error> - 公钥
+    pub fn from_hex(input: &str) -> Result<Self, Sm2Error> {
+        let bytes = SecureUtil::from_hex(input).ok_or_else(|| Sm2Error::InvalidKey("不是十六进制字符串".to_string()))?;
+        Sm2PublicKey::from_bytes(&bytes)
+    }
+
+    /// 导出为65字节的未压缩点。
+    pub fn to_bytes(&self) -> &[u8] {
+        &self.point
+    }
+
+    /// 导出为小写十六进制字符串。
+    pub fn to_hex(&self) -> String {
+        SecureUtil::to_hex(&self.point)
+    }
+
+    /// 验证签名。
+    ///
+    /// # 参数
+    ///
+    /// * `user_id`: &[u8] - 签名者的用户标识，必须与签名时相同
+    /// * `message`: &[u8] - 被签名的消息
+    /// * `signature`: &[u8] - 64字节的签名 `r || s`
+    ///
+    /// 返回值：Result<bool, Sm2Error> - 签名正确时返回true
+    pub fn verify(&self, user_id: &[u8], message: &[u8], signature: &[u8]) -> Result<bool, Sm2Error> {
+        Curve::sm2()?.verify(&self.point, user_id, message, signature)
+    }
+
+    /// 加密数据，每次加密使用新的随机数，相同的明文得到不同的密文。
+    ///
+    /// # 参数
+    ///
+    /// * `format`: Sm2CipherFormat - 密文格式
+    /// * `plaintext`: &[u8] - 明文
+    ///
+    /// 返回值：Result<Vec<u8>, Sm2Error> - 密文，比明文长97字节
+    pub fn encrypt(&self, format: Sm2CipherFormat, plaintext: &[u8]) -> Result<Vec<u8>, Sm2Error> {
+        let curve = Curve::sm2()?;
+        curve.encrypt(&self.point, format, plaintext, || curve.random_scalar())
+    }
+}
+
+impl fmt::Debug for Sm2PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sm2PublicKey({})", self.to_hex())
+    }
+}
+
+/// 将64字节的签名 `r || s` 转换为 ASN.1 DER 格式，与 BouncyCastle 等库的默认格式相同。
+///
+/// # 参数
+///
+/// * `signature`: &[u8] - 64字节的签名
+///
+/// 返回值：Result<Vec<u8>, Sm2Error> - DER 格式的签名
+pub fn signature_to_der(signature: &[u8]) -> Result<Vec<u8>, Sm2Error> {
+    if signature.len() != 64 {
+        return Err(Sm2Error::InvalidSignature(format!("签名长度必须为64字节，实际为{}字节", signature.len())));
+    }
+    let r = BigNum::from_slice(&signature[..32])?;
+    let s = BigNum::from_slice(&signature[32..])?;
+    Ok(EcdsaSig::from_private_components(r, s)?.to_der()?)
+}
+
+/// 将 ASN.1 DER 格式的签名转换为64字节的 `r || s`。
+///
+/// # 参数
+///
+/// * `der`: &[u8] - DER 格式的签名
+///
+/// 返回值：Result<Vec<u8>, Sm2Error> - 64字节的签名
+pub fn signature_from_der(der: &[u8]) -> Result<Vec<u8>, Sm2Error> {
+    let signature = EcdsaSig::from_der(der).map_err(|e| Sm2Error::InvalidSignature(e.to_string()))?;
+    if signature.r().num_bytes() > 32 || signature.s().num_bytes() > 32 {
+        return Err(Sm2Error::InvalidSignature("r 或 s 超过32字节".to_string()));
+    }
+    let mut out = signature.r().to_vec_padded(32)?;
+    out.extend(signature.s().to_vec_padded(32)?);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bn(hex: &str) -> BigNum {
+        BigNum::from_hex_str(hex).unwrap()
+    }
+
+    /// GB/T 32918 附录中示例使用的 Fp-256 曲线。
+    fn example_curve() -> Curve {
+        let mut ctx = BigNumContext::new().unwrap();
+        let mut group = EcGroup::from_components(
+            bn("8542D69E4C044F18E8B92435BF6FF7DE457283915C45517D722EDB8B08F1DFC3"),
+            bn("787968B4FA32C3FD2417842E73BBFEFF2F3C848B6831D7E0EC65228B3937E498"),
+            bn("63E4C6D3B23B0C849CF84241484BFE48F61D59A5B16BA06E6E12D1DA27C5249A"),
+            &mut ctx,
+        )
+        .unwrap();
+        let mut generator = EcPoint::new(&group).unwrap();
+        generator
+            .set_affine_coordinates_gfp(
+                &group,
+                &bn("421DEBD61B62EAB6746434EBC3CC315E32220B3BADD50BDC4C4E6C147FEDD43D"),
+                &bn("0680512BCBB42C07D47349D2153B70C4E5D7FDFCBFA36EA1A85841B9E46E09A2"),
+                &mut ctx,
+            )
+            .unwrap();
+        let order = bn("8542D69E4C044F18E8B92435BF6FF7DD297720630485628D5AE74EE7C32E79B7");
+        group.set_generator(generator, order, BigNum::from_u32(1).unwrap()).unwrap();
+        Curve::from_group(group).unwrap()
+    }
+
+    fn public_point(curve: &Curve, d: &BigNum) -> Vec<u8> {
+        let mut ctx = BigNumContext::new().unwrap();
+        let mut point = EcPoint::new(&curve.group).unwrap();
+        point.mul_generator(&curve.group, d, &ctx).unwrap();
+        point.to_bytes(&curve.group, PointConversionForm::UNCOMPRESSED, &mut ctx).unwrap()
+    }
+
+    #[test]
+    fn test_sm2_sign_standard_vector() {
+        // GB/T 32918.2-2016 附录A.2
+        let curve = example_curve();
+        let d = bn("128B2FA8BD433C6C068C8D803DFF79792A519A55171B1B650C23661D15897263");
+        let public = public_point(&curve, &d);
+        assert_eq!(
+            SecureUtil::to_hex(&public[1..]),
+            concat!(
+                "0ae4c7798aa0f119471bee11825be46202bb79e2a5844495e97c04ff4df2548a",
+                "7c0240f88f1cd4e16352a73c17b7f16f07353e53a176d684a9fe0c6bb798e857"
+            )
+        );
+        let user_id = b"ALICE123@YAHOO.COM";
+        assert_eq!(
+            SecureUtil::to_hex(&curve.z(user_id, &public).unwrap()),
+            "f4a38489e32b45b6f876e3ac2168ca392362dc8f23459c1d1146fc3dbfb7bc9a"
+        );
+
+        let k = || Ok(bn("6CB28D99385C175C94F94E934817663FC176D925DD72B727260DBAAE1FB2F96F"));
+        let signature = curve.sign(&d, &public, user_id, b"message digest", k).unwrap();
+        assert_eq!(
+            SecureUtil::to_hex(&signature),
+            concat!(
+                "40f1ec59f793d9f49e09dcef49130d4194f79fb1eed2caa55bacdb49c4e755d1",
+                "6fc6dac32c5d5cf10c77dfb20f7c2eb667a457872fb09ec56327a67ec7deebe7"
+            )
+        );
+        assert!(curve.verify(&public, user_id, b"message digest", &signature).unwrap());
+        assert!(!curve.verify(&public, b"BOB", b"message digest", &signature).unwrap());
+    }
+
+    #[test]
+    fn test_sm2_encrypt_standard_vector() {
+        // GB/T 32918.4-2016 附录A.2
+        let curve = example_curve();
+        let d = bn("1649AB77A00637BD5E2EFE283FBF353534AA7F7CB89463F208DDBC2920BB0DA0");
+        let public = public_point(&curve, &d);
+        let k = || Ok(bn("4C62EEFD6ECFC2B95B92FD6C3D9575148AFA17425546D49018E5388D49DD7B4F"));
+        let ciphertext = curve.encrypt(&public, Sm2CipherFormat::C1C3C2, b"encryption standard", k).unwrap();
+        assert_eq!(
+            SecureUtil::to_hex(&ciphertext),
+            concat!(
+                "04",
+                "245c26fb68b1ddddb12c4b6bf9f2b6d5fe60a383b0d18d1c4144abf17f6252e7",
+                "76cb9264c2a7e88e52b19903fdc47378f605e36811f5c07423a24b84400f01b8",
+                "9c3d7360c30156fab7c80a0276712da9d8094a634b766d3a285e07480653426d",
+                "650053a89b41c418b0c3aad00d886c00286467"
+            )
+        );
+        assert_eq!(curve.decrypt(&d, Sm2CipherFormat::C1C3C2, &ciphertext).unwrap(), b"encryption standard");
+
+        let ciphertext = curve.encrypt(&public, Sm2CipherFormat::C1C2C3, b"encryption standard", k).unwrap();
+        assert!(SecureUtil::to_hex(&ciphertext).ends_with("9c3d7360c30156fab7c80a0276712da9d8094a634b766d3a285e07480653426d"));
+        assert_eq!(curve.decrypt(&d, Sm2CipherFormat::C1C2C3, &ciphertext).unwrap(), b"encryption standard");
+    }
+
+    #[test]
+    fn test_sm2_keys_and_round_trip() {
+        let key = Sm2PrivateKey::generate().unwrap();
+        let public = key.public_key();
+        assert_eq!(public.to_bytes().len(), 65);
+        let imported = Sm2PrivateKey::from_hex(&key.to_hex().unwrap()).unwrap();
+        assert_eq!(imported.public_key(), public);
+        assert_eq!(&Sm2PublicKey::from_hex(&public.to_hex()).unwrap(), public);
+        assert_eq!(&Sm2PublicKey::from_bytes(&public.to_bytes()[1..]).unwrap(), public);
+        assert!(!format!("{:?}", key).contains(&key.to_hex().unwrap()));
+
+        let signature = key.sign(DEFAULT_USER_ID, b"payload").unwrap();
+        assert!(public.verify(DEFAULT_USER_ID, b"payload", &signature).unwrap());
+        assert!(!public.verify(DEFAULT_USER_ID, b"payloaD", &signature).unwrap());
+        assert!(!public.verify(DEFAULT_USER_ID, b"payload", &signature[1..]).unwrap());
+        let der = signature_to_der(&signature).unwrap();
+        assert_eq!(signature_from_der(&der).unwrap(), signature);
+
+        for format in [Sm2CipherFormat::C1C3C2, Sm2CipherFormat::C1C2C3] {
+            for plaintext in [&b""[..], b"a", &[7; 100]] {
+                let ciphertext = public.encrypt(format, plaintext).unwrap();
+                assert_eq!(ciphertext.len(), plaintext.len() + 97);
+                assert_eq!(key.decrypt(format, &ciphertext).unwrap(), plaintext);
+            }
+        }
+        let mut ciphertext = public.encrypt(Sm2CipherFormat::C1C3C2, b"secret").unwrap();
+        let last = ciphertext.len() - 1;
+        ciphertext[last] ^= 1;
+        assert!(matches!(key.decrypt(Sm2CipherFormat::C1C3C2, &ciphertext), Err(Sm2Error::DecryptFailed)));
+        assert!(matches!(key.decrypt(Sm2CipherFormat::C1C3C2, &ciphertext[..50]), Err(Sm2Error::DecryptFailed)));
+
+        assert!(matches!(Sm2PrivateKey::from_bytes(&[0; 32]), Err(Sm2Error::InvalidKey(_))));
+        // 私钥必须正好32字节且小于 n-1
+        let bytes = key.to_bytes().unwrap();
+        assert!(matches!(Sm2PrivateKey::from_bytes(&bytes[1..]), Err(Sm2Error::InvalidKey(_))));
+        assert!(matches!(Sm2PrivateKey::from_bytes(&[&[0][..], &bytes].concat()), Err(Sm2Error::InvalidKey(_))));
+        let mut n_minus_one = Curve::sm2().unwrap().n;
+        n_minus_one.sub_word(1).unwrap();
+        let n_minus_one = n_minus_one.to_vec_padded(32).unwrap();
+        assert!(matches!(Sm2PrivateKey::from_bytes(&n_minus_one), Err(Sm2Error::InvalidKey(_))));
+        assert!(matches!(Sm2PrivateKey::from_bytes(&[0xff; 32]), Err(Sm2Error::InvalidKey(_))));
+        assert!(key.d.is_const_time());
+        assert!(matches!(Sm2PublicKey::from_bytes(&[4; 65]), Err(Sm2Error::InvalidKey(_))));
+        assert!(matches!(key.sign(&[0; 8192], b"payload"), Err(Sm2Error::UserIdTooLong(8192))));
+    }
+}
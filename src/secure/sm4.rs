@@ -0,0 +1,370 @@
+use std::fmt;
+
+use crypto::util::fixed_time_eq;
+use openssl::error::ErrorStack;
+use openssl::rand::rand_bytes;
+use openssl::symm::{self, Cipher, Crypter, Mode};
+
+/// 密钥字节数。
+pub const KEY_LEN: usize = 16;
+
+/// CBC 模式 IV 的字节数。
+pub const IV_LEN: usize = 16;
+
+/// GCM 模式随机数的字节数。
+pub const NONCE_LEN: usize = 12;
+
+/// GCM 模式认证标签的字节数。
+pub const TAG_LEN: usize = 16;
+
+/// 分组字节数。
+const BLOCK_LEN: usize = 16;
+
+/// GCM 单次最多加密的字节数，NIST SP 800-38D 规定为 (2^32 - 2) 个分组，超过后计数器回绕会重复使用密钥流。
+pub const GCM_MAX_LEN: u64 = ((1 << 32) - 2) * BLOCK_LEN as u64;
+
+/// 计数器模式每批加密的分组数，避免按数据长度分配计数器缓冲区。
+const CTR_BATCH_BLOCKS: usize = 256;
+
+/// GF(2^128) 的约化多项式。
+const GHASH_R: u128 = 0xe1 << 120;
+
+/// SM4 加密错误。
+#[derive(Debug)]
+pub enum Sm4Error {
+    /// 密钥长度不是16字节
+    InvalidKeyLength(usize),
+    /// IV 或随机数长度不正确
+    InvalidIvLength { expected: usize, actual: usize },
+    /// 数据超过 GCM 单次加密的上限 `GCM_MAX_LEN`
+    DataTooLong(usize),
+    /// 解密失败：密钥错误、填充错误，或者密文、标签、附加认证数据被篡改
+    DecryptFailed,
+    /// OpenSSL 返回的其他错误，例如系统的 OpenSSL 未启用 SM4
+    OpenSsl(ErrorStack),
+}
+
+impl fmt::Display for Sm4Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sm4Error::InvalidKeyLength(len) => write!(f, "SM4 密钥长度必须为{}字节，实际为{}字节", KEY_LEN, len),
+            Sm4Error::InvalidIvLength { expected, actual } => {
+                write!(f, "IV 长度必须为{}字节，实际为{}字节", expected, actual)
+            }
+            Sm4Error::DataTooLong(len) => write!(f, "数据长度为{}字节，GCM 单次最多处理{}字节", len, GCM_MAX_LEN),
+            Sm4Error::DecryptFailed => write!(f, "解密失败，密钥错误或数据已被篡改"),
+            Sm4Error::OpenSsl(e) => write!(f, "OpenSSL 错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Sm4Error {}
+
+impl From<ErrorStack> for Sm4Error {
+    fn from(e: ErrorStack) -> Self {
+        Sm4Error::OpenSsl(e)
+    }
+}
+
+/// 生成随机的 SM4 密钥。
+///
+/// 返回值：Result<Vec<u8>, Sm4Error> - 16字节的随机密钥
+pub fn generate_key() -> Result<Vec<u8>, Sm4Error> {
+    random_bytes(KEY_LEN)
+}
+
+/// 生成 CBC 模式使用的随机 IV。
+///
+/// 返回值：Result<Vec<u8>, Sm4Error> - 16字节的随机 IV
+pub fn generate_iv() -> Result<Vec<u8>, Sm4Error> {
+    random_bytes(IV_LEN)
+}
+
+/// 生成 GCM 模式使用的随机数，同一个密钥下随机数绝不能重复使用。
+///
+/// 返回值：Result<Vec<u8>, Sm4Error> - 12字节的随机数
+pub fn generate_nonce() -> Result<Vec<u8>, Sm4Error> {
+    random_bytes(NONCE_LEN)
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, Sm4Error> {
+    let mut buf = vec![0; len];
+    rand_bytes(&mut buf)?;
+    Ok(buf)
+}
+
+/// SM4 分组密码（GB/T 32907-2016），支持 ECB、CBC 和 GCM 模式。
+///
+/// ECB 和 CBC 模式使用 PKCS#7 填充，与 Java 的 `SM4/ECB/PKCS5Padding`、`SM4/CBC/PKCS5Padding` 兼容，但不能发现篡改；
+/// ECB 模式还会暴露相同的明文分组，仅用于对接旧系统。新代码应使用 GCM 模式。
+#[derive(Clone)]
+pub struct Sm4 {
+    key: [u8; KEY_LEN],
+}
+
+impl Sm4 {
+    /// 使用指定的密钥创建 SM4 加密器。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: &[u8] - 16字节的密钥
+    ///
+    /// 返回值：Result<Sm4, Sm4Error> - 加密器
+    pub fn new(key: &[u8]) -> Result<Self, Sm4Error> {
+        let key = key.try_into().map_err(|_| Sm4Error::InvalidKeyLength(key.len()))?;
+        Ok(Sm4 { key })
+    }
+
+    /// 使用 ECB 模式加密。
+    ///
+    /// # 参数
+    ///
+    /// * `plaintext`: &[u8] - 明文
+    ///
+    /// 返回值：Result<Vec<u8>, Sm4Error> - 密文，长度为16的整数倍
+    pub fn encrypt_ecb(&self, plaintext: &[u8]) -> Result<Vec<u8>, Sm4Error> {
+        Ok(symm::encrypt(Cipher::sm4_ecb(), &self.key, None, plaintext)?)
+    }
+
+    /// 使用 ECB 模式解密。
+    ///
+    /// # 参数
+    ///
+    /// * `ciphertext`: &[u8] - 密文
+    ///
+    /// 返回值：Result<Vec<u8>, Sm4Error> - 明文，填充错误时返回 `DecryptFailed`
+    pub fn decrypt_ecb(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Sm4Error> {
+        symm::decrypt(Cipher::sm4_ecb(), &self.key, None, ciphertext).map_err(|_| Sm4Error::DecryptFailed)
+    }
+
+    /// 使用 CBC 模式加密。
+    ///
+    /// # 参数
+    ///
+    /// * `iv`: &[u8] - 16字节的 IV，可以使用 `generate_iv` 生成
+    /// * `plaintext`: &[u8] - 明文
+    ///
+    /// 返回值：Result<Vec<u8>, Sm4Error> - 密文，长度为16的整数倍，不包含 IV
+    pub fn encrypt_cbc(&self, iv: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Sm4Error> {
+        check_iv(iv, IV_LEN)?;
+        Ok(symm::encrypt(Cipher::sm4_cbc(), &self.key, Some(iv), plaintext)?)
+    }
+
+    /// 使用 CBC 模式解密。
+    ///
+    /// # 参数
+    ///
+    /// * `iv`: &[u8] - 加密时使用的 IV
+    /// * `ciphertext`: &[u8] - 密文
+    ///
+    /// 返回值：Result<Vec<u8>, Sm4Error> - 明文，填充错误时返回 `DecryptFailed`
+    pub fn decrypt_cbc(&self, iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Sm4Error> {
+        check_iv(iv, IV_LEN)?;
+        // 不区分失败原因，以免成为填充预言
+        symm::decrypt(Cipher::sm4_cbc(), &self.key, Some(iv), ciphertext).map_err(|_| Sm4Error::DecryptFailed)
+    }
+
+    /// 使用 GCM 模式加密（RFC 8998）。
+    ///
+    /// 部分发行版的 OpenSSL 没有提供 SM4-GCM，这里按 NIST SP 800-38D 在 SM4 分组加密之上实现。
+    ///
+    /// # 参数
+    ///
+    /// * `nonce`: &[u8] - 12字节的随机数，可以使用 `generate_nonce` 生成
+    /// * `plaintext`: &[u8] - 明文
+    /// * `aad`: &[u8] - 附加认证数据，只参与认证而不加密
+    ///
+    /// 返回值：Result<Vec<u8>, Sm4Error> - 密文后接16字节的认证标签，明文超过 `GCM_MAX_LEN` 时返回 `DataTooLong`
+    pub fn encrypt_gcm(&self, nonce: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, Sm4Error> {
+        check_iv(nonce, NONCE_LEN)?;
+        check_gcm_len(plaintext.len())?;
+        let mut out = self.gcm_ctr(nonce, plaintext)?;
+        let tag = self.gcm_tag(nonce, aad, &out)?;
+        out.extend_from_slice(&tag);
+        Ok(out)
+    }
+
+    /// 使用 GCM 模式解密并验证认证标签。
+    ///
+    /// # 参数
+    ///
+    /// * `nonce`: &[u8] - 加密时使用的随机数
+    /// * `ciphertext`: &[u8] - `encrypt_gcm` 的结果，即密文后接认证标签
+    /// * `aad`: &[u8] - 加密时使用的附加认证数据
+    ///
+    /// 返回值：Result<Vec<u8>, Sm4Error> - 明文，密钥错误或任何数据被篡改时返回 `DecryptFailed`
+    pub fn decrypt_gcm(&self, nonce: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, Sm4Error> {
+        check_iv(nonce, NONCE_LEN)?;
+        if ciphertext.len() < TAG_LEN {
+            return Err(Sm4Error::DecryptFailed);
+        }
+        let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - TAG_LEN);
+        check_gcm_len(ciphertext.len())?;
+        // 先验证标签，未通过认证的数据不解密
+        if !fixed_time_eq(&self.gcm_tag(nonce, aad, ciphertext)?, tag) {
+            return Err(Sm4Error::DecryptFailed);
+        }
+        self.gcm_ctr(nonce, ciphertext)
+    }
+
+    /// 不填充地加密整数个分组。
+    fn encrypt_blocks(&self, blocks: &[u8]) -> Result<Vec<u8>, Sm4Error> {
+        let mut crypter = Crypter::new(Cipher::sm4_ecb(), Mode::Encrypt, &self.key, None)?;
+        crypter.pad(false);
+        let mut out = vec![0; blocks.len() + BLOCK_LEN];
+        let mut len = crypter.update(blocks, &mut out)?;
+        len += crypter.finalize(&mut out[len..])?;
+        out.truncate(len);
+        Ok(out)
+    }
+
+    /// 返回第 `counter` 个计数器分组，计数器1用于生成认证标签，从2开始用于加密数据。
+    fn counter_block(nonce: &[u8], counter: u32) -> [u8; BLOCK_LEN] {
+        let mut block = [0; BLOCK_LEN];
+        block[..NONCE_LEN].copy_from_slice(nonce);
+        block[NONCE_LEN..].copy_from_slice(&counter.to_be_bytes());
+        block
+    }
+
+    /// GCM 的计数器模式加解密，调用方需要先用 `check_gcm_len` 检查长度，保证计数器不回绕。
+    fn gcm_ctr(&self, nonce: &[u8], data: &[u8]) -> Result<Vec<u8>, Sm4Error> {
+        let mut crypter = Crypter::new(Cipher::sm4_ecb(), Mode::Encrypt, &self.key, None)?;
+        crypter.pad(false);
+        let mut counters = Vec::with_capacity(CTR_BATCH_BLOCKS * BLOCK_LEN);
+        let mut keystream = vec![0; (CTR_BATCH_BLOCKS + 1) * BLOCK_LEN];
+        let mut counter: u32 = 2;
+        let mut out = Vec::with_capacity(data.len());
+        for chunk in data.chunks(CTR_BATCH_BLOCKS * BLOCK_LEN) {
+            counters.clear();
+            for _ in 0..chunk.len().div_ceil(BLOCK_LEN) {
+                counters.extend_from_slice(&Sm4::counter_block(nonce, counter));
+                counter = counter.wrapping_add(1);
+            }
+            let len = crypter.update(&counters, &mut keystream)?;
+            out.extend(chunk.iter().zip(&keystream[..len]).map(|(byte, key)| byte ^ key));
+        }
+        Ok(out)
+    }
+
+    /// 计算附加认证数据和密文的认证标签。
+    fn gcm_tag(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<[u8; TAG_LEN], Sm4Error> {
+        let mut input = [0; BLOCK_LEN * 2];
+        input[BLOCK_LEN..].copy_from_slice(&Sm4::counter_block(nonce, 1));
+        let encrypted = self.encrypt_blocks(&input)?;
+        let h = u128::from_be_bytes(encrypted[..BLOCK_LEN].try_into().expect("分组长度为16字节"));
+        let mask = u128::from_be_bytes(encrypted[BLOCK_LEN..].try_into().expect("分组长度为16字节"));
+
+        let mut y = 0;
+        for data in [aad, ciphertext] {
+            for chunk in data.chunks(BLOCK_LEN) {
+                let mut block = [0; BLOCK_LEN];
+                block[..chunk.len()].copy_from_slice(chunk);
+                y = gf_mul(y ^ u128::from_be_bytes(block), h);
+            }
+        }
+        let lengths = ((aad.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+        y = gf_mul(y ^ lengths, h);
+        Ok((y ^ mask).to_be_bytes())
+    }
+}
+
+/// GF(2^128) 上的乘法，按位处理且不依赖数据分支，耗时与输入无关。
+fn gf_mul(x: u128, y: u128) -> u128 {
+    let mut z = 0;
+    let mut v = y;
+    for i in 0..128 {
+        z ^= v & 0u128.wrapping_sub((x >> (127 - i)) & 1);
+        v = (v >> 1) ^ (GHASH_R & 0u128.wrapping_sub(v & 1));
+    }
+    z
+}
+
+impl fmt::Debug for Sm4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 不输出密钥内容
+        f.write_str("Sm4")
+    }
+}
+
+fn check_gcm_len(len: usize) -> Result<(), Sm4Error> {
+    if len as u64 > GCM_MAX_LEN {
+        return Err(Sm4Error::DataTooLong(len));
+    }
+    Ok(())
+}
+
+fn check_iv(iv: &[u8], expected: usize) -> Result<(), Sm4Error> {
+    if iv.len() != expected {
+        return Err(Sm4Error::InvalidIvLength { expected, actual: iv.len() });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure::secure::SecureUtil;
+
+    fn hex(input: &str) -> Vec<u8> {
+        SecureUtil::from_hex(input).unwrap()
+    }
+
+    #[test]
+    fn test_sm4_standard_vectors() {
+        // GB/T 32907-2016 附录A 示例1
+        let key = hex("0123456789abcdeffedcba9876543210");
+        let sm4 = Sm4::new(&key).unwrap();
+        let ciphertext = sm4.encrypt_ecb(&key).unwrap();
+        assert_eq!(SecureUtil::to_hex(&ciphertext[..16]), "681edf34d206965e86b3e94f536e4246");
+        assert_eq!(ciphertext.len(), 32);
+        assert_eq!(sm4.decrypt_ecb(&ciphertext).unwrap(), key);
+        let ciphertext = sm4.encrypt_cbc(&[0; IV_LEN], &key).unwrap();
+        assert_eq!(SecureUtil::to_hex(&ciphertext[..16]), "681edf34d206965e86b3e94f536e4246");
+
+        // RFC 8998 附录A.1
+        let nonce = hex("00001234567800000000abcd");
+        let aad = hex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let plaintext = hex(&["aa", "bb", "cc", "dd", "ee", "ff", "ee", "aa"].map(|b| b.repeat(8)).concat());
+        let ciphertext = sm4.encrypt_gcm(&nonce, &plaintext, &aad).unwrap();
+        assert_eq!(
+            SecureUtil::to_hex(&ciphertext),
+            concat!(
+                "17f399f08c67d5ee19d0dc9969c4bb7d5fd46fd3756489069157b282bb200735",
+                "d82710ca5c22f0ccfa7cbf93d496ac15a56834cbcf98c397b4024a2691233b8d",
+                "83de3541e4c2b58177e065a9bf7b62ec"
+            )
+        );
+        assert_eq!(sm4.decrypt_gcm(&nonce, &ciphertext, &aad).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_sm4_round_trip_and_errors() {
+        let sm4 = Sm4::new(&generate_key().unwrap()).unwrap();
+        let iv = generate_iv().unwrap();
+        let nonce = generate_nonce().unwrap();
+        for plaintext in [&b""[..], b"abc", &[7; 16], &[9; 1000], &[5; CTR_BATCH_BLOCKS * BLOCK_LEN + 17]] {
+            assert_eq!(sm4.decrypt_ecb(&sm4.encrypt_ecb(plaintext).unwrap()).unwrap(), plaintext);
+            assert_eq!(sm4.decrypt_cbc(&iv, &sm4.encrypt_cbc(&iv, plaintext).unwrap()).unwrap(), plaintext);
+            let sealed = sm4.encrypt_gcm(&nonce, plaintext, b"id").unwrap();
+            assert_eq!(sealed.len(), plaintext.len() + TAG_LEN);
+            assert_eq!(sm4.decrypt_gcm(&nonce, &sealed, b"id").unwrap(), plaintext);
+        }
+
+        let mut sealed = sm4.encrypt_gcm(&nonce, b"secret", b"id").unwrap();
+        assert!(matches!(sm4.decrypt_gcm(&nonce, &sealed, b"other"), Err(Sm4Error::DecryptFailed)));
+        sealed[0] ^= 1;
+        assert!(matches!(sm4.decrypt_gcm(&nonce, &sealed, b"id"), Err(Sm4Error::DecryptFailed)));
+        assert!(matches!(sm4.decrypt_gcm(&nonce, &sealed[..TAG_LEN - 1], b"id"), Err(Sm4Error::DecryptFailed)));
+
+        assert!(matches!(Sm4::new(&[0; 32]), Err(Sm4Error::InvalidKeyLength(32))));
+        assert!(matches!(
+            sm4.encrypt_cbc(&nonce, b"data"),
+            Err(Sm4Error::InvalidIvLength { expected: IV_LEN, actual: NONCE_LEN })
+        ));
+        assert!(matches!(sm4.decrypt_ecb(&[0; 15]), Err(Sm4Error::DecryptFailed)));
+
+        // 不能实际分配64GiB，直接检查长度上限
+        assert!(check_gcm_len(GCM_MAX_LEN as usize).is_ok());
+        assert!(matches!(check_gcm_len(GCM_MAX_LEN as usize + 1), Err(Sm4Error::DataTooLong(_))));
+    }
+}